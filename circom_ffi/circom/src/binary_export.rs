@@ -0,0 +1,372 @@
+// Compact binary encodings of the constraints of a circuit & of its
+// evaluations, the alternative to the json ones for circuits too large
+// to be shared & parsed as json.
+//
+// The layout follows the iden3 binary formats (wtns, r1cs), little endian:
+//   magic | format version (u32) | number of sections (u32) | sections
+// a section is its type (u32), the size of its content (u64) & the content.
+// Field elements are n8 bytes wide, n8 being the size of the prime rounded up
+// to a multiple of 8 bytes, & always hold their canonical value in [0, prime).
+//
+// The constraints ("vcon") don't change between evaluations, so they are
+// shared once per compiled circuit:
+//   1 header:      n8 (u32) | prime | no. of wires (u64) | no. of constraints (u64)
+//   2 constraints: per constraint, a, b & c, each a number of terms (u32)
+//                  followed by the terms, a wire (u32) & its coefficient
+//   3 symbols:     the constrained then the unconstrained symbols, each list a
+//                  number of symbols (u64) followed by the symbols, their
+//                  original, witness & node id (i64) & their name (u32 length & utf-8)
+//
+// An evaluation ("veva") refers to the constraints by their position:
+//   1 header:      n8 (u32) | prime | no. of assignments (u64) | no. of records (u64)
+//   2 assignments: the assignment of every wire, in witness order
+//   3 records:     per record, the position of its constraint (u64), then
+//                  the values of a, b, c & a * b - c
+//   4 satisfied:   bitmap of the records, bit i % 8 of byte i / 8 is set
+//                  when the i-th record is satisfied
+//   5 reports:     json array of {"id", "report"}, one per unsatisfied record
+//   6 values:      json object of the "public" signals, the "outputs" & the "logs"
+//
+// The bytes are emitted in chunks as the constraints are read or evaluated,
+// so neither encoding is ever held in a single buffer on either side of the ffi.
+pub extern crate num_bigint_dig as num_bigint;
+
+use jsonbb::Builder;
+use num_bigint::BigInt;
+
+use crate::circuit::CircuitLibrary;
+use crate::constraint_system::EvalScope;
+use crate::json_export::{logs_json, public_json, report_json};
+use crate::witness::Logs;
+
+const CONSTRAINTS_MAGIC: &[u8] = b"vcon";
+const CONSTRAINTS_VERSION: u32 = 1;
+const CONSTRAINTS_SECTIONS: u32 = 3;
+const EVALUATION_MAGIC: &[u8] = b"veva";
+const EVALUATION_VERSION: u32 = 2;
+const EVALUATION_SECTIONS: u32 = 6;
+
+/// Size of the chunks shared through the ffi.
+pub const CHUNK_SIZE: usize = 1 << 20;
+
+struct ChunkWriter<F: FnMut(&[u8])> {
+    chunk: Vec<u8>,
+    chunk_size: usize,
+    emit: F,
+    n8: usize,
+    field: BigInt,
+}
+
+impl<F: FnMut(&[u8])> ChunkWriter<F> {
+    fn write(&mut self, bytes: &[u8]) {
+        self.chunk.extend_from_slice(bytes);
+        if self.chunk.len() >= self.chunk_size {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.chunk.is_empty() {
+            (self.emit)(&self.chunk);
+            self.chunk.clear();
+        }
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    fn write_section(&mut self, kind: u32, size: usize) {
+        self.write_u32(kind);
+        self.write_usize(size);
+    }
+
+    fn write_element(&mut self, value: &BigInt) {
+        let value = ((value % &self.field) + &self.field) % &self.field;
+        let (_, mut bytes) = value.to_bytes_le();
+        bytes.resize(self.n8, 0);
+        self.write(&bytes);
+    }
+
+    fn write_preamble(&mut self, magic: &[u8], version: u32, no_sections: u32) {
+        self.write(magic);
+        self.write_u32(version);
+        self.write_u32(no_sections);
+    }
+
+    // the header section, n8 & the prime followed by two counts
+    fn write_header(&mut self, first: usize, second: usize) {
+        let n8 = self.n8;
+        self.write_section(1, 4 + n8 + 16);
+        self.write_u32(n8 as u32);
+        let (_, mut prime) = self.field.to_bytes_le();
+        prime.resize(n8, 0);
+        self.write(&prime);
+        self.write_usize(first);
+        self.write_usize(second);
+    }
+}
+
+fn chunk_writer<F: FnMut(&[u8])>(
+    library: &CircuitLibrary,
+    chunk_size: usize,
+    emit: F,
+) -> ChunkWriter<F> {
+    let field = library.field().clone();
+    let n8 = field.bits().div_ceil(64) * 8;
+    ChunkWriter { chunk: Vec::with_capacity(chunk_size), chunk_size, emit, n8, field }
+}
+
+/// Encodes the constraints & the symbols of the library in the binary
+/// format above, handing it to emit in chunks of about chunk_size bytes.
+pub fn produce_constraints_binary<F: FnMut(&[u8])>(
+    library: &CircuitLibrary,
+    chunk_size: usize,
+    emit: F,
+) {
+    let cs = library.get_constraint_system();
+    let mut w = chunk_writer(library, chunk_size, emit);
+    let n8 = w.n8;
+
+    w.write_preamble(CONSTRAINTS_MAGIC, CONSTRAINTS_VERSION, CONSTRAINTS_SECTIONS);
+    w.write_header(cs.no_wires, cs.constraints.len());
+
+    let terms = cs.constraints.iter().map(|c| c.a().len() + c.b().len() + c.c().len());
+    w.write_section(2, cs.constraints.len() * 3 * 4 + terms.sum::<usize>() * (4 + n8));
+    for c in &cs.constraints {
+        for lc in [c.a(), c.b(), c.c()] {
+            w.write_u32(lc.len() as u32);
+            for (wire, coefficient) in lc {
+                w.write_u32(*wire as u32);
+                w.write_element(coefficient);
+            }
+        }
+    }
+
+    let (constrained, unconstrained) = library.get_signals();
+    let symbols_size: usize = [&constrained, &unconstrained]
+        .iter()
+        .map(|symbols| 8 + symbols.iter().map(|s| 3 * 8 + 4 + s.symbol.len()).sum::<usize>())
+        .sum();
+    w.write_section(3, symbols_size);
+    for symbols in [&constrained, &unconstrained] {
+        w.write_usize(symbols.len());
+        for s in symbols.iter() {
+            for id in [s.original, s.witness, s.node_id] {
+                w.write(&id.to_le_bytes());
+            }
+            w.write_u32(s.symbol.len() as u32);
+            w.write(s.symbol.as_bytes());
+        }
+    }
+
+    w.flush();
+}
+
+/// Encodes an evaluation of the library in the binary format above, the
+/// constraints of the scope are evaluated against the assignments as they
+/// are written & handed to emit in chunks of about chunk_size bytes.
+pub fn produce_constraint_evaluation_binary<F: FnMut(&[u8])>(
+    library: &CircuitLibrary,
+    assignments: &[BigInt],
+    scope: &EvalScope,
+    logs: &Logs,
+    chunk_size: usize,
+    emit: F,
+) {
+    let cs = library.get_constraint_system();
+    let mut w = chunk_writer(library, chunk_size, emit);
+    let n8 = w.n8;
+
+    // failing fast records a constraint at most, which is looked for
+    // before the header declares the number of records
+    let failed: Option<Vec<usize>> =
+        scope.fail_fast().then(|| cs.recorded(assignments, scope).collect());
+    let no_records =
+        failed.as_ref().map_or_else(|| cs.recorded(assignments, scope).count(), Vec::len);
+    let recorded: Box<dyn Iterator<Item = usize>> = match failed {
+        Some(failed) => Box::new(failed.into_iter()),
+        None => Box::new(cs.recorded(assignments, scope)),
+    };
+
+    w.write_preamble(EVALUATION_MAGIC, EVALUATION_VERSION, EVALUATION_SECTIONS);
+    w.write_header(assignments.len(), no_records);
+
+    w.write_section(2, assignments.len() * n8);
+    for assignment in assignments {
+        w.write_element(assignment);
+    }
+
+    let mut satisfied = vec![0u8; no_records.div_ceil(8)];
+    let mut reports = Builder::<Vec<u8>>::new();
+    reports.begin_array();
+    w.write_section(3, no_records * (8 + 4 * n8));
+    for (i, id) in recorded.enumerate() {
+        let c = &cs.constraints[id];
+        let values = cs.values(assignments, c);
+        w.write_usize(id);
+        for value in &values {
+            w.write_element(value);
+        }
+        if values[3] == BigInt::from(0) {
+            satisfied[i / 8] |= 1 << (i % 8);
+        } else if let Some(report) = cs.record(id, assignments, c).report {
+            reports.begin_object();
+            reports.add_string("id");
+            reports.add_u64(id as u64);
+            reports.add_string("report");
+            report_json(&report, &mut reports);
+            reports.end_object();
+        }
+    }
+    reports.end_array();
+
+    w.write_section(4, satisfied.len());
+    w.write(&satisfied);
+
+    let reports = reports.finish().to_string();
+    w.write_section(5, reports.len());
+    w.write(reports.as_bytes());
+
+    let mut builder = Builder::<Vec<u8>>::new();
+    builder.begin_object();
+    public_json(&library.get_public_signals(), assignments, &mut builder);
+    logs_json(logs, &mut builder);
+    builder.end_object();
+    let values = builder.finish().to_string();
+    w.write_section(6, values.len());
+    w.write(values.as_bytes());
+
+    w.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::CircuitPkg;
+    use crate::constraint_system::EvalOptions;
+    use crate::fixtures::{compiled, main_pkg};
+    use std::convert::TryInto;
+
+    #[test]
+    fn binary_evaluation_in_chunks() {
+        let src = indoc::indoc! {"
+            template Square(){
+                signal input a;
+                signal input b;
+                signal output out;
+                out <== a * a;
+                out === b * b;
+            }
+            component main = Square();
+        "};
+        let pkg = CircuitPkg { field: "goldilocks".to_string(), ..main_pkg(src) };
+        let library = compiled(&pkg);
+        let inputs = r#"{"a": 3, "b": 10}"#;
+        // the broken constraint is reported, not trapped on
+        let options = EvalOptions { skip_constraint_asserts: true, ..Default::default() };
+        let Ok((witness, records, _)) = library.execute_with(inputs, &options) else {
+            panic!("execution failed")
+        };
+        let encode = |options: &EvalOptions, chunk_size: usize| {
+            let Ok((witness, scope, logs)) = library.prepare_evaluation(inputs, options) else {
+                panic!("execution failed")
+            };
+            let mut chunks: Vec<Vec<u8>> = Vec::new();
+            produce_constraint_evaluation_binary(
+                &library,
+                &witness,
+                &scope,
+                &logs,
+                chunk_size,
+                |chunk| chunks.push(chunk.to_vec()),
+            );
+            chunks
+        };
+        let chunks = encode(&options, 16);
+        assert!(chunks.len() > 1);
+        assert!(chunks[..chunks.len() - 1].iter().all(|chunk| chunk.len() >= 16));
+        let bin = chunks.concat();
+        assert_eq!(encode(&options, CHUNK_SIZE), vec![bin.clone()]);
+
+        let u32_at = |bin: &[u8], i: usize| u32::from_le_bytes(bin[i..i + 4].try_into().unwrap());
+        let u64_at =
+            |bin: &[u8], i: usize| u64::from_le_bytes(bin[i..i + 8].try_into().unwrap()) as usize;
+        // the kind, start & size of every section
+        let sections = |bin: &[u8]| {
+            let mut sections = Vec::new();
+            let mut at = 12;
+            while at < bin.len() {
+                sections.push((u32_at(bin, at), at + 12, u64_at(bin, at + 4)));
+                at += 12 + u64_at(bin, at + 4);
+            }
+            assert_eq!(at, bin.len());
+            assert_eq!(sections.len(), u32_at(bin, 8) as usize);
+            sections
+        };
+        assert_eq!(&bin[0..4], b"veva");
+        assert_eq!(u32_at(&bin, 4), 2);
+        let evaluation = sections(&bin);
+        assert_eq!(evaluation.iter().map(|s| s.0).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6]);
+
+        // header: n8, prime, number of assignments & records
+        let header = evaluation[0].1;
+        assert_eq!(u32_at(&bin, header), 8);
+        assert_eq!(BigInt::from(u64_at(&bin, header + 4)), *library.field());
+        assert_eq!(u64_at(&bin, header + 12), witness.len());
+        assert_eq!(u64_at(&bin, header + 20), records.len());
+
+        let assignments = evaluation[1].1;
+        for (i, value) in witness.iter().enumerate() {
+            assert_eq!(&BigInt::from(u64_at(&bin, assignments + 8 * i)), value);
+        }
+        // the records only hold the position of the constraint & the values
+        assert_eq!(evaluation[2].2, records.len() * (8 + 4 * 8));
+        let satisfied = bin[evaluation[3].1];
+        for (i, r) in records.iter().enumerate() {
+            let record = evaluation[2].1 + i * (8 + 4 * 8);
+            assert_eq!(u64_at(&bin, record), r.id);
+            assert_eq!(u64_at(&bin, record + 8 + 3 * 8) == 0, r.report.is_none());
+            assert_eq!(satisfied & (1 << i) != 0, r.report.is_none());
+        }
+
+        let json = |section: &(u32, usize, usize)| {
+            serde_json::from_slice::<serde_json::Value>(&bin[section.1..section.1 + section.2])
+                .unwrap()
+        };
+        let reports = json(&evaluation[4]);
+        assert_eq!(reports.as_array().unwrap().len(), 1);
+        assert_eq!(reports[0]["report"]["message"], "Constraint is not satisfied");
+        assert_eq!(json(&evaluation[5])["outputs"]["out"], "9");
+
+        // failing fast declares the single record it holds
+        let failing = EvalOptions { fail_fast: true, ..options.clone() };
+        let bin = encode(&failing, CHUNK_SIZE).concat();
+        let evaluation = sections(&bin);
+        assert_eq!(u64_at(&bin, evaluation[0].1 + 20), 1);
+        assert_eq!(evaluation[2].2, 8 + 4 * 8);
+
+        // the coefficients & the symbols are shared apart, once per circuit
+        let mut bin = Vec::new();
+        produce_constraints_binary(&library, 16, |chunk| bin.extend_from_slice(chunk));
+        assert_eq!(&bin[0..4], b"vcon");
+        assert_eq!(u32_at(&bin, 4), 1);
+        let constraints = sections(&bin);
+        assert_eq!(constraints.iter().map(|s| s.0).collect::<Vec<_>>(), vec![1, 2, 3]);
+        let cs = library.get_constraint_system();
+        assert_eq!(u64_at(&bin, constraints[0].1 + 12), cs.no_wires);
+        assert_eq!(u64_at(&bin, constraints[0].1 + 20), cs.constraints.len());
+        let first = constraints[1].1;
+        assert_eq!(u32_at(&bin, first) as usize, cs.constraints[0].a().len());
+        let (constrained, _) = library.get_signals();
+        assert_eq!(u64_at(&bin, constraints[2].1), constrained.len());
+    }
+}
@@ -1,28 +1,45 @@
 pub extern crate num_bigint_dig as num_bigint;
 pub extern crate num_traits;
 use constraint_writers::sym_writer::SymElem;
+use constraint_writers::wtns_writer::WtnsWriter;
 use num_bigint::BigInt;
+use num::Integer;
 
 use crate::constraint_system::*;
+use crate::snapshot::{SnapshotReader, SnapshotWriter};
+use crate::underconstrained::{Counterexample, MutationOptions};
+use crate::fuzz::{FuzzOptions, Fuzzing};
+use crate::statistics::TemplateStatistics;
+use crate::witness::{InputSignal, Logs};
+use circom_algebra::modular_arithmetic::is_probable_prime;
 use compiler::compiler_interface::{Circuit, CompilationFlags};
+use code_producers::wasm_elements::custom_field;
 
-use parser::{apply_sugar, generate_ast};
+use parser::{
+    apply_sugar, check_custom_gates_pragmas, check_custom_gates_version, check_number_version,
+    generate_ast, normalize_path, parse_number_version, resolve_include, run_parser, IncludesGraph,
+    Version,
+};
 use type_analysis::check_types::check_types;
 use program_structure::{
-    ast::Definition,
+    ast::{Definition, FillMeta, Meta, Statement, AST},
     ast::MainComponent,
     constants::UsefulConstants,
     error_code::ReportCode,
     error_definition::{Report, ReportCollection},
-    file_definition::{FileID, FileLibrary},
+    file_definition::{FileID, FileLibrary, FileLocation},
     program_archive::ProgramArchive,
 };
 
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use constraint_generation::{FlagsExecution, execute::constraint_execution};
 use compiler::compiler_interface::VCP;
 
-use dag::SimplificationFlags;
+use dag::{DAGStatistics, SimplificationFlags};
+use constraint_list::ConstraintList;
 
 use serde::Deserialize;
 use ansi_term::Colour;
@@ -40,6 +57,67 @@ pub struct CircuitPkg {
     pub target_version: String,
     pub field: String,
     pub programs: Programs,
+    #[serde(default)]
+    pub optimization: Optimization,
+    // the unsatisfied constraints also point to the constraints merged into them
+    #[serde(default)]
+    pub provenance: bool,
+}
+
+/// Simplification levels of the constraints, as circom's --O0, --O1 & --O2.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum OptimizationLevel {
+    // no simplification, every signal & constraint is kept
+    O0,
+    // the equalities & the constant signals are simplified
+    O1,
+    // the linear constraints are simplified as well
+    O2,
+}
+
+/// How the constraints of a pkg are simplified.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct Optimization {
+    pub level: OptimizationLevel,
+    // rounds of linear simplification at O2 (--O2round), a single one when not given
+    pub rounds: Option<usize>,
+    // the heuristics of circom before 2.1.6 to pick the linear substitutions
+    pub old_heuristics: bool,
+}
+
+impl Default for Optimization {
+    fn default() -> Self {
+        Optimization { level: OptimizationLevel::O1, rounds: None, old_heuristics: false }
+    }
+}
+
+impl Optimization {
+    // the rounds only apply to O2, which takes one at least
+    fn check(&self) -> Result<(), Report> {
+        let unsupported = |msg: String| {
+            crate::reporting::produce_report_with_message(ReportCode::UnsupportedOptimization, msg)
+        };
+        match (self.level, self.rounds) {
+            (OptimizationLevel::O2, Some(0)) => Err(unsupported("O2 in 0 rounds".to_string())),
+            (OptimizationLevel::O2, _) | (_, None) => Ok(()),
+            (level, Some(rounds)) => Err(unsupported(format!("{:?} in {} rounds", level, rounds))),
+        }
+    }
+
+    fn flags(&self, prime: &str, track_origins: bool) -> SimplificationFlags {
+        SimplificationFlags {
+            no_rounds: self.rounds.unwrap_or(1),
+            flag_f: self.level == OptimizationLevel::O0,
+            flag_s: self.level != OptimizationLevel::O2,
+            parallel_flag: false,
+            port_substitution: false,
+            flag_old_heuristics: self.old_heuristics,
+            track_origins,
+            prime: prime.to_string(),
+            json_substitutions: "".to_string(),
+        }
+    }
 }
 
 impl Default for CircuitPkg {
@@ -48,16 +126,44 @@ impl Default for CircuitPkg {
             target_version: "2.2.0".to_string(),
             field: "bn128".to_string(),
             programs: Vec::new(),
+            optimization: Optimization::default(),
+            provenance: false,
         }
     }
 }
 
+// a main compiled from the .circom files on disk, as circom does
+#[derive(Deserialize)]
+pub struct CircuitFile {
+    pub target_version: String,
+    pub field: String,
+    // path of the file declaring the main component
+    pub root: String,
+    // directories searched for the includes after the one of the including file
+    #[serde(default)]
+    pub libraries: Vec<String>,
+    #[serde(default)]
+    pub optimization: Optimization,
+    #[serde(default)]
+    pub provenance: bool,
+}
+
 pub fn create_default_circuit_pkg(programs: &Programs) -> CircuitPkg {
     let pkg = CircuitPkg::default();
     CircuitPkg { programs: programs.to_vec(), ..pkg }
 }
 
 pub type Definitions = Vec<(FileID, Vec<Definition>)>;
+
+// an assert of the circuit, as the witness calculation names it
+// when it fails: "template Name" or "function Name" & the line
+struct AssertSite {
+    component: String,
+    line: usize,
+    file_id: FileID,
+    location: FileLocation,
+}
+pub type Evaluation = Result<(Vec<BigInt>, LCRecords, Logs), ReportCollection>;
 pub type MainComponents = Vec<(FileID, MainComponent, bool)>;
 
 pub struct ParserOutput {
@@ -79,8 +185,19 @@ pub struct CircuitLibrary {
 
     catalog: Vec<(FileID, String)>,
 
-    wc: crate::witness::WitnessCalculator,
+    wc: crate::witness::WitnessPool,
     constraint_system: ConstraintSystem,
+    // simplified constraints, kept around for the r1cs export
+    constraint_list: Option<ConstraintList>,
+    // r1cs of a restored library, which has no constraint list
+    r1cs: Option<Vec<u8>>,
+    custom_gates: bool,
+    // asserts of the compiled templates & functions
+    asserts: Vec<AssertSite>,
+    // input signals of the main component
+    signature: Vec<InputSignal>,
+    // nodes of the dag of the main, as generated, none for a restored library
+    statistics: Option<DAGStatistics>,
 
     inner: FileLibrary,
 }
@@ -93,13 +210,21 @@ impl Default for CircuitLibrary {
             catalog: Vec::new(),
             inner: FileLibrary::new(),
             constraint_system: ConstraintSystem::default(),
-            wc: crate::witness::WitnessCalculator::default(),
+            constraint_list: None,
+            r1cs: None,
+            custom_gates: false,
+            asserts: Vec::new(),
+            signature: Vec::new(),
+            statistics: None,
+            wc: crate::witness::WitnessPool::default(),
             simplification_flags: SimplificationFlags {
                 no_rounds: 1,
+                flag_f: false,
                 flag_s: true,
                 parallel_flag: false,
                 port_substitution: false,
                 flag_old_heuristics: false,
+                track_origins: false,
                 prime: "bn128".to_string(),
                 json_substitutions: "".to_string(),
             },
@@ -108,6 +233,66 @@ impl Default for CircuitLibrary {
 }
 
 impl CircuitLibrary {
+    // adopt the prime field & target version declared by the pkg
+    pub fn configure(&mut self, circuit_pkg: &CircuitPkg) -> Result<(), ReportCollection> {
+        self.configure_with(
+            &circuit_pkg.field,
+            &circuit_pkg.target_version,
+            &circuit_pkg.optimization,
+            circuit_pkg.provenance,
+        )
+    }
+
+    fn configure_with(
+        &mut self,
+        prime_field: &str,
+        target_version: &str,
+        optimization: &Optimization,
+        provenance: bool,
+    ) -> Result<(), ReportCollection> {
+        let mut reports = Vec::new();
+        let mut field = prime_field.to_string();
+        match UsefulConstants::try_new(&field) {
+            None => reports.push(crate::reporting::produce_report_with_message(
+                ReportCode::UnsupportedPrimeField,
+                prime_field.to_string(),
+            )),
+            Some(constants) if !UsefulConstants::is_named_prime(prime_field) => {
+                if !is_probable_prime(constants.get_p()) {
+                    reports.push(crate::reporting::produce_report_with_message(
+                        ReportCode::NonPrimeField,
+                        prime_field.to_string(),
+                    ));
+                } else if !custom_field::is_supported_prime(constants.get_p()) {
+                    reports.push(crate::reporting::produce_report_with_message(
+                        ReportCode::UnsupportedPrimeSize,
+                        prime_field.to_string(),
+                    ));
+                } else {
+                    // custom primes are passed down in decimal
+                    field = constants.get_p().to_str_radix(10);
+                }
+            }
+            Some(_) => {}
+        }
+        if parse_target_version(target_version).is_none() {
+            reports.push(crate::reporting::produce_report_with_message(
+                ReportCode::UnsupportedTargetVersion,
+                target_version.to_string(),
+            ));
+        }
+        if let Err(report) = optimization.check() {
+            reports.push(report);
+        }
+        if !reports.is_empty() {
+            return Err(reports);
+        }
+        self.simplification_flags = optimization.flags(&field, provenance);
+        self.prime_field = field;
+        self.target_version = target_version.to_string();
+        Ok(())
+    }
+
     pub fn get_circuit_design(&self, id: FileID) -> (String, String) {
         let store = self.inner.to_storage();
         let program = store.get(id).unwrap();
@@ -125,15 +310,79 @@ impl CircuitLibrary {
         );
     }
 
+    // adopt the files read by the parser, their paths are the identities of the programs
+    fn store_files(&mut self, file_library: &FileLibrary) {
+        let store = file_library.to_storage();
+        self.catalog = (0..)
+            .map_while(|id| store.get(id).ok().map(|file| (id, file.name().clone())))
+            .collect();
+        self.inner = file_library.clone();
+    }
+
+    // the programs of the library with their sources, as report labels point into them
+    pub fn programs(&self) -> Vec<(String, String)> {
+        self.catalog.iter().map(|(id, _)| self.get_circuit_design(*id)).collect()
+    }
+
     pub fn parse(&self) -> ParserOutput {
         let mut output = ParserOutput::default();
         let prime_field_bigint = UsefulConstants::new(&self.prime_field).get_p().clone();
+        let version = parse_number_version(&self.target_version);
         let store = self.inner.to_storage();
-        for (id, _) in self.catalog.iter() {
+        // the includes are resolved against the identities of the programs, relative
+        // to the including program first, then to the root of the pkg
+        let programs: HashSet<PathBuf> =
+            self.catalog.iter().map(|(_, identity)| normalize_path(Path::new(identity))).collect();
+        let roots = [PathBuf::new()];
+        let mut includes_graph = IncludesGraph::new();
+        for (id, identity) in self.catalog.iter() {
             let program = store.get(*id).unwrap();
             // Parse the sources and return the program library.
             match generate_ast(*id, program.source(), &prime_field_bigint) {
                 Ok(ast) => {
+                    let path = normalize_path(Path::new(identity));
+                    includes_graph.add_node(
+                        path.clone(),
+                        ast.custom_gates,
+                        ast.custom_gates_declared,
+                    );
+                    for include in ast.includes.iter() {
+                        let exists = |path: &Path| programs.contains(path);
+                        match resolve_include(&path, &include.path, &roots, exists) {
+                            Some(included) => includes_graph.add_resolved_edge(included),
+                            None => {
+                                let mut report = Report::error(
+                                    format!(
+                                        "The program {} to be included is not in the pkg",
+                                        include.path
+                                    ),
+                                    ReportCode::IncludeNotFound,
+                                );
+                                report.add_primary(
+                                    include.meta.file_location(),
+                                    *id,
+                                    "Included here".to_string(),
+                                );
+                                output.reports.push(report);
+                            }
+                        }
+                    }
+                    // programs of a pkg are usually fragments without a pragma,
+                    // so only incompatible versions are reported
+                    if let Err(report) =
+                        check_number_version(identity.clone(), ast.compiler_version, version)
+                    {
+                        output.reports.push(report);
+                    }
+                    if ast.custom_gates {
+                        if let Err(report) = check_custom_gates_version(
+                            identity.clone(),
+                            ast.compiler_version,
+                            version,
+                        ) {
+                            output.reports.push(report);
+                        }
+                    }
                     if let Some(main) = ast.main_component {
                         output.main_components.push((*id, main, ast.custom_gates));
                     }
@@ -147,6 +396,8 @@ impl CircuitLibrary {
             }
         }
 
+        output.reports.append(&mut check_custom_gates_pragmas(&includes_graph));
+
         if output.main_components.len() == 0 {
             let report =
                 crate::reporting::produce_report(ReportCode::NoMainFoundInProject, 0..0, 0);
@@ -159,6 +410,31 @@ impl CircuitLibrary {
         output
     }
 
+    // parse every program of the pkg and describe its syntax tree in json
+    pub fn export_ast(&mut self, circuit_pkg: &CircuitPkg) -> Result<String, ReportCollection> {
+        self.configure(circuit_pkg)?;
+        self.store_circuit(circuit_pkg);
+
+        let prime_field_bigint = UsefulConstants::new(&self.prime_field).get_p().clone();
+        let store = self.inner.to_storage();
+        let mut programs = Vec::new();
+        let mut reports = Vec::new();
+        for (id, identity) in self.catalog.iter() {
+            let program = store.get(*id).unwrap();
+            match generate_ast(*id, program.source(), &prime_field_bigint) {
+                Ok(mut ast) => {
+                    fill_ast(&mut ast, *id);
+                    programs.push((identity.clone(), ast))
+                }
+                Err(mut report) => reports.append(&mut report),
+            }
+        }
+        if !reports.is_empty() {
+            return Err(reports);
+        }
+        Ok(crate::json_export::produce_ast_json(&programs))
+    }
+
     pub fn build_program_archive(&self) -> Result<ProgramArchive, ReportCollection> {
         let mut parsed_data = self.parse();
         if parsed_data.reports.len() > 0 {
@@ -198,13 +474,16 @@ impl CircuitLibrary {
         program: ProgramArchive,
     ) -> Result<(VCP, ReportCollection), ReportCollection> {
         let flags = FlagsExecution { verbose: true, inspect: true };
+        let custom_gates = program.custom_gates;
         let execution_result = constraint_execution(&program, flags, &self.prime_field);
         match execution_result {
             Ok((program_exe, warnings)) => {
                 Report::print_reports(&warnings, &self.inner);
                 match program_exe.export(program, flags) {
                     Ok((dag, mut vcp, warnings)) => {
+                        self.statistics = Some(dag.statistics());
                         let list = dag.map_to_list(SimplificationFlags {
+                            flag_f: self.simplification_flags.flag_f,
                             flag_s: self.simplification_flags.flag_s,
                             parallel_flag: self.simplification_flags.parallel_flag,
                             port_substitution: self.simplification_flags.port_substitution,
@@ -214,10 +493,13 @@ impl CircuitLibrary {
                                 .clone(),
                             no_rounds: self.simplification_flags.no_rounds,
                             flag_old_heuristics: self.simplification_flags.flag_old_heuristics,
+                            track_origins: self.simplification_flags.track_origins,
                             prime: self.prime_field.clone(),
                         });
                         VCP::add_witness_list(&mut vcp, Rc::new(list.get_witness_as_vec()));
                         self.constraint_system.sync(&list);
+                        self.constraint_list = Some(list);
+                        self.custom_gates = custom_gates;
                         Ok((vcp, warnings))
                     }
                     Err(reports) => {
@@ -236,36 +518,87 @@ impl CircuitLibrary {
         &mut self,
         circuit_pkg: &CircuitPkg,
     ) -> Result<ReportCollection, ReportCollection> {
+        // adopt the field & version of the pkg
+        self.configure(circuit_pkg)?;
+
         // store the circuit designs
         self.store_circuit(circuit_pkg);
 
         // build the program archive
-        let program_archive = self.build_program_archive();
-        match program_archive {
-            Ok(mut program_archive) => {
-                // do type checking
-                match do_type_analysis(&mut program_archive) {
-                    // generate constraints
-                    Ok(warnings) => {
-                        Report::print_reports(&warnings, &self.inner);
-                        match self.generate_constraints(program_archive) {
-                            Ok((vcp, warnings)) => {
-                                // compile the circuit
-                                let circuit = Circuit::build(
-                                    vcp,
-                                    CompilationFlags { main_inputs_log: false, wat_flag: false },
-                                    &self.target_version,
-                                );
-                                // build the witness calculator
-                                match crate::witness::WitnessCalculator::new(&circuit) {
-                                    Ok(wc) => {
-                                        self.wc = wc;
-                                        Ok(warnings)
-                                    }
-                                    Err(v) => Err(vec![v]),
-                                }
+        let program_archive = self.build_program_archive()?;
+        self.compile_archive(program_archive)
+    }
+
+    // compile the main declared in a file on disk, the includes are resolved as
+    // circom does: relative to the including file first, then to the libraries
+    pub fn compile_file(
+        &mut self,
+        circuit_file: &CircuitFile,
+    ) -> Result<ReportCollection, ReportCollection> {
+        // adopt the field & version of the file
+        self.configure_with(
+            &circuit_file.field,
+            &circuit_file.target_version,
+            &circuit_file.optimization,
+            circuit_file.provenance,
+        )?;
+
+        // read & parse the file with its includes
+        let prime_field_bigint = UsefulConstants::new(&self.prime_field).get_p().clone();
+        let libraries = circuit_file.libraries.iter().map(PathBuf::from).collect();
+        let parsed = run_parser(
+            circuit_file.root.clone(),
+            &self.target_version,
+            libraries,
+            &prime_field_bigint,
+        );
+        match parsed {
+            Ok((program_archive, mut warnings)) => {
+                self.store_files(program_archive.get_file_library());
+                Report::print_reports(&warnings, &self.inner);
+                let mut compile_warnings = self.compile_archive(program_archive)?;
+                warnings.append(&mut compile_warnings);
+                Ok(warnings)
+            }
+            Err((file_library, reports)) => {
+                self.store_files(&file_library);
+                Report::print_reports(&reports, &self.inner);
+                Err(reports)
+            }
+        }
+    }
+
+    fn compile_archive(
+        &mut self,
+        mut program_archive: ProgramArchive,
+    ) -> Result<ReportCollection, ReportCollection> {
+        // do type checking
+        match do_type_analysis(&mut program_archive) {
+            // generate constraints
+            Ok(warnings) => {
+                Report::print_reports(&warnings, &self.inner);
+                self.asserts = self.collect_asserts(&program_archive);
+                match self.generate_constraints(program_archive) {
+                    Ok((vcp, warnings)) => {
+                        // compile the circuit
+                        let mut circuit = Circuit::build(
+                            vcp,
+                            CompilationFlags { main_inputs_log: false, wat_flag: false },
+                            &self.target_version,
+                        );
+                        let cs = &self.constraint_system;
+                        self.signature = crate::witness::input_signature(
+                            &circuit,
+                            cs.no_public_outputs + cs.no_public_inputs,
+                        );
+                        // build the witness calculators
+                        let paths = cs.component_paths();
+                        match crate::witness::WitnessPool::new(&mut circuit, paths) {
+                            Ok(wc) => {
+                                self.wc = wc;
+                                Ok(warnings)
                             }
-                            Err(v) => Err(v),
+                            Err(v) => Err(vec![v]),
                         }
                     }
                     Err(v) => Err(v),
@@ -274,24 +607,557 @@ impl CircuitLibrary {
             Err(v) => Err(v),
         }
     }
-    pub fn execute(
-        &mut self,
-        input_json: &str,
-    ) -> Result<(Vec<BigInt>, LCRecords), ReportCollection> {
+    // the witness of the inputs, which fails at a === the witness breaks
+    // like the module circom writes
+    pub fn calculate_witness(&self, input_json: &str) -> Result<Vec<BigInt>, ReportCollection> {
         // parse inputs
-        let circuit_inputs = crate::witness::parse_inputs(input_json);
+        let circuit_inputs = crate::witness::parse_inputs(input_json).map_err(|e| vec![e])?;
         // calculate witness
-        let witness = self.wc.calculate_witness(circuit_inputs);
-        // evaluate constraints
-        match witness {
-            Ok(w) => Ok((w.clone(), self.constraint_system.eval_constraints(&w))),
-            Err(e) => return Err(vec![e]),
+        self.witness(circuit_inputs, false).map(|(witness, _)| witness)
+    }
+
+    pub fn execute(&self, input_json: &str) -> Evaluation {
+        self.execute_with(input_json, &EvalOptions::default())
+    }
+
+    // execute, evaluating the constraints selected by the options
+    pub fn execute_with(&self, input_json: &str, options: &EvalOptions) -> Evaluation {
+        let (witness, scope, logs) = self.prepare_evaluation(input_json, options)?;
+        let records = self.constraint_system.eval_constraints_in(&witness, &scope);
+        Ok((witness, records, logs))
+    }
+
+    // the witness of the inputs & the constraints an evaluation covers,
+    // for the callers that evaluate the constraints as they go
+    pub fn prepare_evaluation(
+        &self,
+        input_json: &str,
+        options: &EvalOptions,
+    ) -> Result<(Vec<BigInt>, EvalScope, Logs), ReportCollection> {
+        let scope = self.constraint_system.eval_scope(options).map_err(|e| vec![e])?;
+        // parse inputs
+        let circuit_inputs = crate::witness::parse_inputs(input_json).map_err(|e| vec![e])?;
+        let (witness, logs) = self.witness(circuit_inputs, options.skip_constraint_asserts)?;
+        Ok((witness, scope, logs))
+    }
+
+    pub fn execute_batch(&self, inputs_json: &str) -> Result<Vec<Evaluation>, ReportCollection> {
+        self.execute_batch_with(inputs_json, &EvalOptions::default())
+    }
+
+    // execute every input of a json array, spreading them over worker threads.
+    // Each entry of the result belongs to the input at the same index.
+    pub fn execute_batch_with(
+        &self,
+        inputs_json: &str,
+        options: &EvalOptions,
+    ) -> Result<Vec<Evaluation>, ReportCollection> {
+        let scope = self.constraint_system.eval_scope(options).map_err(|e| vec![e])?;
+        let batch = crate::witness::parse_batch_inputs(inputs_json).map_err(|e| vec![e])?;
+        let workers = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(batch.len())
+            .max(1);
+
+        let mut evaluations: Vec<Option<Evaluation>> = batch.iter().map(|_| None).collect();
+        std::thread::scope(|workers_scope| {
+            let handles = (0..workers)
+                .map(|worker| {
+                    let (batch, constraints) = (&batch, &scope);
+                    workers_scope.spawn(move || {
+                        batch
+                            .iter()
+                            .enumerate()
+                            .skip(worker)
+                            .step_by(workers)
+                            .map(|(i, inputs)| {
+                                let evaluation = match inputs {
+                                    Ok(inputs) => {
+                                        self.evaluate(inputs.clone(), constraints, options)
+                                    }
+                                    Err(report) => Err(vec![report.clone()]),
+                                };
+                                (i, evaluation)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            for handle in handles {
+                let results = handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e));
+                for (i, evaluation) in results {
+                    evaluations[i] = Some(evaluation);
+                }
+            }
+        });
+        // every input is evaluated by a worker, or its panic resumed above
+        evaluations.into_iter().collect::<Option<Vec<_>>>().ok_or_else(|| {
+            vec![crate::reporting::produce_report_with_message(
+                ReportCode::InternalPanic,
+                "an input of the batch was not evaluated".to_string(),
+            )]
+        })
+    }
+
+    // check the inputs against the main component & calculate the witness
+    // with the inputs reduced modulo the prime, carrying on past the ===
+    // the witness breaks when skip_constraint_asserts is set
+    fn witness(
+        &self,
+        mut inputs: crate::witness::CircuitInputs,
+        skip_constraint_asserts: bool,
+    ) -> Result<(Vec<BigInt>, Logs), ReportCollection> {
+        for value in inputs.values_mut().flat_map(|input| input.values.iter_mut()) {
+            *value = value.mod_floor(self.field());
+        }
+        let reports = crate::witness::check_inputs(&inputs, &self.signature);
+        if !reports.is_empty() {
+            return Err(reports);
+        }
+        let inputs = inputs.into_iter().map(|(name, input)| (name, input.values));
+        self.wc.calculate_witness(inputs, skip_constraint_asserts).map_err(|mut e| {
+            self.label_assert(&mut e);
+            vec![e]
+        })
+    }
+
+    // calculate the witness & evaluate the constraints against it
+    fn evaluate(
+        &self,
+        inputs: crate::witness::CircuitInputs,
+        scope: &EvalScope,
+        options: &EvalOptions,
+    ) -> Evaluation {
+        let (witness, logs) = self.witness(inputs, options.skip_constraint_asserts)?;
+        let records = self.constraint_system.eval_constraints_in(&witness, scope);
+        Ok((witness, records, logs))
+    }
+
+    // evaluate the constraints against a witness of the caller: the full
+    // witness, or the witness of the inputs with some signals overridden
+    pub fn execute_witness(&self, witness_json: &str, options: &EvalOptions) -> Evaluation {
+        let scope = self.constraint_system.eval_scope(options).map_err(|e| vec![e])?;
+        let supplied = crate::witness::parse_supplied_witness(witness_json).map_err(|e| vec![e])?;
+        let malformed = |msg: String| {
+            vec![crate::reporting::produce_report_with_message(ReportCode::MalformedWitness, msg)]
+        };
+        let no_wires = self.constraint_system.no_wires;
+        let (mut witness, logs): (Vec<Option<BigInt>>, Logs) =
+            match (supplied.witness, supplied.inputs) {
+                (Some(_), Some(_)) => {
+                    return Err(malformed("both the witness & the inputs are given".to_string()))
+                }
+                (Some(witness), None) if witness.len() != no_wires => {
+                    return Err(malformed(format!(
+                        "{} values are given for the {} wires of the witness",
+                        witness.len(),
+                        no_wires
+                    )))
+                }
+                (Some(witness), None) => {
+                    let witness = witness.into_iter().map(|v| Some(v.mod_floor(self.field())));
+                    (witness.collect(), Vec::new())
+                }
+                (None, Some(inputs)) => {
+                    let (witness, logs) = self.witness(inputs, options.skip_constraint_asserts)?;
+                    (witness.into_iter().map(Some).collect(), logs)
+                }
+                // only the signals are given, the constant wire aside
+                (None, None) => {
+                    let mut witness = vec![None; no_wires];
+                    witness[0] = Some(BigInt::from(1));
+                    (witness, Vec::new())
+                }
+            };
+
+        // the symbols sharing a wire must be given the same value
+        let mut reports = Vec::new();
+        let mut overridden: HashMap<usize, (String, BigInt)> = HashMap::new();
+        for (symbol, value) in supplied.signals {
+            match self.constraint_system.symbols.iter().find(|s| s.symbol == symbol) {
+                Some(s) if s.witness >= 0 && (s.witness as usize) < no_wires => {
+                    let value = value.mod_floor(self.field());
+                    match overridden.get(&(s.witness as usize)) {
+                        Some((other, given)) if *given != value => {
+                            reports.push(crate::reporting::produce_report_with_message(
+                                ReportCode::MalformedWitness,
+                                format!(
+                                    "{} & {} share a wire but are given {} & {}",
+                                    other, symbol, given, value
+                                ),
+                            ))
+                        }
+                        _ => {
+                            witness[s.witness as usize] = Some(value.clone());
+                            overridden.insert(s.witness as usize, (symbol, value));
+                        }
+                    }
+                }
+                Some(_) => {
+                    let mut report = crate::reporting::produce_report_with_message(
+                        ReportCode::UnknownWitnessSignal,
+                        symbol,
+                    );
+                    report.add_note("It was removed by the simplification".to_string());
+                    reports.push(report);
+                }
+                None => reports.push(crate::reporting::produce_report_with_message(
+                    ReportCode::UnknownWitnessSignal,
+                    symbol,
+                )),
+            }
+        }
+        if !reports.is_empty() {
+            return Err(reports);
+        }
+        let (signals, _) = self.constraint_system.signals();
+        let missing: Vec<&str> = witness
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| value.is_none())
+            .map(|(w, _)| signals[w].symbol.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(malformed(format!("no value is given for {}", missing.join(", "))));
+        }
+
+        let witness: Vec<BigInt> = witness.into_iter().flatten().collect();
+        if witness[0] != BigInt::from(1) {
+            return Err(malformed(format!("the constant wire is {} instead of 1", witness[0])));
+        }
+        let records = self.constraint_system.eval_constraints_in(&witness, &scope);
+        Ok((witness, records, logs))
+    }
+
+    // look for underconstrained signals by mutating the witness of the inputs,
+    // which must satisfy every constraint to begin with
+    pub fn detect_underconstrained(
+        &self,
+        input_json: &str,
+        options: &MutationOptions,
+    ) -> Result<Vec<Counterexample>, ReportCollection> {
+        options.check()?;
+        let witness = self.calculate_witness(input_json)?;
+        Ok(self.constraint_system.find_underconstrained(&witness, options))
+    }
+
+    // draw inputs shaped like the input signals of the main component & collect
+    // the ones failing the witness calculation or the constraints, shrunk
+    pub fn fuzz(&self, options: &FuzzOptions) -> Result<Fuzzing, ReportCollection> {
+        // a broken === is told apart from the traps as an unsatisfied constraint,
+        // the failures being told apart by the constraints they break
+        let evaluation = EvalOptions { skip_constraint_asserts: true, ..Default::default() };
+        let scope = self.constraint_system.eval_scope(&evaluation).map_err(|e| vec![e])?;
+        crate::fuzz::fuzz(&self.signature, self.field(), options, |inputs| {
+            self.evaluate(inputs, &scope, &evaluation)
+        })
+        .map_err(|e| vec![e])
+    }
+
+    // calculate the witness and serialize it in the iden3 wtns format
+    pub fn export_wtns(&self, input_json: &str) -> Result<Vec<u8>, ReportCollection> {
+        let witness = self.calculate_witness(input_json)?;
+        let failure = |_| {
+            vec![crate::reporting::produce_report_with_message(
+                ReportCode::ExportFailure,
+                "wtns".to_string(),
+            )]
+        };
+        let field_size = self.wc.n64 as usize * 8;
+        let mut wtns =
+            WtnsWriter::new(Cursor::new(Vec::new()), &self.wc.prime, field_size, witness.len())
+                .map_err(failure)?;
+        for value in witness.iter() {
+            wtns.write_witness(value).map_err(failure)?;
+        }
+        WtnsWriter::finish_writing(wtns).map(|cursor| cursor.into_inner()).map_err(failure)
+    }
+
+    // serialize the simplified constraints in the iden3 r1cs format
+    pub fn export_r1cs(&self) -> Result<Vec<u8>, ReportCollection> {
+        let list = match (&self.constraint_list, &self.r1cs) {
+            (Some(list), _) => list,
+            (None, Some(r1cs)) => return Ok(r1cs.clone()),
+            (None, None) => return Err(not_compiled("r1cs")),
+        };
+        list.r1cs_to_writer(Cursor::new(Vec::new()), self.custom_gates)
+            .map(|cursor| cursor.into_inner())
+            .map_err(|_| {
+                vec![crate::reporting::produce_report_with_message(
+                    ReportCode::ExportFailure,
+                    "r1cs".to_string(),
+                )]
+            })
+    }
+
+    // the witness calculation module, as circom --wasm would write it
+    pub fn export_wasm(&self) -> Result<Vec<u8>, ReportCollection> {
+        match self.wc.binary() {
+            [] => Err(not_compiled("wasm")),
+            binary => Ok(binary.to_vec()),
+        }
+    }
+
+    pub fn export_sym(&self) -> Result<String, ReportCollection> {
+        if !self.is_compiled() {
+            return Err(not_compiled("sym"));
+        }
+        Ok(self.constraint_system.sym())
+    }
+
+    // statistics of the template instances of the main, as generated & simplified
+    pub fn template_statistics(&self) -> Result<Vec<TemplateStatistics>, ReportCollection> {
+        if !self.is_compiled() {
+            return Err(not_compiled("statistics"));
+        }
+        let Some(statistics) = &self.statistics else {
+            return Err(vec![crate::reporting::produce_report_with_message(
+                ReportCode::ExportFailure,
+                "statistics, a library restored from a snapshot keeps none".to_string(),
+            )]);
+        };
+        Ok(crate::statistics::template_statistics(statistics, &self.constraint_system))
+    }
+
+    // snapshot of the compiled state, restored by CircuitLibrary::load.
+    // The snapshot embeds native code & must only be loaded by the host
+    // (same wasmer version & cpu) that saved it.
+    pub fn save(&self) -> Result<Vec<u8>, ReportCollection> {
+        if !self.is_compiled() {
+            return Err(not_compiled("library"));
+        }
+        let r1cs = self.export_r1cs()?;
+        let module = self.wc.serialize_module().map_err(|e| vec![e])?;
+
+        let mut snapshot = SnapshotWriter::new();
+        snapshot.write_str(&self.target_version);
+        snapshot.write_str(&self.prime_field);
+        snapshot.write_bool(self.custom_gates);
+        snapshot.write_usize(self.catalog.len());
+        for (id, identity) in self.catalog.iter() {
+            let (_, src) = self.get_circuit_design(*id);
+            snapshot.write_str(identity);
+            snapshot.write_str(&src);
+        }
+        snapshot.write_constraint_system(&self.constraint_system);
+        snapshot.write_usize(self.asserts.len());
+        for site in self.asserts.iter() {
+            snapshot.write_str(&site.component);
+            snapshot.write_usize(site.line);
+            snapshot.write_usize(site.file_id);
+            snapshot.write_usize(site.location.start);
+            snapshot.write_usize(site.location.end);
+        }
+        snapshot.write_usize(self.signature.len());
+        for signal in self.signature.iter() {
+            snapshot.write_str(&signal.name);
+            snapshot.write_usize(signal.dimensions.len());
+            for dimension in signal.dimensions.iter() {
+                snapshot.write_usize(*dimension);
+            }
+            snapshot.write_bool(signal.public);
+            snapshot.write_usize(signal.start);
+            snapshot.write_usize(signal.size);
         }
+        snapshot.write_bytes(self.wc.binary());
+        snapshot.write_bytes(&module);
+        snapshot.write_bytes(&r1cs);
+        Ok(snapshot.finish())
+    }
+
+    pub fn load(bytes: &[u8]) -> Result<CircuitLibrary, ReportCollection> {
+        let malformed = |msg: String| {
+            vec![crate::reporting::produce_report_with_message(ReportCode::MalformedLibrary, msg)]
+        };
+        let mut snapshot = SnapshotReader::new(bytes).map_err(malformed)?;
+        let mut library = CircuitLibrary {
+            target_version: snapshot.read_string().map_err(malformed)?,
+            prime_field: snapshot.read_string().map_err(malformed)?,
+            custom_gates: snapshot.read_bool().map_err(malformed)?,
+            ..Default::default()
+        };
+        library.simplification_flags.prime = library.prime_field.clone();
+        for _ in 0..snapshot.read_usize().map_err(malformed)? {
+            let identity = snapshot.read_string().map_err(malformed)?;
+            let src = snapshot.read_string().map_err(malformed)?;
+            let file_id = library.inner.add_file(identity.clone(), src);
+            library.catalog.push((file_id, identity));
+        }
+        library.constraint_system = snapshot.read_constraint_system().map_err(malformed)?;
+        let in_catalog = |file_id: usize| library.catalog.iter().any(|(id, _)| *id == file_id);
+        let origins = &library.constraint_system.origins;
+        if let Some(origin) = origins.iter().find(|o| !in_catalog(o.source.file_id)) {
+            return Err(malformed(format!("constraint of {} is not in a program", origin.path)));
+        }
+        for _ in 0..snapshot.read_usize().map_err(malformed)? {
+            let component = snapshot.read_string().map_err(malformed)?;
+            let line = snapshot.read_usize().map_err(malformed)?;
+            let file_id = snapshot.read_usize().map_err(malformed)?;
+            let location = snapshot.read_usize().map_err(malformed)?
+                ..snapshot.read_usize().map_err(malformed)?;
+            if !library.catalog.iter().any(|(id, _)| *id == file_id) {
+                return Err(malformed(format!("assert of {} is not in a program", component)));
+            }
+            library.asserts.push(AssertSite { component, line, file_id, location });
+        }
+        for _ in 0..snapshot.read_usize().map_err(malformed)? {
+            let name = snapshot.read_string().map_err(malformed)?;
+            let mut dimensions = Vec::new();
+            for _ in 0..snapshot.read_usize().map_err(malformed)? {
+                dimensions.push(snapshot.read_usize().map_err(malformed)?);
+            }
+            library.signature.push(InputSignal {
+                name,
+                dimensions,
+                public: snapshot.read_bool().map_err(malformed)?,
+                start: snapshot.read_usize().map_err(malformed)?,
+                size: snapshot.read_usize().map_err(malformed)?,
+            });
+        }
+        let binary = snapshot.read_bytes().map_err(malformed)?;
+        let module = snapshot.read_bytes().map_err(malformed)?;
+        library.r1cs = Some(snapshot.read_bytes().map_err(malformed)?.to_vec());
+        snapshot.finish().map_err(malformed)?;
+
+        let paths = library.constraint_system.component_paths();
+        library.wc = crate::witness::WitnessPool::from_serialized(binary, module, paths)
+            .map_err(|e| malformed(e.get_message().clone()))?;
+        Ok(library)
+    }
+
+    fn collect_asserts(&self, program: &ProgramArchive) -> Vec<AssertSite> {
+        let mut asserts = Vec::new();
+        let templates = program
+            .get_templates()
+            .values()
+            .map(|t| (format!("template {}", t.get_name()), t.get_file_id(), t.get_body()));
+        let functions = program
+            .get_functions()
+            .values()
+            .map(|f| (format!("function {}", f.get_name()), f.get_file_id(), f.get_body()));
+        for (component, file_id, body) in templates.chain(functions) {
+            let mut metas = Vec::new();
+            assert_metas(body, &mut metas);
+            for meta in metas {
+                if let Some(line) = self.inner.get_line(meta.start, file_id) {
+                    asserts.push(AssertSite {
+                        component: component.clone(),
+                        line,
+                        file_id,
+                        location: meta.location.clone(),
+                    });
+                }
+            }
+        }
+        asserts
+    }
+
+    // point a failed assert at its source,
+    // from the "template Name_N line: L" of its report
+    fn label_assert(&self, report: &mut Report) {
+        if !matches!(report.get_code(), ReportCode::WitnessAssertFailed) {
+            return;
+        }
+        let message = report.get_message().clone();
+        let Some((component, line)) = message
+            .strip_prefix("Assert failed in ")
+            .and_then(|failed| failed.split_once(" line: "))
+        else {
+            return;
+        };
+        // drop the instance number of the component
+        let component = component.rsplit_once('_').map_or(component, |(name, _)| name);
+        let site = self
+            .asserts
+            .iter()
+            .find(|site| site.component == component && line.parse() == Ok(site.line));
+        if let Some(site) = site {
+            report.add_primary(
+                site.location.clone(),
+                site.file_id,
+                "This assert failed".to_string(),
+            );
+        }
+    }
+
+    fn is_compiled(&self) -> bool {
+        self.constraint_list.is_some() || self.r1cs.is_some()
+    }
+
+    pub fn field(&self) -> &BigInt {
+        &self.constraint_system.field
     }
 
     pub fn get_signals(&self) -> (Vec<&SymElem>, Vec<&SymElem>) {
         self.constraint_system.signals()
     }
+
+    pub fn get_public_signals(&self) -> PublicSignals<'_> {
+        self.constraint_system.public_signals()
+    }
+
+    pub fn get_constraint_system(&self) -> &ConstraintSystem {
+        &self.constraint_system
+    }
+
+    pub fn get_signature(&self) -> &[InputSignal] {
+        &self.signature
+    }
+}
+
+// parsing leaves the file & element ids of the nodes unset
+fn fill_ast(ast: &mut AST, file_id: FileID) {
+    let mut elem_id = 0;
+    ast.meta.set_file_id(file_id);
+    for definition in ast.definitions.iter_mut() {
+        match definition {
+            Definition::Template { meta, body, .. }
+            | Definition::Function { meta, body, .. }
+            | Definition::Bus { meta, body, .. } => {
+                meta.set_file_id(file_id);
+                body.fill(file_id, &mut elem_id);
+            }
+        }
+    }
+    if let Some((_, call)) = ast.main_component.as_mut() {
+        call.fill(file_id, &mut elem_id);
+    }
+}
+
+fn assert_metas<'a>(stmt: &'a Statement, metas: &mut Vec<&'a Meta>) {
+    match stmt {
+        Statement::Assert { meta, .. } => metas.push(meta),
+        Statement::IfThenElse { if_case, else_case, .. } => {
+            assert_metas(if_case, metas);
+            if let Some(else_case) = else_case {
+                assert_metas(else_case, metas);
+            }
+        }
+        Statement::While { stmt, .. } => assert_metas(stmt, metas),
+        Statement::Block { stmts, .. } => stmts.iter().for_each(|s| assert_metas(s, metas)),
+        Statement::InitializationBlock { initializations, .. } => {
+            initializations.iter().for_each(|s| assert_metas(s, metas))
+        }
+        _ => (),
+    }
+}
+
+fn not_compiled(artifact: &str) -> ReportCollection {
+    vec![crate::reporting::produce_report_with_message(
+        ReportCode::ExportFailure,
+        format!("{}, the circuit has not been compiled", artifact),
+    )]
+}
+
+fn parse_target_version(version: &str) -> Option<Version> {
+    let numbers = version
+        .split('.')
+        .map(|n| n.parse::<usize>().ok())
+        .collect::<Option<Vec<usize>>>()?;
+    match numbers.as_slice() {
+        [major, minor, patch] => Some((*major, *minor, *patch)),
+        _ => None,
+    }
 }
 
 pub fn do_type_analysis(
@@ -307,8 +1173,540 @@ pub fn do_type_analysis(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::{compile_main, compiled, main_pkg, program, SQUARE};
+    use std::convert::TryInto;
+    #[test]
+    fn compile_with_pkg_field() {
+        let pkg = CircuitPkg { field: "goldilocks".to_string(), ..main_pkg(SQUARE) };
+        let library = compiled(&pkg);
+        assert_eq!(library.wc.prime, UsefulConstants::new(&pkg.field).get_p().clone());
+        assert_eq!(library.constraint_system.field, library.wc.prime);
+
+        let unsupported = CircuitPkg { field: "babyjubjub".to_string(), ..pkg };
+        match CircuitLibrary::default().compile(&unsupported) {
+            Ok(_) => assert!(false),
+            Err(v) => assert_eq!(v[0].get_code().to_string(), "VE01"),
+        }
+    }
+
+    #[test]
+    fn compile_with_custom_prime() {
+        // 2^31 + 11
+        let pkg = CircuitPkg { field: "0x8000000b".to_string(), ..main_pkg(SQUARE) };
+        let library = compiled(&pkg);
+        assert_eq!(library.wc.prime, BigInt::from(2147483659u64));
+        match library.execute(r#"{"a": 2147483000}"#) {
+            Ok((witness, records, _)) => {
+                // (p - 659)^2 mod p
+                assert_eq!(witness[1], BigInt::from(434281));
+                assert!(records.iter().all(|r| r.report.is_none()));
+            }
+            Err(_) => assert!(false),
+        }
+
+        // 3 * 715827883
+        let composite = CircuitPkg { field: "2147483649".to_string(), ..pkg };
+        match CircuitLibrary::default().compile(&composite) {
+            Ok(_) => assert!(false),
+            Err(v) => assert_eq!(v[0].get_code().to_string(), "VE03"),
+        }
+
+        // a small prime & 2^89 - 1 don't fit the limbs of the witness generator
+        for field in ["7", "618970019642690137449562111"] {
+            let pkg = CircuitPkg { field: field.to_string(), ..main_pkg(SQUARE) };
+            match CircuitLibrary::default().compile(&pkg) {
+                Ok(_) => assert!(false),
+                Err(v) => assert_eq!(v[0].get_code().to_string(), "VE25"),
+            }
+        }
+    }
+
     #[test]
+    fn export_r1cs_in_memory() {
+        match CircuitLibrary::default().export_r1cs() {
+            Ok(_) => assert!(false),
+            Err(v) => assert_eq!(v[0].get_code().to_string(), "VE07"),
+        }
+        let library = compile_main(SQUARE);
+        let r1cs = match library.export_r1cs() {
+            Ok(r1cs) => r1cs,
+            Err(_) => panic!("r1cs export failed"),
+        };
+
+        let u32_at = |i: usize| u32::from_le_bytes(r1cs[i..i + 4].try_into().unwrap()) as usize;
+        assert_eq!(&r1cs[0..4], b"r1cs");
+        assert_eq!(u32_at(4), 1);
+        assert_eq!(u32_at(8), 3);
 
+        // walk the sections: type, size, content
+        let mut sections = HashMap::new();
+        let mut pos = 12;
+        while pos < r1cs.len() {
+            let size = u64::from_le_bytes(r1cs[pos + 4..pos + 12].try_into().unwrap()) as usize;
+            sections.insert(u32_at(pos), pos + 12);
+            pos += 12 + size;
+        }
+        assert_eq!(pos, r1cs.len());
+        assert_eq!(sections.len(), 3);
+
+        let header = sections[&1];
+        assert_eq!(u32_at(header), 32);
+        let prime = BigInt::from_bytes_le(num_bigint::Sign::Plus, &r1cs[header + 4..header + 36]);
+        assert_eq!(prime, library.constraint_system.field);
+        // wires, public outputs, public inputs, private inputs
+        assert_eq!(u32_at(header + 36), 3);
+        assert_eq!(u32_at(header + 40), 1);
+        assert_eq!(u32_at(header + 44), 0);
+        assert_eq!(u32_at(header + 48), 1);
+        // constraints, after the 8 byte label count
+        assert_eq!(u32_at(header + 60), 1);
+    }
+
+    #[test]
+    fn export_wtns_in_memory() {
+        let pkg = CircuitPkg { field: "goldilocks".to_string(), ..main_pkg(SQUARE) };
+        let library = compiled(&pkg);
+        let wtns = match library.export_wtns(r#"{"a": 3}"#) {
+            Ok(wtns) => wtns,
+            Err(_) => panic!("wtns export failed"),
+        };
+
+        let u32_at = |i: usize| u32::from_le_bytes(wtns[i..i + 4].try_into().unwrap()) as usize;
+        let u64_at = |i: usize| u64::from_le_bytes(wtns[i..i + 8].try_into().unwrap());
+        assert_eq!(&wtns[0..4], b"wtns");
+        assert_eq!(u32_at(4), 2);
+        assert_eq!(u32_at(8), 2);
+
+        // header: n8, prime, number of witness values
+        assert_eq!(u32_at(12), 1);
+        assert_eq!(u64_at(16), 16);
+        assert_eq!(u32_at(24), 8);
+        assert_eq!(BigInt::from(u64_at(28)), library.wc.prime);
+        assert_eq!(u32_at(36), 3);
+
+        // witness: one, out, a
+        assert_eq!(u32_at(40), 2);
+        assert_eq!(u64_at(44), 24);
+        assert_eq!([u64_at(52), u64_at(60), u64_at(68)], [1, 9, 3]);
+        assert_eq!(wtns.len(), 76);
+
+        assert!(library.export_wtns(r#"{"a": "x"}"#).is_err());
+    }
+
+    #[test]
+    fn export_wasm_and_sym() {
+        assert!(CircuitLibrary::default().export_wasm().is_err());
+        assert!(CircuitLibrary::default().export_sym().is_err());
+        let library = compile_main(SQUARE);
+
+        match library.export_wasm() {
+            Ok(wasm) => {
+                assert_eq!(&wasm[0..4], b"\0asm");
+                // the exported module computes the same witness
+                let mut wc = crate::witness::WitnessCalculator::default();
+                assert!(wc.load(&wasm).is_ok());
+                assert_eq!(wc.prime, library.wc.prime);
+            }
+            Err(_) => assert!(false),
+        }
+        match library.export_sym() {
+            Ok(sym) => assert_eq!(sym, "1,1,0,main.out\n2,2,0,main.a\n"),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn execute_batch_of_inputs() {
+        let src = indoc::indoc! {"
+            template IsSquare(){
+                signal input a;
+                signal input b;
+                signal sqrd <== a * a;
+                sqrd === b;
+            }
+            component main = IsSquare();
+        "};
+        let library = compile_main(src);
+
+        let batch: Vec<String> =
+            (0..16).map(|i| format!(r#"{{"a": {}, "b": {}}}"#, i, i * i)).collect();
+        let batch = format!("[{}, {{\"a\": 1.5}}]", batch.join(", "));
+        let evaluations = match library.execute_batch(&batch) {
+            Ok(evaluations) => evaluations,
+            Err(_) => panic!("batch execution failed"),
+        };
+        assert_eq!(evaluations.len(), 17);
+        for (i, evaluation) in evaluations[..16].iter().enumerate() {
+            match evaluation {
+                Ok((witness, records, _)) => {
+                    assert_eq!(witness[1], BigInt::from(i));
+                    assert!(records.iter().all(|r| r.report.is_none()));
+                }
+                Err(_) => assert!(false),
+            }
+        }
+        match &evaluations[16] {
+            Ok(_) => assert!(false),
+            Err(v) => assert_eq!(v[0].get_code().to_string(), "VE05"),
+        }
+
+        let (x, y) = library.get_signals();
+        let json = crate::json_export::produce_batch_evaluation_json(
+            library.field(),
+            &evaluations,
+            &x,
+            &y,
+            &library.get_public_signals(),
+        );
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["evaluations"].as_array().unwrap().len(), 17);
+        assert_eq!(json["evaluations"][3]["satisfied"], true);
+        assert_eq!(json["evaluations"][16]["reports"][0]["code"], "VE05");
+
+        assert!(library.execute_batch(r#"{"a": 1}"#).is_err());
+    }
+
+    #[test]
+    fn concurrent_execution() {
+        let src = indoc::indoc! {"
+            template IsSquare(){
+                signal input a;
+                signal input b;
+                signal sqrd <== a * a;
+                sqrd === b;
+            }
+            component main = IsSquare();
+        "};
+        let library = compile_main(src);
+        assert_eq!(library.wc.idle(), 1);
+
+        // the library is shared between threads, each checking out its own calculator
+        let library = &library;
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|i| {
+                    scope.spawn(move || {
+                        let inputs = format!(r#"{{"a": {}, "b": {}}}"#, i, i * i + i % 2);
+                        match library.execute(&inputs) {
+                            Ok((witness, records, _)) => {
+                                assert_eq!(witness[1], BigInt::from(i));
+                                records.iter().all(|r| r.report.is_none())
+                            }
+                            // the calculation stops at the broken ===
+                            Err(reports) => {
+                                assert!(matches!(
+                                    reports[0].get_code(),
+                                    ReportCode::WitnessAssertFailed
+                                ));
+                                false
+                            }
+                        }
+                    })
+                })
+                .collect();
+            for (i, handle) in handles.into_iter().enumerate() {
+                assert_eq!(handle.join().unwrap(), i % 2 == 0);
+            }
+        });
+        // the instances that trapped are dropped, the others go back to the pool
+        assert!(library.wc.idle() <= 4);
+        assert!(library.execute(r#"{"a": 2, "b": 4}"#).is_ok());
+        assert!(library.wc.idle() >= 1);
+    }
+
+    #[test]
+    fn witness_exceptions() {
+        let src = indoc::indoc! {"
+            template Bounded(){
+                signal input x;
+                log(\"checking\", x);
+                assert(x < 10);
+            }
+            template Quotient(){
+                signal input a;
+                signal input b;
+                signal output q;
+                component bounded = Bounded();
+                bounded.x <== a;
+                q <-- a \\ b;
+            }
+            component main = Quotient();
+        "};
+        let library = compile_main(src);
+        assert!(library.execute(r#"{"a": 7, "b": 2}"#).is_ok());
+
+        let failure = |input: &str| match library.execute(input) {
+            Ok(_) => panic!("execution of {} should fail", input),
+            Err(reports) => reports[0].clone(),
+        };
+
+        let report = failure(r#"{"a": 12, "b": 2}"#);
+        assert!(matches!(report.get_code(), ReportCode::WitnessAssertFailed));
+        assert_eq!(report.get_message(), "Assert failed in template Bounded_0 line: 3");
+        assert_eq!(report.get_primary().len(), 1);
+        let notes = report.get_notes();
+        assert!(notes.iter().any(|n| n.starts_with("called from template Quotient_")));
+        assert!(notes.contains(&"log: checking 12".to_string()));
+
+        let report = failure(r#"{"a": 7, "b": 0}"#);
+        assert!(matches!(report.get_code(), ReportCode::WitnessDivisionByZero));
+        assert_eq!(report.get_message(), "Integer division by zero in template Quotient_1");
+
+        // unchecked inputs fail in the witness calculation
+        let runtime_failure = |input: &str| {
+            let inputs = crate::witness::parse_inputs(input).ok().unwrap();
+            let inputs = inputs.into_iter().map(|(name, input)| (name, input.values));
+            library.wc.calculate_witness(inputs, false).err().unwrap()
+        };
+        let report = runtime_failure(r#"{"a": 7, "c": 1}"#);
+        assert!(matches!(report.get_code(), ReportCode::WitnessSignalNotFound));
+        assert_eq!(report.get_message(), "c is not an input signal of the main component");
+
+        let report = runtime_failure(r#"{"a": 7}"#);
+        assert!(matches!(report.get_code(), ReportCode::WitnessMissingInputs));
+        assert_eq!(report.get_message(), "Not all the input signals have been set, got 1 of 2");
+    }
+
+    #[test]
+    fn resolve_includes_between_programs() {
+        let utils = program(
+            "lib/utils.circom",
+            "include \"./gates/not.circom\"; template Double(){ signal input a; signal output b; b <== 2 * a; }",
+        );
+        let not = program(
+            "lib/gates/not.circom",
+            "template Not(){ signal input a; signal output b; b <== 1 - a; }",
+        );
+        let main = |include: &str| {
+            program(
+                "circuits/main.circom",
+                &format!(
+                    "include \"{}\"; template Main(){{ signal input a; signal output b; b <== Double()(a); }} component main = Main();",
+                    include
+                ),
+            )
+        };
+        let compiles = |programs: Vec<Program>| {
+            let pkg = create_default_circuit_pkg(&programs);
+            CircuitLibrary::default().compile(&pkg)
+        };
+
+        // relative to the including program, then to the root of the pkg
+        assert!(compiles(vec![main("../lib/utils.circom"), utils.clone(), not.clone()]).is_ok());
+        assert!(compiles(vec![main("lib/utils.circom"), utils.clone(), not.clone()]).is_ok());
+
+        let programs = vec![main("./utils.circom"), utils.clone(), not];
+        let Err(reports) = compiles(programs) else { panic!("the include was resolved") };
+        assert!(matches!(reports[0].get_code(), ReportCode::IncludeNotFound));
+        let label = &reports[0].get_primary()[0];
+        assert_eq!(label.range, 0.."include \"./utils.circom\";".len());
+        let Err(reports) = compiles(vec![main("../lib/utils.circom"), utils.clone()]) else {
+            panic!("the include was resolved")
+        };
+        assert!(matches!(reports[0].get_code(), ReportCode::IncludeNotFound));
+
+        // the includers of a custom template need the custom templates pragma as well
+        let gate = program(
+            "lib/gates/not.circom",
+            "pragma circom 2.0.6; pragma custom_templates; template custom Not(){ signal input a; signal output b; b <-- 1 - a; }",
+        );
+        let Err(reports) = compiles(vec![main("../lib/utils.circom"), utils, gate]) else {
+            panic!("the pragmas were not checked")
+        };
+        assert!(reports.iter().any(|r| matches!(r.get_code(), ReportCode::CustomGatesPragmaError)));
+    }
+
+    #[test]
+    fn compile_file_with_libraries() {
+        let dir = std::env::temp_dir().join(format!("veritas-compile-file-{}", std::process::id()));
+        let write = |path: &str, src: &str| {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, src).unwrap();
+            path.display().to_string()
+        };
+        let main = |include: &str| {
+            format!("pragma circom 2.0.0;\ninclude \"{}\";\ncomponent main = Square();\n", include)
+        };
+        write(
+            "lib/square.circom",
+            indoc::indoc! {"
+                pragma circom 2.0.0;
+                template Square(){
+                    signal input a;
+                    signal output b;
+                    b <== a * a;
+                }
+            "},
+        );
+        write(
+            "lib/broken.circom",
+            "pragma circom 2.0.0;\ntemplate Square(){ signal output b; b <== ; }\n",
+        );
+        let file = |root: String| CircuitFile {
+            target_version: "2.2.0".to_string(),
+            field: "bn128".to_string(),
+            root,
+            libraries: vec![dir.join("lib").display().to_string()],
+            optimization: Optimization::default(),
+            provenance: false,
+        };
+
+        // the include is found in the library
+        let root = write("circuits/main.circom", &main("square.circom"));
+        let mut library = CircuitLibrary::default();
+        assert!(library.compile_file(&file(root.clone())).is_ok());
+        let Ok((witness, _, _)) = library.execute(r#"{"a": 3}"#) else {
+            panic!("evaluation failed")
+        };
+        assert_eq!(witness, vec![BigInt::from(1), BigInt::from(9), BigInt::from(3)]);
+        let programs = library.programs();
+        assert_eq!(programs.len(), 2);
+        assert_eq!(programs[0].0, root);
+        assert!(programs[1].0.ends_with("square.circom"));
+
+        // the labels of the reports point into the files read from disk
+        let root = write("circuits/includes_broken.circom", &main("broken.circom"));
+        let mut library = CircuitLibrary::default();
+        let Err(reports) = library.compile_file(&file(root)) else {
+            panic!("the broken template compiled")
+        };
+        let label = &reports[0].get_primary()[0];
+        let (identity, src) = &library.programs()[label.file_id];
+        assert!(identity.ends_with("lib/broken.circom"));
+        assert_eq!(&src[label.range.clone()], "<== ;");
+
+        let root = write("circuits/missing.circom", &main("absent.circom"));
+        let Err(reports) = CircuitLibrary::default().compile_file(&file(root)) else {
+            panic!("the missing include was found")
+        };
+        assert!(matches!(reports[0].get_code(), ReportCode::IncludeNotFound));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn optimization_levels() {
+        let src = indoc::indoc! {"
+            template Chain(){
+                signal input a;
+                signal input b;
+                signal output out;
+                signal s <== a + b;
+                signal t <== s;
+                out <== t * t;
+            }
+            component main = Chain();
+        "};
+        let at = |level: OptimizationLevel| {
+            let optimization = Optimization { level, ..Default::default() };
+            let pkg = CircuitPkg { optimization, ..main_pkg(src) };
+            compiled(&pkg)
+        };
+
+        // O0 keeps every constraint & signal, O1 drops t === s & O2 s <== a + b
+        let library = at(OptimizationLevel::O0);
+        let cs = &library.constraint_system;
+        assert_eq!((cs.num_constraints, cs.no_wires), (3, 6));
+        assert!(cs.symbols.iter().all(|s| s.witness >= 0));
+        let Ok((_, records, _)) = library.execute(r#"{"a": 1, "b": 2}"#) else {
+            panic!("evaluation failed")
+        };
+        assert!(records.iter().all(|r| r.report.is_none()));
+        assert_eq!(at(OptimizationLevel::O1).constraint_system.num_constraints, 2);
+        let library = at(OptimizationLevel::O2);
+        assert_eq!(library.constraint_system.num_constraints, 1);
+        // in a single round, unless told otherwise
+        assert_eq!(library.simplification_flags.no_rounds, 1);
+
+        let unsupported = |level: OptimizationLevel, rounds: usize| {
+            let optimization = Optimization { level, rounds: Some(rounds), ..Default::default() };
+            let pkg = CircuitPkg { optimization, ..main_pkg(src) };
+            match CircuitLibrary::default().compile(&pkg) {
+                Err(reports) => {
+                    matches!(reports[0].get_code(), ReportCode::UnsupportedOptimization)
+                }
+                Ok(_) => false,
+            }
+        };
+        assert!(unsupported(OptimizationLevel::O2, 0));
+        assert!(unsupported(OptimizationLevel::O1, 2));
+        assert!(!unsupported(OptimizationLevel::O2, 1));
+    }
+
+    #[test]
+    fn evaluate_supplied_witness() {
+        let src = indoc::indoc! {"
+            template Square(){
+                signal input a;
+                signal output out;
+                signal b;
+                b <== a * a;
+                out <== b * b;
+            }
+            component main = Square();
+        "};
+        let mut library = compile_main(src);
+        let options = EvalOptions::default();
+        let unsatisfied = |evaluation: &Evaluation| match evaluation {
+            Ok((_, records, _)) => records.iter().filter(|r| r.report.is_some()).count(),
+            Err(_) => panic!("evaluation failed"),
+        };
+
+        // the honest witness, given in full, satisfies the constraints
+        let Ok(witness) = library.calculate_witness(r#"{"a": 2}"#) else {
+            panic!("witness calculation failed")
+        };
+        let values: Vec<String> = witness.iter().map(|v| format!("\"{}\"", v)).collect();
+        let full = format!(r#"{{"witness": [{}]}}"#, values.join(", "));
+        assert_eq!(unsatisfied(&library.execute_witness(&full, &options)), 0);
+        // the values of the witness are reduced like the ones of the signals
+        let p = library.field().clone();
+        let values: Vec<String> = witness.iter().map(|v| format!("\"{}\"", v + &p)).collect();
+        let shifted = format!(r#"{{"witness": ["1", {}]}}"#, values[1..].join(", "));
+        let Ok((assignments, _, _)) = library.execute_witness(&shifted, &options) else {
+            panic!("evaluation failed")
+        };
+        assert_eq!(assignments, witness);
+
+        // b overridden breaks both constraints on it, the rest comes from the inputs
+        let partial = r#"{"inputs": {"a": 2}, "signals": {"main.b": 5}}"#;
+        let evaluation = library.execute_witness(partial, &options);
+        assert_eq!(unsatisfied(&evaluation), 2);
+        let Ok((assignments, _, _)) = evaluation else { unreachable!() };
+        assert_eq!(assignments[1], BigInt::from(16));
+
+        // every wire given by symbol, the constant one aside
+        let signals = r#"{"signals": {"main.a": 2, "main.b": 4, "main.out": "-1"}}"#;
+        assert_eq!(unsatisfied(&library.execute_witness(signals, &options)), 1);
+        // an alias of b shares its wire, the same value may be given to both
+        let b = library.constraint_system.symbols.iter().find(|s| s.symbol == "main.b").unwrap();
+        let alias = SymElem {
+            original: b.original,
+            witness: b.witness,
+            node_id: b.node_id,
+            symbol: "main.alias".to_string(),
+        };
+        library.constraint_system.symbols.push(alias);
+        let shared = r#"{"inputs": {"a": 2}, "signals": {"main.b": 5, "main.alias": "5"}}"#;
+        assert_eq!(unsatisfied(&library.execute_witness(shared, &options)), 2);
+
+        let code = |witness: &str| match library.execute_witness(witness, &options) {
+            Err(reports) => reports[0].get_code().to_string(),
+            Ok(_) => panic!("{} was evaluated", witness),
+        };
+        assert_eq!(code(r#"{"inputs": {"a": 2}, "signals": {"main.c": 1}}"#), "VE22");
+        assert_eq!(code(r#"{"witness": ["1", "16"]}"#), "VE23");
+        let constant = format!(r#"{{"witness": ["2", {}]}}"#, values[1..].join(", "));
+        assert_eq!(code(&constant), "VE23");
+        let conflicting = r#"{"inputs": {"a": 2}, "signals": {"main.alias": 6, "main.b": 5}}"#;
+        assert_eq!(code(conflicting), "VE23");
+        assert_eq!(code(r#"{"signals": {"main.a": 2}}"#), "VE23");
+        assert_eq!(code(r#"{"inputs": {"a": 2}, "witness": []}"#), "VE23");
+        assert_eq!(code(r#"{"input": {"a": 2}}"#), "VE05");
+    }
+
+    #[test]
     fn generate_witness_multi_temp() {
         let inputs_str = r#"{"a":1, "b":2, "nonce": "3"}"#;
 
@@ -396,11 +1794,19 @@ mod tests {
         match library.compile(&pkg) {
             Ok(warnings) => {
                 Report::print_reports(&warnings, &library.inner);
-                match library.execute(inputs_str) {
-                    Ok((witness, records)) => {
+                // the inputs break the constraints, which get reported
+                let options = EvalOptions { skip_constraint_asserts: true, ..Default::default() };
+                match library.execute_with(inputs_str, &options) {
+                    Ok((witness, records, logs)) => {
                         let (x, y) = library.get_signals();
                         let r_str = crate::json_export::produce_constraint_evaluation_json(
-                            &records, &x, &y, &witness,
+                            library.field(),
+                            &records,
+                            &x,
+                            &y,
+                            &library.get_public_signals(),
+                            &witness,
+                            &logs,
                         );
                         println!("{}", r_str);
                     }
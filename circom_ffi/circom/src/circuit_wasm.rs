@@ -9,6 +9,10 @@ pub fn generate_wat_instructions(circuit: &Circuit) -> Vec<String> {
     code.push("(module".to_string());
     let mut code_aux = generate_imports_list();
     code.append(&mut code_aux);
+    if circuit.wasm_producer.is_evaluation_module() {
+        code_aux = generate_evaluation_imports_list();
+        code.append(&mut code_aux);
+    }
     code_aux = generate_memory_def_list(&circuit.wasm_producer);
     code.append(&mut code_aux);
 
@@ -114,3 +118,55 @@ pub fn generate_circuit_wasm(circuit: &Circuit) -> Result<Vec<u8>, Report> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint_system::EvalOptions;
+    use crate::fixtures::compile_main;
+
+    #[test]
+    fn broken_constraint_in_exported_module() {
+        let src = indoc::indoc! {"
+            template Double(){
+                signal input a;
+                signal output b;
+                b <-- a + 1;
+                b === 2 * a;
+            }
+            component main = Double();
+        "};
+        let library = compile_main(src);
+
+        // the evaluation stops at the broken constraint like circom
+        match library.execute(r#"{"a": 3}"#) {
+            Ok(_) => panic!("the witness calculation carried on"),
+            Err(reports) => {
+                assert!(matches!(reports[0].get_code(), ReportCode::WitnessAssertFailed))
+            }
+        }
+        // or carries on & reports it unsatisfied when asked to
+        let carry_on = EvalOptions { skip_constraint_asserts: true, ..Default::default() };
+        match library.execute_with(r#"{"a": 3}"#, &carry_on) {
+            Ok((_, records, _)) => {
+                assert_eq!(records.iter().filter(|r| r.report.is_some()).count(), 1)
+            }
+            Err(_) => panic!("execution failed"),
+        }
+        // the witness & its export fail on it
+        let reports = library.export_wtns(r#"{"a": 3}"#).err().unwrap();
+        assert!(matches!(reports[0].get_code(), ReportCode::WitnessAssertFailed));
+        assert!(library.export_wtns(r#"{"a": 1}"#).is_ok());
+
+        // the exported module still checks the constraint
+        let mut wc = crate::witness::WitnessCalculator::default();
+        assert!(wc.load(&library.export_wasm().ok().unwrap()).is_ok());
+        let inputs = |input: &str| {
+            let inputs = crate::witness::parse_inputs(input).ok().unwrap();
+            inputs.into_iter().map(|(name, input)| (name, input.values))
+        };
+        let report = wc.calculate_witness(inputs(r#"{"a": 3}"#)).err().unwrap();
+        assert!(matches!(report.get_code(), ReportCode::WitnessAssertFailed));
+        assert!(wc.calculate_witness(inputs(r#"{"a": 1}"#)).is_ok());
+    }
+}
@@ -8,7 +8,10 @@ use num_bigint::BigInt;
 use num::ToPrimitive;
 
 use std::collections::HashMap;
-use constraint_list::{EncodingIterator, IteratorSignal, ConstraintList};
+use constraint_list::{ConstraintOrigin, EncodingIterator, IteratorSignal, ConstraintList};
+use serde::Deserialize;
+
+use crate::reporting::produce_report_with_message;
 
 pub type C = circom_algebra::algebra::Constraint<usize>;
 pub type A = circom_algebra::algebra::ArithmeticExpression<usize>;
@@ -28,7 +31,11 @@ pub struct ConstraintSystem {
     pub num_constraints: usize,
 
     pub symbols: Vec<SymElem>,
+    // component instances of the main, in the order of the symbols
+    pub components: Vec<ComponentInstance>,
     pub constraints: Vec<C>,
+    // statements the constraints come from, indexed by their origins
+    pub origins: Vec<ConstraintOrigin>,
 }
 
 impl Default for ConstraintSystem {
@@ -46,7 +53,9 @@ impl Default for ConstraintSystem {
             num_constraints: 0,
 
             symbols: Vec::new(),
+            components: Vec::new(),
             constraints: Vec::new(),
+            origins: Vec::new(),
         }
     }
 }
@@ -63,11 +72,24 @@ impl ConstraintSystem {
 
         self.sync_signals(list);
 
+        // keep the origins of the constraints left by the simplification
+        let mut kept = HashMap::new();
         let cids = list.constraints.get_ids();
         self.num_constraints = cids.len();
         for c_id in cids {
             let c = list.constraints.read_constraint(c_id).unwrap();
-            let c = C::apply_correspondence(&c, &list.signal_map);
+            let mut c = C::apply_correspondence(&c, &list.signal_map);
+            let origins = c
+                .origins()
+                .iter()
+                .map(|origin| {
+                    *kept.entry(*origin).or_insert_with(|| {
+                        self.origins.push(list.origins[*origin].clone());
+                        self.origins.len() - 1
+                    })
+                })
+                .collect();
+            c.set_origins(origins);
             if C::is_linear(&c) {
                 self.no_linear += 1;
             } else {
@@ -82,7 +104,13 @@ impl ConstraintSystem {
             mut iter: EncodingIterator,
             list: &ConstraintList,
             sym: &mut Vec<SymElem>,
+            components: &mut Vec<ComponentInstance>,
         ) {
+            components.push(ComponentInstance {
+                node_id: iter.node_id,
+                path: iter.path.clone(),
+                signal_start: iter.offset + 1,
+            });
             // ommit the constraints from the DAG encoding
             // refer to the constraints from the constraint list
             let (signals, _) = EncodingIterator::take(&mut iter);
@@ -104,20 +132,132 @@ impl ConstraintSystem {
 
             for edge in EncodingIterator::edges(&iter) {
                 let next = EncodingIterator::next(&iter, edge);
-                signal_iteration(next, list, sym);
+                signal_iteration(next, list, sym, components);
             }
         }
 
         let iter = EncodingIterator::new(&list.dag_encoding);
-        signal_iteration(iter, list, &mut self.symbols);
+        signal_iteration(iter, list, &mut self.symbols, &mut self.components);
+    }
+
+    pub fn eval_constraints(&self, assignments: &Vec<BigInt>) -> LCRecords {
+        self.eval_constraints_in(assignments, &EvalScope::default())
+    }
+
+    // the constraints of the scope, with a record for each of them or,
+    // failing fast, only for the first unsatisfied one
+    pub fn eval_constraints_in(&self, assignments: &Vec<BigInt>, scope: &EvalScope) -> LCRecords {
+        self.recorded(assignments, scope)
+            .map(|id| self.record(id, assignments, &self.constraints[id]))
+            .collect()
     }
 
-    pub fn eval_constraints(&mut self, assignments: &Vec<BigInt>) -> LCRecords {
-        let mut records = Vec::new();
-        for c in &self.constraints {
-            records.push(LCRecord::new(assignments, c.a(), c.b(), c.c(), &self.field));
+    // ids of the constraints an evaluation records, in order
+    pub(crate) fn recorded<'a>(
+        &'a self,
+        assignments: &'a [BigInt],
+        scope: &'a EvalScope,
+    ) -> impl Iterator<Item = usize> + 'a {
+        let in_scope = move |c: &C| match &scope.origins {
+            // the first origin is the statement of the constraint itself
+            Some(origins) => {
+                c.origins().first().and_then(|origin| origins.get(*origin)) == Some(&true)
+            }
+            None => true,
+        };
+        self.constraints
+            .iter()
+            .enumerate()
+            .filter(move |(_, c)| in_scope(c))
+            .filter(move |(_, c)| !scope.fail_fast || !self.is_satisfied(assignments, c))
+            .take(if scope.fail_fast { 1 } else { usize::MAX })
+            .map(|(id, _)| id)
+    }
+
+    // the record of a constraint, an unsatisfied one is reported at the
+    // statement it comes from & at the ones the simplification merged into it
+    pub(crate) fn record(&self, id: usize, assignments: &[BigInt], c: &C) -> LCRecord {
+        let mut record = LCRecord::new(id, assignments, c.a(), c.b(), c.c(), &self.field);
+        if let Some(report) = &mut record.report {
+            let origins = c.origins().iter().filter_map(|origin| self.origins.get(*origin));
+            for (i, origin) in origins.enumerate() {
+                let location = origin.source.location.clone();
+                let file_id = origin.source.file_id;
+                if i == 0 {
+                    let msg = format!("Constraint of {} ({})", origin.path, origin.template);
+                    report.add_primary(location, file_id, msg);
+                } else {
+                    let msg = format!(
+                        "Merged with a constraint of {} ({})",
+                        origin.path, origin.template
+                    );
+                    report.add_secondary(location, file_id, Some(msg));
+                }
+            }
         }
-        records
+        record
+    }
+
+    pub(crate) fn is_satisfied(&self, assignments: &[BigInt], c: &C) -> bool {
+        self.values(assignments, c)[3] == BigInt::from(0)
+    }
+
+    // the values of a, b, c & a * b - c in [0, prime)
+    pub(crate) fn values(&self, assignments: &[BigInt], c: &C) -> [BigInt; 4] {
+        let eval = |lc: &LC<usize>| {
+            lc.iter().fold(BigInt::from(0), |acc, (k, v)| {
+                add(&acc, &mul(v, &assignments[*k], &self.field), &self.field)
+            })
+        };
+        let (a, b, c) = (eval(c.a()), eval(c.b()), eval(c.c()));
+        let y = sub(&mul(&a, &b, &self.field), &c, &self.field);
+        [a, b, c, y]
+    }
+
+    // resolve the options of an evaluation, the scope is every constraint
+    // generated by the component or by one of its subcomponents. A constraint
+    // keeps its component when the simplification renames its signals
+    // to the ones of another component
+    pub fn eval_scope(&self, options: &EvalOptions) -> Result<EvalScope, Report> {
+        let origins = match &options.scope {
+            Some(path) => {
+                if !self.components.iter().any(|component| component.path == *path) {
+                    return Err(produce_report_with_message(
+                        ReportCode::UnknownComponent,
+                        path.clone(),
+                    ));
+                }
+                let under = |origin: &ConstraintOrigin| {
+                    origin.path.strip_prefix(path.as_str()).map_or(false, |rest| {
+                        rest.is_empty() || rest.starts_with('.') || rest.starts_with('[')
+                    })
+                };
+                Some(self.origins.iter().map(under).collect())
+            }
+            None => None,
+        };
+        Ok(EvalScope { origins, fail_fast: options.fail_fast })
+    }
+
+    // paths of the component instances by their first signal. A component
+    // without signals of its own shares it with its first subcomponent,
+    // the outer one is kept
+    pub fn component_paths(&self) -> HashMap<usize, String> {
+        let mut paths = HashMap::new();
+        for component in self.components.iter() {
+            paths.entry(component.signal_start).or_insert_with(|| component.path.clone());
+        }
+        paths
+    }
+
+    // the content of the .sym file, in the order circom writes it
+    pub fn sym(&self) -> String {
+        let mut sym = String::new();
+        for s in &self.symbols {
+            sym.push_str(&s.to_string());
+            sym.push('\n');
+        }
+        sym
     }
 
     pub fn signals(&self) -> (Vec<&SymElem>, Vec<&SymElem>) {
@@ -136,10 +276,67 @@ impl ConstraintSystem {
         }
         (mapped_signals, unmapped_signals)
     }
+
+    pub fn public_signals(&self) -> PublicSignals<'_> {
+        let outputs = 1..=self.no_public_outputs as i64;
+        PublicSignals {
+            size: self.no_public_outputs + self.no_public_inputs,
+            outputs: self.symbols.iter().filter(|s| outputs.contains(&s.original)).collect(),
+        }
+    }
+}
+
+/// The public signals of the main component, as snarkjs lists them:
+/// the outputs then the public inputs, from the first wire after the constant.
+pub struct PublicSignals<'a> {
+    pub size: usize,
+    // symbols of the outputs, in witness order
+    pub outputs: Vec<&'a SymElem>,
+}
+
+/// A component instance of the main, as the DAG encoding reaches it.
+pub struct ComponentInstance {
+    pub node_id: usize,
+    // path of the instance, e.g. main.hasher[1]
+    pub path: String,
+    // index of the first signal of the instance, the witness
+    // calculation lays its signals out from there
+    pub signal_start: usize,
+}
+
+/// Options of a constraint evaluation.
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct EvalOptions {
+    // stop at the first unsatisfied constraint
+    pub fail_fast: bool,
+    // path of a component instance, e.g. main.hasher: only the constraints
+    // generated by the component & by its subcomponents are evaluated
+    pub scope: Option<String>,
+    // the witness calculation carries on past a === the witness breaks,
+    // which the evaluation reports unsatisfied, instead of failing there
+    pub skip_constraint_asserts: bool,
+}
+
+/// The constraints an evaluation covers, resolved from its EvalOptions.
+#[derive(Default)]
+pub struct EvalScope {
+    // origins of the scoped component & of its subcomponents,
+    // every constraint when None
+    origins: Option<Vec<bool>>,
+    fail_fast: bool,
+}
+
+impl EvalScope {
+    pub fn fail_fast(&self) -> bool {
+        self.fail_fast
+    }
 }
 
 pub type LCRecords = Vec<LCRecord>;
 pub struct LCRecord {
+    // position of the constraint in the constraint system
+    pub id: usize,
     pub field: BigInt,
     pub a_constraints: Vec<(usize, BigInt)>,
     pub b_constraints: Vec<(usize, BigInt)>,
@@ -159,7 +356,8 @@ fn val(elem: &BigInt, field: &BigInt) -> BigInt {
 
 impl LCRecord {
     pub fn new(
-        assignments: &Vec<BigInt>,
+        id: usize,
+        assignments: &[BigInt],
         a_constraints: &LC<usize>,
         b_constraints: &LC<usize>,
         c_constraints: &LC<usize>,
@@ -195,6 +393,7 @@ impl LCRecord {
             );
             r.add_note(note);
             Self {
+                id,
                 field: field.clone(),
                 a_constraints: a_constraints.iter().map(|(k, v)| (*k, v.clone())).collect(),
                 b_constraints: b_constraints.iter().map(|(k, v)| (*k, v.clone())).collect(),
@@ -204,6 +403,7 @@ impl LCRecord {
             }
         } else {
             Self {
+                id,
                 field: field.clone(),
                 a_constraints: a_constraints.iter().map(|(k, v)| (*k, v.clone())).collect(),
                 b_constraints: b_constraints.iter().map(|(k, v)| (*k, v.clone())).collect(),
@@ -249,3 +449,238 @@ impl LCRecord {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{CircuitLibrary, CircuitPkg, Optimization, OptimizationLevel};
+    use crate::fixtures::{compile_main, compiled, main_pkg};
+    use std::collections::HashSet;
+
+    #[test]
+    fn scoped_and_fail_fast_evaluation() {
+        let src = indoc::indoc! {"
+            template Cube(){
+                signal input x;
+                signal output cb;
+                signal sq <== x * x;
+                cb <== sq * x;
+            }
+            template WrongCube(){
+                signal input x;
+                signal output cb;
+                signal sq <== x * x;
+                cb <-- sq;
+                cb === sq * x;
+            }
+            template Cubes(){
+                signal input a;
+                signal input b;
+                signal output out[3];
+                component cube = Cube();
+                component wrong[2];
+                cube.x <== a;
+                out[0] <== cube.cb;
+                for (var i = 0; i < 2; i++) {
+                    wrong[i] = WrongCube();
+                    wrong[i].x <== i == 0 ? a : b;
+                    out[i + 1] <== wrong[i].cb;
+                }
+            }
+            component main = Cubes();
+        "};
+        let library = compile_main(src);
+        let inputs = r#"{"a": 3, "b": 5}"#;
+        let unsatisfied = |options: &EvalOptions| match library.execute_with(inputs, options) {
+            Ok((_, records, _)) => {
+                let ids: Vec<usize> =
+                    records.iter().filter(|r| r.report.is_some()).map(|r| r.id).collect();
+                (records.len(), ids)
+            }
+            Err(_) => panic!("execution failed"),
+        };
+
+        // the witness calculation carries on past the broken === of WrongCube
+        let carry_on = EvalOptions { skip_constraint_asserts: true, ..Default::default() };
+        let (all, failing) = unsatisfied(&carry_on);
+        assert_eq!(all, library.get_constraint_system().constraints.len());
+        assert_eq!(failing.len(), 2);
+
+        // only the first unsatisfied constraint is recorded
+        let options = EvalOptions { fail_fast: true, ..carry_on.clone() };
+        assert_eq!(unsatisfied(&options), (1, failing[..1].to_vec()));
+
+        let scoped = |path: &str| EvalOptions { scope: Some(path.to_string()), ..carry_on.clone() };
+        let (cube, none) = unsatisfied(&scoped("main.cube"));
+        assert!(cube > 0 && cube < all);
+        assert!(none.is_empty());
+
+        // cube.x & wrong[0].x are simplified into main.a, the constraints
+        // on it stay with the component that generated them
+        let ids = |path: &str| match library.execute_with(inputs, &scoped(path)) {
+            Ok((_, records, _)) => records.iter().map(|r| r.id).collect::<Vec<usize>>(),
+            Err(_) => panic!("execution failed"),
+        };
+        let (cube_ids, wrong_ids) = (ids("main.cube"), ids("main.wrong[0]"));
+        assert_eq!((cube_ids.len(), wrong_ids.len()), (2, 2));
+        assert!(cube_ids.iter().all(|id| !wrong_ids.contains(id)));
+        let cs = library.get_constraint_system();
+        let a = cs.symbols.iter().find(|s| s.symbol == "main.a").unwrap().witness as usize;
+        let on_a = |id: &usize| cs.constraints[*id].a().contains_key(&a);
+        assert!(cube_ids.iter().any(on_a) && wrong_ids.iter().any(on_a));
+
+        // nothing to record, failing fast in a satisfied component
+        let options = EvalOptions { fail_fast: true, ..scoped("main.cube") };
+        let Ok((witness, records, logs)) = library.execute_with(inputs, &options) else {
+            panic!("execution failed")
+        };
+        assert!(records.is_empty());
+        let (x, y) = library.get_signals();
+        let json = crate::json_export::produce_constraint_evaluation_json(
+            library.field(),
+            &records,
+            &x,
+            &y,
+            &library.get_public_signals(),
+            &witness,
+            &logs,
+        );
+        assert!(serde_json::from_str::<serde_json::Value>(&json).is_ok());
+        assert_eq!(unsatisfied(&scoped("main.wrong[0]")).1, failing[..1].to_vec());
+        assert_eq!(unsatisfied(&scoped("main")).1, failing);
+
+        match library.execute_with(inputs, &scoped("main.hasher")) {
+            Ok(_) => panic!("main.hasher is not a component"),
+            Err(reports) => assert!(matches!(reports[0].get_code(), ReportCode::UnknownComponent)),
+        }
+    }
+
+    #[test]
+    fn unsatisfied_constraint_provenance() {
+        let src = indoc::indoc! {"
+            template Sum(){
+                signal input a;
+                signal input b;
+                signal output out;
+                signal s;
+                s <== a + b;
+                out <-- s * s + 1;
+                out === s * s;
+            }
+            template Outer(){
+                signal input a;
+                signal input b;
+                signal output out;
+                component sum = Sum();
+                sum.a <== a;
+                sum.b <== b;
+                out <== sum.out;
+            }
+            component main = Outer();
+        "};
+        let pkg = main_pkg(src);
+        let carry_on = EvalOptions { skip_constraint_asserts: true, ..Default::default() };
+        let labels = |library: &CircuitLibrary| {
+            let Ok((_, records, _)) = library.execute_with(r#"{"a": 1, "b": 2}"#, &carry_on) else {
+                panic!("execution failed")
+            };
+            let failing: Vec<&Report> = records.iter().filter_map(|r| r.report.as_ref()).collect();
+            assert_eq!(failing.len(), 1);
+            let texts = |labels: &[codespan_reporting::diagnostic::Label<usize>]| {
+                labels
+                    .iter()
+                    .map(|l| (src[l.range.clone()].to_string(), l.message.clone()))
+                    .collect::<Vec<_>>()
+            };
+            (texts(failing[0].get_primary()), texts(failing[0].get_secondary()))
+        };
+
+        let (primary, secondary) = labels(&compiled(&pkg));
+        assert_eq!(
+            primary,
+            vec![("out === s * s;".to_string(), "Constraint of main.sum (Sum())".to_string())]
+        );
+        assert!(secondary.is_empty());
+
+        // the linear simplification merges s <== a + b into the constraint,
+        // which only points to it when the provenance is asked for
+        let optimization = Optimization { level: OptimizationLevel::O2, ..Default::default() };
+        let pkg = CircuitPkg { optimization, ..pkg };
+        let (primary, secondary) = labels(&compiled(&pkg));
+        assert_eq!(primary[0].0, "out === s * s;");
+        assert!(secondary.is_empty());
+
+        let optimization = Optimization { level: OptimizationLevel::O2, ..Default::default() };
+        let pkg = CircuitPkg { optimization, provenance: true, ..pkg };
+        let library = compiled(&pkg);
+        let (primary, secondary) = labels(&library);
+        assert_eq!(primary[0].0, "out === s * s;");
+        assert_eq!(
+            secondary,
+            vec![(
+                "s <== a + b".to_string(),
+                "Merged with a constraint of main.sum (Sum())".to_string()
+            )]
+        );
+
+        // the provenance is kept by the snapshots
+        let loaded = CircuitLibrary::load(&library.save().ok().unwrap()).ok().unwrap();
+        assert_eq!(labels(&loaded), (primary, secondary));
+    }
+
+    #[test]
+    fn provenance_of_large_clusters() {
+        let src = indoc::indoc! {"
+            template Chain(n){
+                signal input a;
+                signal output o[n];
+                signal output out;
+                signal s[n];
+                s[0] <== a + 1;
+                for (var i = 1; i < n; i++) {
+                    s[i] <== s[i - 1] + 1;
+                }
+                for (var i = 0; i < n; i++) {
+                    o[i] <== a;
+                }
+                out <-- s[n - 1] * s[n - 1] + 1;
+                out === s[n - 1] * s[n - 1];
+            }
+            component main = Chain(300);
+        "};
+        let tracked = |provenance: bool| {
+            let optimization = Optimization { level: OptimizationLevel::O2, ..Default::default() };
+            let pkg = CircuitPkg { optimization, provenance, ..main_pkg(src) };
+            compiled(&pkg)
+        };
+        let carry_on = EvalOptions { skip_constraint_asserts: true, ..Default::default() };
+        let merged = |library: &CircuitLibrary| {
+            let Ok((_, records, _)) = library.execute_with(r#"{"a": 1}"#, &carry_on) else {
+                panic!("execution failed")
+            };
+            let failing: Vec<&Report> = records.iter().filter_map(|r| r.report.as_ref()).collect();
+            assert_eq!(failing.len(), 1);
+            failing[0].get_secondary().len()
+        };
+
+        // the constraints left by the equalities of o & a only keep their own origin
+        let library = tracked(false);
+        let cs = library.get_constraint_system();
+        assert!(cs.constraints.iter().all(|c| c.origins().len() == 1));
+        assert_eq!(merged(&library), 0);
+
+        // every constraint merged is pointed to once
+        let library = tracked(true);
+        let cs = library.get_constraint_system();
+        for c in &cs.constraints {
+            let unique: HashSet<_> = c.origins().iter().collect();
+            assert_eq!(unique.len(), c.origins().len());
+        }
+        // the 299 equalities left point to the 300 of the cluster, the
+        // non linear one to the 300 linear constraints of s
+        let mut per_constraint: Vec<_> = cs.constraints.iter().map(|c| c.origins().len()).collect();
+        per_constraint.sort();
+        assert_eq!(per_constraint, [vec![300; 299], vec![301]].concat());
+        assert_eq!(merged(&library), 300);
+    }
+}
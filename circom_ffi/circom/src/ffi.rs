@@ -1,8 +1,14 @@
 use std::{
     ffi::{c_char, c_void, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
 };
-use crate::circuit::CircuitLibrary;
-use super::reporting::report_error;
+use serde::de::DeserializeOwned;
+use crate::circuit::{CircuitFile, CircuitLibrary, Evaluation};
+use crate::constraint_system::EvalOptions;
+use crate::fuzz::FuzzOptions;
+use crate::underconstrained::MutationOptions;
+use program_structure::{error_code::ReportCode, error_definition::Report};
+use super::reporting::{produce_report_with_message, report_diagnostic, report_error};
 
 #[repr(C)]
 pub struct FFICircom {
@@ -19,76 +25,629 @@ extern "C" {
     fn share_evaluations(ctx_handle: usize, ceval_json: *const c_void, len: usize);
     fn share_report(ctx_handle: usize, report: *const c_void, len: usize);
     fn share_circom_ptr(ctx_handle: usize, ptr: *const FFICircom);
+    fn share_buffer(ctx_handle: usize, buff: *const c_void, len: usize);
+    fn share_chunk(ctx_handle: usize, chunk: *const c_void, len: usize);
+}
+
+/// Runs the body of an exported function, turning a panic into an
+/// InternalPanic report instead of unwinding across the C ABI.
+fn ffi_guard<F: FnOnce()>(ctx_handle: usize, f: F) {
+    if let Err(cause) = catch_unwind(AssertUnwindSafe(f)) {
+        let msg = if let Some(msg) = cause.downcast_ref::<&str>() {
+            msg.to_string()
+        } else if let Some(msg) = cause.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            "unknown panic payload".to_string()
+        };
+        let report = produce_report_with_message(ReportCode::InternalPanic, msg);
+        report_diagnostic(ctx_handle, &report.to_diagnostic(), &mut Vec::new());
+    }
+}
+
+/// Borrows a C string as UTF-8, reporting it with the given code if it is not.
+fn ffi_str<'a>(raw: *const c_char, code: ReportCode) -> Result<&'a str, Report> {
+    if raw.is_null() {
+        return Err(produce_report_with_message(code, "received a null string".to_string()));
+    }
+    unsafe { CStr::from_ptr(raw) }
+        .to_str()
+        .map_err(|e| produce_report_with_message(code, e.to_string()))
+}
+
+/// Deserializes the JSON string into a CircuitPkg struct.
+fn ffi_pkg(pkg_json_raw: *const c_char) -> Result<crate::circuit::CircuitPkg, Report> {
+    let pkg_json_str = ffi_str(pkg_json_raw, ReportCode::MalformedPackageJson)?;
+    serde_json::from_str(pkg_json_str)
+        .map_err(|e| produce_report_with_message(ReportCode::MalformedPackageJson, e.to_string()))
+}
+
+/// Deserializes the JSON string into a CircuitFile struct.
+fn ffi_circuit_file(file_json_raw: *const c_char) -> Result<CircuitFile, Report> {
+    let file_json_str = ffi_str(file_json_raw, ReportCode::MalformedPackageJson)?;
+    serde_json::from_str(file_json_str)
+        .map_err(|e| produce_report_with_message(ReportCode::MalformedPackageJson, e.to_string()))
+}
+
+/// Deserializes the options of a call, a null string takes the defaults.
+fn ffi_options<T: DeserializeOwned + Default>(
+    options_json: *const c_char,
+    what: &str,
+) -> Result<T, Report> {
+    if options_json.is_null() {
+        return Ok(T::default());
+    }
+    let options_json_str = ffi_str(options_json, ReportCode::MalformedInputJson)?;
+    serde_json::from_str(options_json_str).map_err(|e| {
+        produce_report_with_message(
+            ReportCode::MalformedInputJson,
+            format!("invalid {} options: {}", what, e),
+        )
+    })
+}
+
+/// Deserializes the evaluation options, a null string takes the defaults.
+fn ffi_eval_options(options_json: *const c_char) -> Result<EvalOptions, Report> {
+    ffi_options(options_json, "evaluation")
+}
+
+/// Borrows the library behind the handle, reporting it if it was never compiled.
+/// The library is only borrowed immutably: witnesses are calculated by a pool
+/// of calculators, so the same handle can be used from several threads at once.
+fn ffi_library<'a>(ctx_handle: usize, ffi_circom: *mut FFICircom) -> Option<&'a CircuitLibrary> {
+    if ffi_circom.is_null() || unsafe { (*ffi_circom).inner.is_null() } {
+        report_error(ctx_handle, "The circuit library has not been compiled", &mut Vec::new());
+        return None;
+    }
+    Some(unsafe { &*((*ffi_circom).inner as *const CircuitLibrary) })
 }
 
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn ffi_compile_library(ctx_handle: usize, pkg_json_raw: *const c_char) {
-    let mut buff = Vec::with_capacity(100_000);
+    ffi_guard(ctx_handle, || {
+        let mut buff = Vec::with_capacity(100_000);
 
-    // Deserialize the JSON string into a CircuitPkg struct
-    let pkg_json_str = unsafe { CStr::from_ptr(pkg_json_raw) }.to_str().unwrap();
-    let pkg_json: Result<crate::circuit::CircuitPkg, serde_json::Error> =
-        serde_json::from_str(pkg_json_str);
+        let circuit_pkg = match ffi_pkg(pkg_json_raw) {
+            Ok(circuit_pkg) => circuit_pkg,
+            Err(r) => {
+                report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                return;
+            }
+        };
+
+        let mut library = crate::circuit::CircuitLibrary::default();
+        match library.compile(&circuit_pkg) {
+            Ok(warnings) => {
+                for w in warnings.iter() {
+                    report_diagnostic(ctx_handle, &w.to_diagnostic(), &mut buff);
+                }
+            }
+            Err(v) => {
+                for r in v.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
+            }
+        }
+        // TODO: Optimize this
+        let ffi_lib =
+            Box::new(FFICircom { inner: Box::into_raw(Box::new(library)) as *mut c_void });
+        unsafe {
+            share_circom_ptr(ctx_handle, Box::into_raw(ffi_lib));
+        }
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_compile_file compiles the main declared in a .circom file on disk,
+/// shares the library through share_circom_ptr and the programs read
+/// from disk through share_buffer, as the report labels point into them.
+pub extern "C" fn ffi_compile_file(ctx_handle: usize, file_json_raw: *const c_char) {
+    ffi_guard(ctx_handle, || {
+        let mut buff = Vec::new();
+
+        let circuit_file = match ffi_circuit_file(file_json_raw) {
+            Ok(circuit_file) => circuit_file,
+            Err(r) => {
+                report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                return;
+            }
+        };
+
+        let mut library = CircuitLibrary::default();
+        let reports = match library.compile_file(&circuit_file) {
+            Ok(warnings) => warnings,
+            Err(errors) => errors,
+        };
+        for r in reports.iter() {
+            report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+        }
+        let programs = crate::json_export::produce_programs_json(&library.programs());
+        ffi_pass_buffer(ctx_handle, programs.as_ptr(), programs.len());
+        let ffi_lib =
+            Box::new(FFICircom { inner: Box::into_raw(Box::new(library)) as *mut c_void });
+        unsafe {
+            share_circom_ptr(ctx_handle, Box::into_raw(ffi_lib));
+        }
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn ffi_circuit_execution(
+    ctx_handle: usize,
+    ffi_circom: *mut FFICircom,
+    inputs_json: *const c_char,
+    options_json: *const c_char,
+) {
+    ffi_evaluation(ctx_handle, ffi_circom, inputs_json, options_json, CircuitLibrary::execute_with)
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_witness_evaluation evaluates the constraints against a witness given
+/// by the caller, the full witness or signals overriding the witness of the
+/// inputs, & shares the evaluation like ffi_circuit_execution.
+pub extern "C" fn ffi_witness_evaluation(
+    ctx_handle: usize,
+    ffi_circom: *mut FFICircom,
+    witness_json: *const c_char,
+    options_json: *const c_char,
+) {
+    ffi_evaluation(
+        ctx_handle,
+        ffi_circom,
+        witness_json,
+        options_json,
+        CircuitLibrary::execute_witness,
+    )
+}
 
-    let circuit_pkg = match pkg_json {
-        Ok(circuit_pkg) => circuit_pkg,
-        Err(e) => {
-            report_error(ctx_handle, &e.to_string(), &mut buff);
+/// Evaluates the constraints against the json given to the library & shares
+/// the evaluation through share_evaluations, or reports why it failed.
+fn ffi_evaluation(
+    ctx_handle: usize,
+    ffi_circom: *mut FFICircom,
+    json: *const c_char,
+    options_json: *const c_char,
+    evaluate: fn(&CircuitLibrary, &str, &EvalOptions) -> Evaluation,
+) {
+    ffi_guard(ctx_handle, || {
+        let mut buff = Vec::new();
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
             return;
+        };
+
+        let given = ffi_str(json, ReportCode::MalformedInputJson)
+            .and_then(|given| Ok((given, ffi_eval_options(options_json)?)));
+        let (json_str, options) = match given {
+            Ok(given) => given,
+            Err(r) => {
+                report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                return;
+            }
+        };
+
+        match evaluate(library, json_str, &options) {
+            Ok((witness, records, logs)) => {
+                let (x, y) = library.get_signals();
+                let constraint_evaluation = crate::json_export::produce_constraint_evaluation_json(
+                    library.field(),
+                    &records,
+                    &x,
+                    &y,
+                    &library.get_public_signals(),
+                    &witness,
+                    &logs,
+                );
+                unsafe {
+                    share_evaluations(
+                        ctx_handle,
+                        constraint_evaluation.as_ptr() as *const c_void,
+                        constraint_evaluation.len(),
+                    );
+                }
+            }
+            Err(reports) => {
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
+            }
         }
-    };
+    })
+}
 
-    let mut library = crate::circuit::CircuitLibrary::default();
-    match library.compile(&circuit_pkg) {
-        Ok(warnings) => {
-            for w in warnings.iter() {
-                crate::reporting::report_diagnostic(ctx_handle, &w.to_diagnostic(), &mut buff);
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_constraints_binary shares the constraints & the symbols of the circuit
+/// in the compact binary format of binary_export, one chunk at a time
+/// through share_chunk. The evaluations in that format refer to them.
+pub extern "C" fn ffi_constraints_binary(ctx_handle: usize, ffi_circom: *mut FFICircom) {
+    ffi_guard(ctx_handle, || {
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
+            return;
+        };
+        crate::binary_export::produce_constraints_binary(
+            library,
+            crate::binary_export::CHUNK_SIZE,
+            |chunk| unsafe {
+                share_chunk(ctx_handle, chunk.as_ptr() as *const c_void, chunk.len());
+            },
+        );
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_circuit_execution_binary executes the inputs like ffi_circuit_execution,
+/// but shares the evaluation in the compact binary format of binary_export,
+/// one chunk at a time through share_chunk.
+pub extern "C" fn ffi_circuit_execution_binary(
+    ctx_handle: usize,
+    ffi_circom: *mut FFICircom,
+    inputs_json: *const c_char,
+    options_json: *const c_char,
+) {
+    ffi_guard(ctx_handle, || {
+        let mut buff = Vec::new();
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
+            return;
+        };
+        let inputs = ffi_str(inputs_json, ReportCode::MalformedInputJson)
+            .and_then(|inputs| Ok((inputs, ffi_eval_options(options_json)?)));
+        let (inputs_json_str, options) = match inputs {
+            Ok(inputs) => inputs,
+            Err(r) => {
+                report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                return;
+            }
+        };
+        match library.prepare_evaluation(inputs_json_str, &options) {
+            Ok((witness, scope, logs)) => {
+                crate::binary_export::produce_constraint_evaluation_binary(
+                    library,
+                    &witness,
+                    &scope,
+                    &logs,
+                    crate::binary_export::CHUNK_SIZE,
+                    |chunk| unsafe {
+                        share_chunk(ctx_handle, chunk.as_ptr() as *const c_void, chunk.len());
+                    },
+                );
+            }
+            Err(reports) => {
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
             }
         }
-        Err(v) => {
-            for r in v.iter() {
-                crate::reporting::report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_circuit_execution_batch executes a json array of inputs and shares
+/// a single batch evaluation (symbols once, then one entry per input)
+/// through share_buffer.
+pub extern "C" fn ffi_circuit_execution_batch(
+    ctx_handle: usize,
+    ffi_circom: *mut FFICircom,
+    inputs_json: *const c_char,
+    options_json: *const c_char,
+) {
+    ffi_guard(ctx_handle, || {
+        let mut buff = Vec::new();
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
+            return;
+        };
+        let inputs = ffi_str(inputs_json, ReportCode::MalformedInputJson)
+            .and_then(|inputs| Ok((inputs, ffi_eval_options(options_json)?)));
+        let (inputs_json_str, options) = match inputs {
+            Ok(inputs) => inputs,
+            Err(r) => {
+                report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                return;
+            }
+        };
+        match library.execute_batch_with(inputs_json_str, &options) {
+            Ok(evaluations) => {
+                let (x, y) = library.get_signals();
+                let batch_evaluation = crate::json_export::produce_batch_evaluation_json(
+                    library.field(),
+                    &evaluations,
+                    &x,
+                    &y,
+                    &library.get_public_signals(),
+                );
+                ffi_pass_buffer(ctx_handle, batch_evaluation.as_ptr(), batch_evaluation.len());
+            }
+            Err(reports) => {
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
             }
         }
-    }
-    // TODO: Optimize this
-    let ffi_lib = Box::new(FFICircom { inner: Box::into_raw(Box::new(library)) as *mut c_void });
-    unsafe {
-        share_circom_ptr(ctx_handle, Box::into_raw(ffi_lib));
-    }
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn ffi_circuit_execution(
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_detect_underconstrained mutates the witness of the inputs in search of
+/// underconstrained signals & shares the counterexamples it found as a json
+/// array through share_buffer, each with its report.
+pub extern "C" fn ffi_detect_underconstrained(
     ctx_handle: usize,
     ffi_circom: *mut FFICircom,
     inputs_json: *const c_char,
+    options_json: *const c_char,
 ) {
-    pub fn ffi_pass_evals(ctx_handle: usize, ptr: *const u8, len: usize) {
-        unsafe {
-            share_evaluations(ctx_handle, ptr as *const c_void, len);
+    ffi_guard(ctx_handle, || {
+        let mut buff = Vec::new();
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
+            return;
         };
-    }
-    let ffi_circom = unsafe { &mut *ffi_circom };
-    let mut buff = Vec::with_capacity(100_000);
+        let inputs = ffi_str(inputs_json, ReportCode::MalformedInputJson).and_then(|inputs| {
+            Ok((inputs, ffi_options::<MutationOptions>(options_json, "mutation")?))
+        });
+        let (inputs_json_str, options) = match inputs {
+            Ok(inputs) => inputs,
+            Err(r) => {
+                report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                return;
+            }
+        };
+        match library.detect_underconstrained(inputs_json_str, &options) {
+            Ok(counterexamples) => {
+                let json = crate::json_export::produce_counterexamples_json(&counterexamples);
+                ffi_pass_buffer(ctx_handle, json.as_ptr(), json.len());
+            }
+            Err(reports) => {
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
+            }
+        }
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_fuzz draws inputs for the library as the options say & shares the
+/// failures it found, with their shrunk reproducers, as json through share_buffer.
+pub extern "C" fn ffi_fuzz(
+    ctx_handle: usize,
+    ffi_circom: *mut FFICircom,
+    options_json: *const c_char,
+) {
+    ffi_guard(ctx_handle, || {
+        let mut buff = Vec::new();
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
+            return;
+        };
+        let fuzzing = ffi_options::<FuzzOptions>(options_json, "fuzzing")
+            .map_err(|r| vec![r])
+            .and_then(|options| library.fuzz(&options));
+        match fuzzing {
+            Ok(fuzzing) => {
+                let json = crate::json_export::produce_fuzzing_json(&fuzzing);
+                ffi_pass_buffer(ctx_handle, json.as_ptr(), json.len());
+            }
+            Err(reports) => {
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
+            }
+        }
+    })
+}
 
-    let inputs_json_str = unsafe { CStr::from_ptr(inputs_json) }.to_str().unwrap();
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_export_ast parses the programs of the pkg and shares
+/// their syntax trees as a json array through share_buffer.
+pub extern "C" fn ffi_export_ast(ctx_handle: usize, pkg_json_raw: *const c_char) {
+    ffi_guard(ctx_handle, || {
+        let mut buff = Vec::new();
+        let circuit_pkg = match ffi_pkg(pkg_json_raw) {
+            Ok(circuit_pkg) => circuit_pkg,
+            Err(r) => {
+                report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                return;
+            }
+        };
 
-    let library = unsafe { &mut *(ffi_circom.inner as *mut CircuitLibrary) };
-    match library.execute(inputs_json_str) {
-        Ok((witness, records)) => {
-            let (x, y) = library.get_signals();
-            let constraint_evaluation =
-                crate::json_export::produce_constraint_evaluation_json(&records, &x, &y, &witness);
-            ffi_pass_evals(ctx_handle, constraint_evaluation.as_ptr(), constraint_evaluation.len());
+        let mut library = crate::circuit::CircuitLibrary::default();
+        match library.export_ast(&circuit_pkg) {
+            Ok(ast) => ffi_pass_buffer(ctx_handle, ast.as_ptr(), ast.len()),
+            Err(reports) => {
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
+            }
         }
-        Err(report) => {
-            for report in report.iter() {
-                crate::reporting::report_diagnostic(ctx_handle, &report.to_diagnostic(), &mut buff);
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_export_r1cs shares the r1cs binary of a compiled library through share_buffer.
+pub extern "C" fn ffi_export_r1cs(ctx_handle: usize, ffi_circom: *mut FFICircom) {
+    ffi_guard(ctx_handle, || {
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
+            return;
+        };
+        match library.export_r1cs() {
+            Ok(r1cs) => ffi_pass_buffer(ctx_handle, r1cs.as_ptr(), r1cs.len()),
+            Err(reports) => {
+                let mut buff = Vec::new();
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
             }
         }
-    }
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_export_wtns calculates the witness for the inputs and shares it
+/// in the wtns binary format through share_buffer.
+pub extern "C" fn ffi_export_wtns(
+    ctx_handle: usize,
+    ffi_circom: *mut FFICircom,
+    inputs_json: *const c_char,
+) {
+    ffi_guard(ctx_handle, || {
+        let mut buff = Vec::new();
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
+            return;
+        };
+        let inputs_json_str = match ffi_str(inputs_json, ReportCode::MalformedInputJson) {
+            Ok(inputs_json_str) => inputs_json_str,
+            Err(r) => {
+                report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                return;
+            }
+        };
+        match library.export_wtns(inputs_json_str) {
+            Ok(wtns) => ffi_pass_buffer(ctx_handle, wtns.as_ptr(), wtns.len()),
+            Err(reports) => {
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
+            }
+        }
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_export_wasm shares the witness calculation wasm module through share_buffer.
+pub extern "C" fn ffi_export_wasm(ctx_handle: usize, ffi_circom: *mut FFICircom) {
+    ffi_guard(ctx_handle, || {
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
+            return;
+        };
+        match library.export_wasm() {
+            Ok(wasm) => ffi_pass_buffer(ctx_handle, wasm.as_ptr(), wasm.len()),
+            Err(reports) => {
+                let mut buff = Vec::new();
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
+            }
+        }
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_export_sym shares the content of the .sym file through share_buffer.
+pub extern "C" fn ffi_export_sym(ctx_handle: usize, ffi_circom: *mut FFICircom) {
+    ffi_guard(ctx_handle, || {
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
+            return;
+        };
+        match library.export_sym() {
+            Ok(sym) => ffi_pass_buffer(ctx_handle, sym.as_ptr(), sym.len()),
+            Err(reports) => {
+                let mut buff = Vec::new();
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
+            }
+        }
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_template_statistics shares the statistics of the template instances of
+/// the compiled main as a json array through share_buffer.
+pub extern "C" fn ffi_template_statistics(ctx_handle: usize, ffi_circom: *mut FFICircom) {
+    ffi_guard(ctx_handle, || {
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
+            return;
+        };
+        match library.template_statistics() {
+            Ok(statistics) => {
+                let json = crate::json_export::produce_template_statistics_json(&statistics);
+                ffi_pass_buffer(ctx_handle, json.as_ptr(), json.len())
+            }
+            Err(reports) => {
+                let mut buff = Vec::new();
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
+            }
+        }
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_save_library shares a snapshot of the compiled library through share_buffer,
+/// which ffi_load_library restores without compiling the pkg again.
+pub extern "C" fn ffi_save_library(ctx_handle: usize, ffi_circom: *mut FFICircom) {
+    ffi_guard(ctx_handle, || {
+        let Some(library) = ffi_library(ctx_handle, ffi_circom) else {
+            return;
+        };
+        match library.save() {
+            Ok(snapshot) => ffi_pass_buffer(ctx_handle, snapshot.as_ptr(), snapshot.len()),
+            Err(reports) => {
+                let mut buff = Vec::new();
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
+            }
+        }
+    })
+}
+
+#[no_mangle]
+/// ffi_snapshot_fingerprint shares, through share_buffer, the fingerprint
+/// of the snapshots this build saves & loads.
+pub extern "C" fn ffi_snapshot_fingerprint(ctx_handle: usize) {
+    ffi_guard(ctx_handle, || {
+        let fingerprint = crate::snapshot::snapshot_fingerprint();
+        ffi_pass_buffer(ctx_handle, fingerprint.as_ptr(), fingerprint.len())
+    })
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+/// ffi_load_library restores a library saved by ffi_save_library
+/// and shares it through share_circom_ptr.
+/// The snapshot holds native code, only pass snapshots saved by this host.
+pub extern "C" fn ffi_load_library(ctx_handle: usize, snapshot: *const u8, len: usize) {
+    ffi_guard(ctx_handle, || {
+        let mut buff = Vec::new();
+        if snapshot.is_null() {
+            let r = produce_report_with_message(
+                ReportCode::MalformedLibrary,
+                "received a null snapshot".to_string(),
+            );
+            report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+            return;
+        }
+        let snapshot = unsafe { std::slice::from_raw_parts(snapshot, len) };
+        match CircuitLibrary::load(snapshot) {
+            Ok(library) => {
+                let ffi_lib =
+                    Box::new(FFICircom { inner: Box::into_raw(Box::new(library)) as *mut c_void });
+                unsafe {
+                    share_circom_ptr(ctx_handle, Box::into_raw(ffi_lib));
+                }
+            }
+            Err(reports) => {
+                for r in reports.iter() {
+                    report_diagnostic(ctx_handle, &r.to_diagnostic(), &mut buff);
+                }
+            }
+        }
+    })
 }
 
 #[no_mangle]
@@ -97,9 +656,9 @@ pub extern "C" fn ffi_circuit_execution(
 /// This function is called in GO code to free the memory allocated by the Rust code.
 pub extern "C" fn free_string(s: *mut c_char) {
     if !s.is_null() {
-        unsafe {
+        let _ = catch_unwind(|| unsafe {
             drop(CString::from_raw(s));
-        }
+        });
     }
 }
 
@@ -108,9 +667,12 @@ pub extern "C" fn free_string(s: *mut c_char) {
 /// This function is called in GO code to free the memory allocated by the Rust code.
 pub extern "C" fn free_circom(ffi_circom: *mut FFICircom) {
     if !ffi_circom.is_null() {
-        unsafe {
-            let _ = Box::from_raw(ffi_circom);
-        }
+        let _ = catch_unwind(|| unsafe {
+            let ffi_circom = Box::from_raw(ffi_circom);
+            if !ffi_circom.inner.is_null() {
+                drop(Box::from_raw(ffi_circom.inner as *mut CircuitLibrary));
+            }
+        });
     }
 }
 
@@ -119,3 +681,9 @@ pub fn ffi_pass_report(ctx_handle: usize, ptr: *const u8, len: usize) {
         share_report(ctx_handle, ptr as *const c_void, len);
     };
 }
+
+pub fn ffi_pass_buffer(ctx_handle: usize, ptr: *const u8, len: usize) {
+    unsafe {
+        share_buffer(ctx_handle, ptr as *const c_void, len);
+    };
+}
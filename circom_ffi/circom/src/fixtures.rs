@@ -0,0 +1,36 @@
+// Fixtures shared by the tests of the modules: the libraries they evaluate,
+// compiled from the source of a single main program.
+use crate::circuit::{create_default_circuit_pkg, CircuitLibrary, CircuitPkg, Program};
+
+/// The main of a single constraint: out <== a * a.
+pub const SQUARE: &str = indoc::indoc! {"
+    template Square(){
+        signal input a;
+        signal output out;
+        out <== a * a;
+    }
+    component main = Square();
+"};
+
+pub fn program(identity: &str, src: &str) -> Program {
+    Program { identity: identity.to_string(), src: src.to_string() }
+}
+
+/// The default pkg of a single program holding the main component.
+pub fn main_pkg(src: &str) -> CircuitPkg {
+    create_default_circuit_pkg(&vec![program("main", src)])
+}
+
+/// The library compiled from the pkg, which must compile.
+pub fn compiled(pkg: &CircuitPkg) -> CircuitLibrary {
+    let mut library = CircuitLibrary::default();
+    if let Err(reports) = library.compile(pkg) {
+        panic!("compilation failed: {}", reports[0].get_message());
+    }
+    library
+}
+
+/// The library compiled from the default pkg of the main program.
+pub fn compile_main(src: &str) -> CircuitLibrary {
+    compiled(&main_pkg(src))
+}
@@ -0,0 +1,442 @@
+// Property based fuzzing of a circuit library.
+//
+// Inputs shaped like the input signals of the main component are drawn from
+// random field elements, boundary values (0, 1, p - 1, 2^k & 2^k - 1) or the
+// ranges given for the signals. Every input failing the witness calculation
+// (a trap of the witness calculator) or the constraints is shrunk towards 0
+// into a minimal reproducer that fails the same way. The inputs rejected by
+// the checks of the inputs are told apart, as the circuit is not at fault.
+pub extern crate num_bigint_dig as num_bigint;
+
+use num_bigint::{BigInt, Sign};
+use program_structure::{error_code::ReportCode, error_definition::ReportCollection};
+use program_structure::error_definition::Report;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::circuit::Evaluation;
+use crate::reporting::produce_report_with_message;
+use crate::witness::{CircuitInputs, InputSignal, InputValues};
+
+/// Options of a fuzzing session.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct FuzzOptions {
+    // seed of the generator, the same seed draws the same inputs
+    pub seed: u64,
+    // number of inputs drawn
+    pub runs: usize,
+    // inclusive ranges the values of an input signal are drawn from, by name
+    pub ranges: HashMap<String, FuzzRange>,
+    // stop once this many distinct failures are found
+    pub max_failures: usize,
+    // evaluations spent shrinking each failing input
+    pub shrink_budget: usize,
+}
+
+impl Default for FuzzOptions {
+    fn default() -> Self {
+        FuzzOptions {
+            seed: 0,
+            runs: 100,
+            ranges: HashMap::new(),
+            max_failures: 8,
+            shrink_budget: 512,
+        }
+    }
+}
+
+/// Inclusive range of values, in decimal.
+#[derive(Deserialize, Clone)]
+pub struct FuzzRange {
+    pub min: String,
+    pub max: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FailureKind {
+    // the witness calculation trapped: a failed assert, a division by zero...
+    Trap,
+    // the witness does not satisfy the constraints
+    Unsatisfied,
+    // the inputs were rejected before the witness calculation, e.g. out of bounds
+    Rejected,
+}
+
+impl std::fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailureKind::Trap => f.write_str("trap"),
+            FailureKind::Unsatisfied => f.write_str("unsatisfied"),
+            FailureKind::Rejected => f.write_str("rejected"),
+        }
+    }
+}
+
+/// Inputs that failed the same way once shrunk.
+pub struct FuzzFailure {
+    pub kind: FailureKind,
+    // first run that failed this way & the number of runs that did
+    pub run: usize,
+    pub occurrences: usize,
+    // values of the signals of the session, as drawn & once shrunk
+    pub input: Vec<Vec<BigInt>>,
+    pub reproducer: Vec<Vec<BigInt>>,
+    // reports of the reproducer, one per unsatisfied constraint for the constraints
+    pub reports: ReportCollection,
+}
+
+pub struct Fuzzing {
+    pub seed: u64,
+    pub runs: usize,
+    // the input signals values are drawn for, the buses field by field
+    pub signals: Vec<InputSignal>,
+    pub failures: Vec<FuzzFailure>,
+}
+
+// SplitMix64, a fixed generator keeps the seeds reproducible across builds
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn index(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    // a value in [0, bound)
+    fn below(&mut self, bound: &BigInt) -> BigInt {
+        let words = bound.bits() / 64 + 2;
+        let bytes: Vec<u8> = (0..words).flat_map(|_| self.next_u64().to_le_bytes()).collect();
+        BigInt::from_bytes_le(Sign::Plus, &bytes) % bound
+    }
+}
+
+struct Draw {
+    min: BigInt,
+    max: BigInt,
+    // the range covers the whole field
+    field: bool,
+}
+
+impl Draw {
+    // a value of the range half of the time, a boundary otherwise
+    fn value(&self, rng: &mut Rng) -> BigInt {
+        let mut boundaries = match rng.index(4) {
+            0 | 1 => return &self.min + rng.below(&(&self.max - &self.min + 1)),
+            2 if self.field => vec![BigInt::from(0), BigInt::from(1), self.max.clone()],
+            2 => vec![self.min.clone(), self.max.clone()],
+            _ => (0..self.max.bits())
+                .flat_map(|k| [BigInt::from(1) << k, (BigInt::from(1) << k) - 1])
+                .filter(|v| &self.min <= v && v <= &self.max)
+                .collect(),
+        };
+        if boundaries.is_empty() {
+            return self.min.clone();
+        }
+        let i = rng.index(boundaries.len());
+        boundaries.swap_remove(i)
+    }
+}
+
+// the input signals values are drawn for, a bus is drawn field by field
+fn drawn_signals(signature: &[InputSignal]) -> Vec<InputSignal> {
+    let is_bus = |signal: &InputSignal| {
+        signature.iter().any(|other| {
+            other.name.strip_prefix(signal.name.as_str()).is_some_and(|rest| {
+                rest.starts_with('.') || (rest.starts_with('[') && rest.contains('.'))
+            })
+        })
+    };
+    signature.iter().filter(|s| !is_bus(s)).cloned().collect()
+}
+
+fn parse_range(name: &str, range: &FuzzRange, field: &BigInt) -> Result<(BigInt, BigInt), Report> {
+    let parse = |value: &str| BigInt::parse_bytes(value.as_bytes(), 10);
+    match (parse(&range.min), parse(&range.max)) {
+        (Some(min), Some(max)) if BigInt::from(0) <= min && min <= max && &max < field => {
+            Ok((min, max))
+        }
+        _ => Err(produce_report_with_message(
+            ReportCode::MalformedInputJson,
+            format!("invalid range [{}, {}] of {}", range.min, range.max, name),
+        )),
+    }
+}
+
+fn to_inputs(signals: &[InputSignal], values: &[Vec<BigInt>]) -> CircuitInputs {
+    signals
+        .iter()
+        .zip(values.iter())
+        .map(|(s, v)| (s.name.clone(), InputValues { shape: s.shape(), values: v.clone() }))
+        .collect()
+}
+
+// a failed evaluation
+struct Failed {
+    kind: FailureKind,
+    reports: ReportCollection,
+    // ids of the unsatisfied constraints
+    constraints: Vec<usize>,
+}
+
+// the codes of the checks of the inputs, against the main component
+// & in the witness calculator
+fn rejects_inputs(code: &ReportCode) -> bool {
+    matches!(
+        code,
+        ReportCode::MalformedInputJson
+            | ReportCode::MissingInputSignal
+            | ReportCode::UnknownInputSignal
+            | ReportCode::InputSignalSizeMismatch
+            | ReportCode::WitnessSignalNotFound
+            | ReportCode::WitnessTooManyInputs
+            | ReportCode::WitnessSignalAlreadySet
+            | ReportCode::WitnessInputOutOfBounds
+            | ReportCode::WitnessMissingInputs
+    )
+}
+
+// how the evaluation failed, if it did
+fn failure(evaluation: Evaluation) -> Option<Failed> {
+    match evaluation {
+        Err(reports) => {
+            let rejected = reports.first().is_some_and(|r| rejects_inputs(r.get_code()));
+            let kind = if rejected { FailureKind::Rejected } else { FailureKind::Trap };
+            Some(Failed { kind, reports, constraints: Vec::new() })
+        }
+        Ok((_, records, _)) => {
+            let (constraints, reports): (Vec<usize>, ReportCollection) =
+                records.into_iter().filter_map(|r| Some((r.id, r.report?))).unzip();
+            if reports.is_empty() {
+                None
+            } else {
+                Some(Failed { kind: FailureKind::Unsatisfied, reports, constraints })
+            }
+        }
+    }
+}
+
+// whether two failures are the same: of the same kind, with the same
+// constraints unsatisfied or else the same report code
+fn same_failure(a: &Failed, b: &Failed) -> bool {
+    let code = |failed: &Failed| failed.reports.first().map(|r| r.get_code().to_string());
+    a.kind == b.kind
+        && match a.kind {
+            FailureKind::Unsatisfied => a.constraints == b.constraints,
+            FailureKind::Trap | FailureKind::Rejected => code(a) == code(b),
+        }
+}
+
+// shrink the values one at a time, each to 0 or else to the smallest value
+// the bisection finds failing the same way, until no value shrinks anymore
+fn shrink<E: Fn(CircuitInputs) -> Evaluation>(
+    signals: &[InputSignal],
+    values: &[Vec<BigInt>],
+    failed: Failed,
+    budget: usize,
+    evaluate: &E,
+) -> (Vec<Vec<BigInt>>, ReportCollection) {
+    let mut values = values.to_vec();
+    let mut failed = failed;
+    let mut budget = budget;
+    let mut shrunk = true;
+    while shrunk {
+        shrunk = false;
+        for i in 0..values.len() {
+            for j in 0..values[i].len() {
+                // lo does not fail the same way, hi does
+                let (mut lo, mut hi) = (None, values[i][j].clone());
+                while lo.as_ref().map_or(hi > BigInt::from(0), |lo| &hi - lo > BigInt::from(1)) {
+                    if budget == 0 {
+                        return (values, failed.reports);
+                    }
+                    budget -= 1;
+                    let candidate = match &lo {
+                        None => BigInt::from(0),
+                        Some(lo) => (lo + &hi) / 2,
+                    };
+                    values[i][j] = candidate.clone();
+                    match failure(evaluate(to_inputs(signals, &values))) {
+                        Some(f) if same_failure(&f, &failed) => {
+                            failed = f;
+                            hi = candidate;
+                            shrunk = true;
+                        }
+                        _ => lo = Some(candidate),
+                    }
+                }
+                values[i][j] = hi;
+            }
+        }
+    }
+    (values, failed.reports)
+}
+
+/// Fuzzes the evaluation of the inputs of a main component with the given signature.
+pub fn fuzz<E: Fn(CircuitInputs) -> Evaluation>(
+    signature: &[InputSignal],
+    field: &BigInt,
+    options: &FuzzOptions,
+    evaluate: E,
+) -> Result<Fuzzing, Report> {
+    let signals = drawn_signals(signature);
+    let mut draws = Vec::new();
+    for signal in signals.iter() {
+        let draw = match options.ranges.get(&signal.name) {
+            Some(range) => {
+                let (min, max) = parse_range(&signal.name, range, field)?;
+                Draw { min, max, field: false }
+            }
+            None => Draw { min: BigInt::from(0), max: field - 1, field: true },
+        };
+        draws.push(draw);
+    }
+    if let Some(name) = options.ranges.keys().find(|name| !signals.iter().any(|s| &s.name == *name))
+    {
+        return Err(produce_report_with_message(ReportCode::UnknownInputSignal, name.clone()));
+    }
+
+    let mut rng = Rng(options.seed);
+    let mut fuzzing =
+        Fuzzing { seed: options.seed, runs: 0, signals: signals.clone(), failures: Vec::new() };
+    for run in 0..options.runs {
+        if fuzzing.failures.len() >= options.max_failures {
+            break;
+        }
+        fuzzing.runs += 1;
+        let values: Vec<Vec<BigInt>> = signals
+            .iter()
+            .zip(draws.iter())
+            .map(|(signal, draw)| (0..signal.size).map(|_| draw.value(&mut rng)).collect())
+            .collect();
+        let Some(failed) = failure(evaluate(to_inputs(&signals, &values))) else {
+            continue;
+        };
+        let kind = failed.kind;
+        let (reproducer, reports) =
+            shrink(&signals, &values, failed, options.shrink_budget, &evaluate);
+        match fuzzing.failures.iter_mut().find(|f| f.kind == kind && f.reproducer == reproducer) {
+            Some(known) => known.occurrences += 1,
+            None => fuzzing.failures.push(FuzzFailure {
+                kind,
+                run,
+                occurrences: 1,
+                input: values,
+                reproducer,
+                reports,
+            }),
+        }
+    }
+    Ok(fuzzing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint_system::EvalOptions;
+    use crate::fixtures::compile_main;
+
+    #[test]
+    fn fuzz_and_shrink_failing_inputs() {
+        let src = indoc::indoc! {"
+            template Fuzzed(){
+                signal input a;
+                signal input b[2];
+                signal output c;
+                assert(a < 1000);
+                signal sq <== b[0] * b[0];
+                c <-- b[0] < 100 ? b[0] : 0;
+                c * c === sq;
+            }
+            component main = Fuzzed();
+        "};
+        let library = compile_main(src);
+        let options = FuzzOptions { seed: 7, runs: 10, ..FuzzOptions::default() };
+        let Ok(fuzzing) = library.fuzz(&options) else { panic!("fuzzing failed") };
+        let reproducer = |kind: FailureKind| {
+            let failure = fuzzing.failures.iter().find(|f| f.kind == kind).unwrap();
+            failure.reproducer.iter().map(|v| v.iter().map(|v| v.to_string()).collect()).collect()
+        };
+        let reproducer: Vec<Vec<Vec<String>>> =
+            vec![reproducer(FailureKind::Trap), reproducer(FailureKind::Unsatisfied)];
+        assert_eq!(
+            reproducer,
+            vec![
+                vec![vec!["1000".to_string()], vec!["0".to_string(), "0".to_string()]],
+                vec![vec!["0".to_string()], vec!["100".to_string(), "0".to_string()]],
+            ]
+        );
+
+        // the seed makes the session reproducible
+        let json = crate::json_export::produce_fuzzing_json(&fuzzing);
+        let Ok(again) = library.fuzz(&options) else { panic!("fuzzing failed") };
+        assert_eq!(crate::json_export::produce_fuzzing_json(&again), json);
+        let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        let trap = json["failures"].as_array().unwrap().iter().find(|f| f["kind"] == "trap");
+        assert_eq!(trap.unwrap()["reproducer"], serde_json::json!({"a": "1000", "b": ["0", "0"]}));
+
+        // drawn from its range, a never trips the assert
+        let mut ranged = options.clone();
+        let range = FuzzRange { min: "0".to_string(), max: "999".to_string() };
+        ranged.ranges.insert("a".to_string(), range.clone());
+        let Ok(fuzzing) = library.fuzz(&ranged) else { panic!("fuzzing failed") };
+        assert!(fuzzing.failures.iter().all(|f| f.kind == FailureKind::Unsatisfied));
+
+        ranged.ranges.insert("x".to_string(), range);
+        match library.fuzz(&ranged) {
+            Ok(_) => panic!("x is not an input signal"),
+            Err(reports) => {
+                assert!(matches!(reports[0].get_code(), ReportCode::UnknownInputSignal))
+            }
+        }
+    }
+
+    #[test]
+    fn tell_failures_apart() {
+        let src = indoc::indoc! {"
+            template Pair(){
+                signal input a;
+                signal input b;
+                signal x;
+                signal y;
+                assert(a != 7);
+                x <-- a;
+                y <-- b;
+                x * x === 1;
+                y * y === 1;
+            }
+            component main = Pair();
+        "};
+        let library = compile_main(src);
+        let carry_on = EvalOptions { skip_constraint_asserts: true, ..Default::default() };
+        let failed = |input: &str| failure(library.execute_with(input, &carry_on)).unwrap();
+
+        // the unsatisfied failures break the same constraints
+        let (a, b, both) = (
+            failed(r#"{"a": 0, "b": 1}"#),
+            failed(r#"{"a": 1, "b": 0}"#),
+            failed(r#"{"a": 0, "b": 0}"#),
+        );
+        assert!(a.kind == FailureKind::Unsatisfied && a.constraints.len() == 1);
+        assert!(same_failure(&a, &failed(r#"{"a": 5, "b": 1}"#)));
+        assert!(!same_failure(&a, &b));
+        assert!(!same_failure(&a, &both));
+
+        // the traps are told apart by their report code, from the inputs the checks
+        // reject, which are no fault of the circuit
+        let trap = failed(r#"{"a": 7, "b": 1}"#);
+        assert_eq!(trap.kind, FailureKind::Trap);
+        let rejected = failed(r#"{"a": [0, 1], "b": 1}"#);
+        assert_eq!(rejected.kind, FailureKind::Rejected);
+        assert!(!same_failure(&rejected, &failed(r#"{"a": 0}"#)));
+        assert!(same_failure(&rejected, &failed(r#"{"a": [0, 2], "b": 5}"#)));
+        assert!(failure(library.execute_with(r#"{"a": 1, "b": 1}"#, &carry_on)).is_none());
+    }
+}
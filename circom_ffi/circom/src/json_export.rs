@@ -2,60 +2,36 @@ pub extern crate num_bigint_dig as num_bigint;
 pub extern crate num_traits;
 use num_bigint::BigInt;
 use jsonbb::Builder;
+use std::convert::TryFrom;
 
 use constraint_writers::sym_writer::SymElem;
 use program_structure::ast::*;
+use program_structure::error_definition::Report;
 
+use crate::circuit::Evaluation;
 use crate::constraint_system::*;
+use crate::fuzz::Fuzzing;
+use crate::statistics::{Counts, TemplateStatistics};
+use crate::underconstrained::Counterexample;
+use crate::witness::InputSignal;
+use crate::witness::Logs;
 
 pub fn produce_constraint_evaluation_json(
+    field: &BigInt,
     records: &LCRecords,
     constrained: &Vec<&SymElem>,
     unconstrained: &Vec<&SymElem>,
+    public: &PublicSignals,
     assignments: &Vec<BigInt>,
+    logs: &Logs,
 ) -> String {
     let mut builder = Builder::<Vec<u8>>::new();
     builder.begin_object();
     builder.add_string("field");
-    builder.add_string(&records[0].field.to_string());
+    builder.add_string(&field.to_string());
 
     // create json objects for symbols
-    builder.add_string("symbols");
-    builder.begin_object();
-
-    builder.add_string("constrained");
-    builder.begin_array();
-    for sym in constrained {
-        builder.begin_object();
-        builder.add_string("original");
-        builder.add_string(&sym.original.to_string());
-        builder.add_string("witness");
-        builder.add_string(&sym.witness.to_string());
-        builder.add_string("node_id");
-        builder.add_string(&sym.node_id.to_string());
-        builder.add_string("symbol");
-        builder.add_string(&sym.symbol);
-        builder.end_object();
-    }
-    builder.end_array();
-
-    builder.add_string("unconstrained");
-    builder.begin_array();
-    for sym in unconstrained {
-        builder.begin_object();
-        builder.add_string("original");
-        builder.add_string(&sym.original.to_string());
-        builder.add_string("witness");
-        builder.add_string(&sym.witness.to_string());
-        builder.add_string("node_id");
-        builder.add_string(&sym.node_id.to_string());
-        builder.add_string("symbol");
-        builder.add_string(&sym.symbol);
-        builder.end_object();
-    }
-    builder.end_array();
-
-    builder.end_object();
+    symbols_json(constrained, unconstrained, &mut builder);
 
     builder.add_string("assignments");
     builder.begin_array();
@@ -64,11 +40,16 @@ pub fn produce_constraint_evaluation_json(
     }
     builder.end_array();
 
+    public_json(public, assignments, &mut builder);
+    logs_json(logs, &mut builder);
+
     builder.add_string("constraints");
     builder.begin_array();
     for r in records {
         builder.begin_object();
 
+        builder.add_string("id");
+        builder.add_u64(r.id as u64);
         builder.add_string("a_constraints");
         builder.begin_array();
         for c in &r.a_constraints {
@@ -107,29 +88,589 @@ pub fn produce_constraint_evaluation_json(
 
         builder.end_array();
         builder.add_string("satisfied");
-        match &r.report {
-            Some(r) => {
-                builder.add_string("false");
+        builder.add_bool(r.report.is_none());
+        if let Some(r) = &r.report {
+            builder.add_string("report");
+            report_json(r, &mut builder);
+        }
+        builder.end_object();
+    }
+    builder.end_array();
+    builder.end_object();
+
+    let json = builder.finish();
+    json.to_string()
+}
+
+pub fn produce_batch_evaluation_json(
+    field: &BigInt,
+    evaluations: &Vec<Evaluation>,
+    constrained: &Vec<&SymElem>,
+    unconstrained: &Vec<&SymElem>,
+    public: &PublicSignals,
+) -> String {
+    let mut builder = Builder::<Vec<u8>>::new();
+    builder.begin_object();
+    builder.add_string("field");
+    builder.add_string(&field.to_string());
+
+    // the symbols are shared by every evaluation of the batch
+    symbols_json(constrained, unconstrained, &mut builder);
+
+    builder.add_string("evaluations");
+    builder.begin_array();
+    for evaluation in evaluations {
+        builder.begin_object();
+        match evaluation {
+            Ok((assignments, records, logs)) => {
+                builder.add_string("assignments");
+                builder.begin_array();
+                for assignment in assignments {
+                    builder.add_string(&assignment.to_string());
+                }
+                builder.end_array();
+                public_json(public, assignments, &mut builder);
+                logs_json(logs, &mut builder);
+
+                let unsatisfied: Vec<usize> =
+                    records.iter().filter(|r| r.report.is_some()).map(|r| r.id).collect();
+                builder.add_string("satisfied");
+                builder.add_bool(unsatisfied.is_empty());
+                builder.add_string("unsatisfied_constraints");
+                builder.begin_array();
+                for i in unsatisfied {
+                    builder.add_u64(i as u64);
+                }
+                builder.end_array();
             }
-            None => {
-                builder.add_string("true");
+            Err(reports) => {
+                builder.add_string("reports");
+                builder.begin_array();
+                for report in reports {
+                    report_json(report, &mut builder);
+                }
+                builder.end_array();
+            }
+        }
+        builder.end_object();
+    }
+    builder.end_array();
+    builder.end_object();
+
+    let json = builder.finish();
+    json.to_string()
+}
+
+// the counterexamples of an underconstrained signal analysis,
+// each with the signals it mutates & its report
+pub fn produce_counterexamples_json(counterexamples: &[Counterexample]) -> String {
+    let mut builder = Builder::<Vec<u8>>::new();
+    builder.begin_array();
+    for counterexample in counterexamples {
+        builder.begin_object();
+        builder.add_string("signals");
+        builder.begin_array();
+        for s in counterexample.signals.iter() {
+            builder.begin_object();
+            builder.add_string("symbol");
+            builder.add_string(&s.symbol);
+            builder.add_string("witness");
+            builder.add_u64(s.witness as u64);
+            builder.add_string("value");
+            builder.add_string(&s.value.to_string());
+            builder.add_string("alternative");
+            builder.add_string(&s.alternative.to_string());
+            builder.end_object();
+        }
+        builder.end_array();
+        builder.add_string("report");
+        report_json(&counterexample.report(), &mut builder);
+        builder.end_object();
+    }
+    builder.end_array();
+
+    let json = builder.finish();
+    json.to_string()
+}
+
+// the statistics of the template instances, each with its own & cumulative counts
+pub fn produce_template_statistics_json(statistics: &[TemplateStatistics]) -> String {
+    let mut builder = Builder::<Vec<u8>>::new();
+    builder.begin_array();
+    for s in statistics {
+        builder.begin_object();
+        builder.add_string("path");
+        builder.add_string(&s.instance.path);
+        builder.add_string("template");
+        builder.add_string(&s.instance.template);
+        builder.add_string("parameters");
+        builder.begin_array();
+        for parameter in s.instance.parameters.iter() {
+            builder.add_string(&parameter.to_string());
+        }
+        builder.end_array();
+        builder.add_string("node");
+        builder.add_u64(s.instance.node as u64);
+        builder.add_string("components");
+        builder.add_u64(s.instance.components as u64);
+        builder.add_string("times_reached");
+        builder.add_u64(s.instance.times_reached as u64);
+        builder.add_string("own");
+        counts_json(&s.own, &mut builder);
+        builder.add_string("cumulative");
+        counts_json(&s.cumulative, &mut builder);
+        builder.end_object();
+    }
+    builder.end_array();
+
+    let json = builder.finish();
+    json.to_string()
+}
+
+// the programs of a library, with the identities the report labels are attached to
+pub fn produce_programs_json(programs: &[(String, String)]) -> String {
+    let mut builder = Builder::<Vec<u8>>::new();
+    builder.begin_array();
+    for (identity, src) in programs {
+        builder.begin_object();
+        builder.add_string("identity");
+        builder.add_string(identity);
+        builder.add_string("src");
+        builder.add_string(src);
+        builder.end_object();
+    }
+    builder.end_array();
+
+    let json = builder.finish();
+    json.to_string()
+}
+
+fn counts_json(counts: &Counts, builder: &mut Builder<Vec<u8>>) {
+    let pairs = |builder: &mut Builder<Vec<u8>>, pairs: &[(&str, usize)]| {
+        builder.begin_object();
+        for (key, value) in pairs {
+            builder.add_string(key);
+            builder.add_u64(*value as u64);
+        }
+        builder.end_object();
+    };
+    builder.begin_object();
+    builder.add_string("signals");
+    pairs(
+        builder,
+        &[
+            ("inputs", counts.inputs),
+            ("outputs", counts.outputs),
+            ("intermediates", counts.intermediates),
+        ],
+    );
+    builder.add_string("generated");
+    pairs(
+        builder,
+        &[("linear", counts.generated_linear), ("non_linear", counts.generated_non_linear)],
+    );
+    builder.add_string("simplified");
+    pairs(
+        builder,
+        &[("linear", counts.simplified_linear), ("non_linear", counts.simplified_non_linear)],
+    );
+    builder.end_object();
+}
+
+// the failures of a fuzzing session, with their inputs shaped like the signals
+pub fn produce_fuzzing_json(fuzzing: &Fuzzing) -> String {
+    let mut builder = Builder::<Vec<u8>>::new();
+    builder.begin_object();
+    builder.add_string("seed");
+    builder.add_u64(fuzzing.seed);
+    builder.add_string("runs");
+    builder.add_u64(fuzzing.runs as u64);
+    builder.add_string("failures");
+    builder.begin_array();
+    for failure in fuzzing.failures.iter() {
+        builder.begin_object();
+        builder.add_string("kind");
+        builder.add_string(&failure.kind.to_string());
+        builder.add_string("run");
+        builder.add_u64(failure.run as u64);
+        builder.add_string("occurrences");
+        builder.add_u64(failure.occurrences as u64);
+        builder.add_string("input");
+        fuzz_input_json(&fuzzing.signals, &failure.input, &mut builder);
+        builder.add_string("reproducer");
+        fuzz_input_json(&fuzzing.signals, &failure.reproducer, &mut builder);
+        builder.add_string("reports");
+        builder.begin_array();
+        for report in failure.reports.iter() {
+            report_json(report, &mut builder);
+        }
+        builder.end_array();
+        builder.end_object();
+    }
+    builder.end_array();
+    builder.end_object();
+
+    let json = builder.finish();
+    json.to_string()
+}
+
+// an input object, the values of an array in nested arrays
+fn fuzz_input_json(
+    signals: &[InputSignal],
+    values: &[Vec<BigInt>],
+    builder: &mut Builder<Vec<u8>>,
+) {
+    fn nested(dimensions: &[usize], values: &[BigInt], builder: &mut Builder<Vec<u8>>) {
+        match dimensions.split_first() {
+            None => builder.add_string(&values[0].to_string()),
+            Some((dimension, rest)) => {
+                builder.begin_array();
+                let stride = rest.iter().product::<usize>();
+                for i in 0..*dimension {
+                    nested(rest, &values[i * stride..(i + 1) * stride], builder);
+                }
+                builder.end_array();
+            }
+        }
+    }
+    builder.begin_object();
+    for (signal, values) in signals.iter().zip(values.iter()) {
+        builder.add_string(&signal.name);
+        if signal.dimensions.iter().product::<usize>() == values.len() {
+            nested(&signal.dimensions, values, builder);
+        } else {
+            // the values of a bus array field are not shaped by its dimensions
+            builder.begin_array();
+            for value in values {
+                builder.add_string(&value.to_string());
+            }
+            builder.end_array();
+        }
+    }
+    builder.end_object();
+}
+
+// the public signals as a snarkjs public.json lists them,
+// & the outputs of the main component in the shape they are declared with
+pub fn public_json(public: &PublicSignals, assignments: &[BigInt], builder: &mut Builder<Vec<u8>>) {
+    builder.add_string("public");
+    builder.begin_array();
+    for assignment in assignments.iter().skip(1).take(public.size) {
+        builder.add_string(&assignment.to_string());
+    }
+    builder.end_array();
+
+    let mut outputs = Output::Fields(Vec::new());
+    for sym in public.outputs.iter() {
+        let name = sym.symbol.strip_prefix("main.").unwrap_or(&sym.symbol);
+        let value = usize::try_from(sym.witness).ok().and_then(|w| assignments.get(w));
+        outputs.insert(&output_path(name), value.map(|v| v.to_string()));
+    }
+    builder.add_string("outputs");
+    outputs.to_json(builder);
+}
+
+enum Step<'a> {
+    Field(&'a str),
+    Index(usize),
+}
+
+// "out.p[1][0].x" is out, p, 1, 0, x
+fn output_path(name: &str) -> Vec<Step<'_>> {
+    let mut path = Vec::new();
+    for part in name.split('.') {
+        let mut accesses = part.split('[');
+        path.push(Step::Field(accesses.next().unwrap_or_default()));
+        for access in accesses {
+            match access.trim_end_matches(']').parse() {
+                Ok(i) => path.push(Step::Index(i)),
+                Err(_) => path.push(Step::Field(access)),
+            }
+        }
+    }
+    path
+}
+
+// an output rebuilt from its signals: a value, an array or the fields of a bus
+enum Output {
+    Value(Option<String>),
+    Array(Vec<Output>),
+    Fields(Vec<(String, Output)>),
+}
+
+impl Output {
+    fn insert(&mut self, path: &[Step], value: Option<String>) {
+        let Some((step, rest)) = path.split_first() else {
+            *self = Output::Value(value);
+            return;
+        };
+        let empty = || match rest.first() {
+            Some(Step::Index(_)) => Output::Array(Vec::new()),
+            Some(Step::Field(_)) => Output::Fields(Vec::new()),
+            None => Output::Value(None),
+        };
+        let next = match (self, step) {
+            (Output::Fields(fields), Step::Field(name)) => {
+                match fields.iter().position(|(field, _)| field == name) {
+                    Some(i) => &mut fields[i].1,
+                    None => {
+                        fields.push((name.to_string(), empty()));
+                        &mut fields.last_mut().unwrap().1
+                    }
+                }
+            }
+            (Output::Array(elements), Step::Index(i)) => {
+                while elements.len() <= *i {
+                    elements.push(empty());
+                }
+                &mut elements[*i]
+            }
+            // the symbols disagree on the shape of the output
+            _ => return,
+        };
+        next.insert(rest, value);
+    }
+
+    fn to_json(&self, builder: &mut Builder<Vec<u8>>) {
+        match self {
+            Output::Value(Some(value)) => builder.add_string(value),
+            Output::Value(None) => builder.add_null(),
+            Output::Array(elements) => {
+                builder.begin_array();
+                for element in elements {
+                    element.to_json(builder);
+                }
+                builder.end_array();
+            }
+            Output::Fields(fields) => {
+                builder.begin_object();
+                for (name, field) in fields {
+                    builder.add_string(name);
+                    field.to_json(builder);
+                }
+                builder.end_object();
             }
         }
+    }
+}
+
+// the lines written by log(), in the order they were written
+pub fn logs_json(logs: &Logs, builder: &mut Builder<Vec<u8>>) {
+    builder.add_string("logs");
+    builder.begin_array();
+    for log in logs {
+        builder.begin_object();
+        builder.add_string("message");
+        builder.add_string(&log.message);
+        builder.add_string("component");
+        match &log.component {
+            Some(component) => builder.add_string(component),
+            None => builder.add_null(),
+        }
         builder.end_object();
     }
     builder.end_array();
+}
+
+pub fn report_json(report: &Report, builder: &mut Builder<Vec<u8>>) {
+    builder.begin_object();
+    builder.add_string("code");
+    builder.add_string(&report.get_code().to_string());
+    builder.add_string("message");
+    builder.add_string(report.get_message());
+    builder.add_string("labels");
+    builder.begin_array();
+    for label in report.get_primary().iter().chain(report.get_secondary()) {
+        builder.begin_object();
+        builder.add_string("style");
+        builder.add_string(&format!("{:?}", label.style));
+        builder.add_string("file_id");
+        builder.add_u64(label.file_id as u64);
+        builder.add_string("range");
+        builder.begin_object();
+        builder.add_string("start");
+        builder.add_u64(label.range.start as u64);
+        builder.add_string("end");
+        builder.add_u64(label.range.end as u64);
+        builder.end_object();
+        builder.add_string("message");
+        builder.add_string(&label.message);
+        builder.end_object();
+    }
+    builder.end_array();
+    builder.add_string("notes");
+    builder.begin_array();
+    for note in report.get_notes() {
+        builder.add_string(note);
+    }
+    builder.end_array();
     builder.end_object();
+}
+
+pub fn symbols_json(
+    constrained: &Vec<&SymElem>,
+    unconstrained: &Vec<&SymElem>,
+    builder: &mut Builder<Vec<u8>>,
+) {
+    builder.add_string("symbols");
+    builder.begin_object();
+
+    builder.add_string("constrained");
+    builder.begin_array();
+    for sym in constrained {
+        builder.begin_object();
+        builder.add_string("original");
+        builder.add_string(&sym.original.to_string());
+        builder.add_string("witness");
+        builder.add_string(&sym.witness.to_string());
+        builder.add_string("node_id");
+        builder.add_string(&sym.node_id.to_string());
+        builder.add_string("symbol");
+        builder.add_string(&sym.symbol);
+        builder.end_object();
+    }
+    builder.end_array();
+
+    builder.add_string("unconstrained");
+    builder.begin_array();
+    for sym in unconstrained {
+        builder.begin_object();
+        builder.add_string("original");
+        builder.add_string(&sym.original.to_string());
+        builder.add_string("witness");
+        builder.add_string(&sym.witness.to_string());
+        builder.add_string("node_id");
+        builder.add_string(&sym.node_id.to_string());
+        builder.add_string("symbol");
+        builder.add_string(&sym.symbol);
+        builder.end_object();
+    }
+    builder.end_array();
+
+    builder.end_object();
+}
+
+pub fn produce_ast_json(programs: &Vec<(String, AST)>) -> String {
+    let mut builder = Builder::<Vec<u8>>::new();
+    builder.begin_array();
+    for (identity, ast) in programs {
+        builder.begin_object();
+        builder.add_string("identity");
+        builder.add_string(identity);
+        meta_json(&ast.meta, &mut builder);
+
+        builder.add_string("compiler_version");
+        match ast.compiler_version {
+            Some((major, minor, patch)) => {
+                builder.add_string(&format!("{}.{}.{}", major, minor, patch));
+            }
+            None => {
+                builder.add_null();
+            }
+        }
+        builder.add_string("custom_gates");
+        builder.add_bool(ast.custom_gates);
+
+        builder.add_string("includes");
+        builder.begin_array();
+        for include in &ast.includes {
+            builder.add_string(&include.path);
+        }
+        builder.end_array();
+
+        builder.add_string("definitions");
+        builder.begin_array();
+        for definition in &ast.definitions {
+            definition_json_builder(definition, &mut builder);
+        }
+        builder.end_array();
+
+        builder.add_string("main_component");
+        match &ast.main_component {
+            Some(main) => main_component_json_builder(main, &mut builder),
+            None => {
+                builder.add_null();
+            }
+        }
+        builder.end_object();
+    }
+    builder.end_array();
 
     let json = builder.finish();
     json.to_string()
 }
 
+pub fn meta_json(meta: &Meta, builder: &mut Builder<Vec<u8>>) {
+    builder.add_string("meta");
+    builder.begin_object();
+    builder.add_string("elem_id");
+    builder.add_u64(meta.elem_id as u64);
+    builder.add_string("start");
+    builder.add_u64(meta.start as u64);
+    builder.add_string("end");
+    builder.add_u64(meta.end as u64);
+    builder.add_string("file_id");
+    match meta.file_id {
+        Some(file_id) => {
+            builder.add_u64(file_id as u64);
+        }
+        None => {
+            builder.add_null();
+        }
+    }
+    builder.end_object();
+}
+
+pub fn definition_json_builder(def: &Definition, builder: &mut Builder<Vec<u8>>) {
+    let (kind, meta, name, args, body) = match def {
+        Definition::Template { meta, name, args, body, .. } => ("Template", meta, name, args, body),
+        Definition::Function { meta, name, args, body, .. } => ("Function", meta, name, args, body),
+        Definition::Bus { meta, name, args, body, .. } => ("Bus", meta, name, args, body),
+    };
+    builder.begin_object();
+    builder.add_string("definition");
+    builder.add_string(kind);
+    meta_json(meta, builder);
+    builder.add_string("name");
+    builder.add_string(name);
+    builder.add_string("args");
+    builder.begin_array();
+    for arg in args {
+        builder.add_string(arg);
+    }
+    builder.end_array();
+    if let Definition::Template { parallel, is_custom_gate, .. } = def {
+        builder.add_string("parallel");
+        builder.add_bool(*parallel);
+        builder.add_string("is_custom_gate");
+        builder.add_bool(*is_custom_gate);
+    }
+    builder.add_string("body");
+    statement_json_builder(body, builder);
+    builder.end_object();
+}
+
+pub fn main_component_json_builder(main: &MainComponent, builder: &mut Builder<Vec<u8>>) {
+    let (public, call) = main;
+    builder.begin_object();
+    builder.add_string("public");
+    builder.begin_array();
+    for signal in public {
+        builder.add_string(signal);
+    }
+    builder.end_array();
+    builder.add_string("call");
+    expression_json_builder(call, builder);
+    builder.end_object();
+}
+
 pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
     match st {
         Statement::IfThenElse { meta, cond, if_case, else_case } => {
             builder.begin_object();
             builder.add_string("statement");
             builder.add_string("IfThenElse");
+            meta_json(meta, builder);
             builder.add_string("condition");
             expression_json_builder(&cond, builder);
             builder.add_string("if_case");
@@ -149,6 +690,7 @@ pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
             builder.begin_object();
             builder.add_string("statement");
             builder.add_string("While");
+            meta_json(meta, builder);
             builder.add_string("condition");
             expression_json_builder(&cond, builder);
             builder.add_string("body");
@@ -159,6 +701,7 @@ pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
             builder.begin_object();
             builder.add_string("statement");
             builder.add_string("Return");
+            meta_json(meta, builder);
             builder.add_string("value");
             expression_json_builder(&value, builder);
             builder.end_object();
@@ -167,6 +710,7 @@ pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
             builder.begin_object();
             builder.add_string("statement");
             builder.add_string("InitializationBlock");
+            meta_json(meta, builder);
             builder.add_string("type");
             variable_type_json(&xtype, builder);
             builder.add_string("initializations");
@@ -183,6 +727,8 @@ pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
             builder.add_string("statement");
             builder.add_string("Declaration");
 
+            meta_json(meta, builder);
+
             builder.add_string("type");
             variable_type_json(&xtype, builder);
 
@@ -206,6 +752,7 @@ pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
             builder.begin_object();
             builder.add_string("statement");
             builder.add_string("Substitution");
+            meta_json(meta, builder);
             builder.add_string("variable");
             builder.add_string(var);
             builder.add_string("access");
@@ -224,6 +771,7 @@ pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
             builder.begin_object();
             builder.add_string("statement");
             builder.add_string("MultSubstitution");
+            meta_json(meta, builder);
             builder.add_string("lhs");
             expression_json_builder(lhe, builder);
             builder.add_string("operator");
@@ -236,6 +784,7 @@ pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
             builder.begin_object();
             builder.add_string("statement");
             builder.add_string("UnderscoreSubstitution");
+            meta_json(meta, builder);
             builder.add_string("operator");
             builder.add_string(&op.to_string());
             builder.add_string("rhs");
@@ -246,6 +795,7 @@ pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
             builder.begin_object();
             builder.add_string("statement");
             builder.add_string("ConstraintEquality");
+            meta_json(meta, builder);
             builder.add_string("lhs");
             expression_json_builder(lhe, builder);
             builder.add_string("rhs");
@@ -256,6 +806,7 @@ pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
             builder.begin_object();
             builder.add_string("statement");
             builder.add_string("LogCall");
+            meta_json(meta, builder);
             builder.add_string("args");
             builder.begin_array();
             for arg in args {
@@ -268,6 +819,7 @@ pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
             builder.begin_object();
             builder.add_string("statement");
             builder.add_string("Block");
+            meta_json(meta, builder);
             builder.add_string("statements");
             builder.begin_array();
             for stmt in stmts {
@@ -280,6 +832,7 @@ pub fn statement_json_builder(st: &Statement, builder: &mut Builder<Vec<u8>>) {
             builder.begin_object();
             builder.add_string("statement");
             builder.add_string("Assert");
+            meta_json(meta, builder);
             builder.add_string("arg");
             expression_json_builder(arg, builder);
             builder.end_object();
@@ -318,6 +871,7 @@ pub fn expression_json_builder(exp: &Expression, builder: &mut Builder<Vec<u8>>)
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("UniformArray");
+            meta_json(meta, builder);
             builder.add_string("value");
             expression_json_builder(value, builder);
             builder.add_string("dimension");
@@ -328,6 +882,7 @@ pub fn expression_json_builder(exp: &Expression, builder: &mut Builder<Vec<u8>>)
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("Tuple");
+            meta_json(meta, builder);
             builder.add_string("values");
             builder.begin_array();
             for v in values {
@@ -340,6 +895,7 @@ pub fn expression_json_builder(exp: &Expression, builder: &mut Builder<Vec<u8>>)
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("ArrayInLine");
+            meta_json(meta, builder);
             builder.add_string("values");
             builder.begin_array();
             for v in values {
@@ -348,10 +904,11 @@ pub fn expression_json_builder(exp: &Expression, builder: &mut Builder<Vec<u8>>)
             builder.end_array();
             builder.end_object();
         }
-        Expression::InfixOp { lhe, infix_op, rhe, .. } => {
+        Expression::InfixOp { meta, lhe, infix_op, rhe } => {
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("InfixOp");
+            meta_json(meta, builder);
             builder.add_string("lhs");
             expression_json_builder(lhe, builder);
             builder.add_string("operator");
@@ -361,10 +918,11 @@ pub fn expression_json_builder(exp: &Expression, builder: &mut Builder<Vec<u8>>)
             expression_json_builder(rhe, builder);
             builder.end_object();
         }
-        Expression::PrefixOp { prefix_op, rhe, .. } => {
+        Expression::PrefixOp { meta, prefix_op, rhe } => {
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("PrefixOp");
+            meta_json(meta, builder);
             builder.add_string("operator");
 
             let operator = match prefix_op {
@@ -377,10 +935,11 @@ pub fn expression_json_builder(exp: &Expression, builder: &mut Builder<Vec<u8>>)
             expression_json_builder(rhe, builder);
             builder.end_object();
         }
-        Expression::InlineSwitchOp { cond, if_true, if_false, .. } => {
+        Expression::InlineSwitchOp { meta, cond, if_true, if_false } => {
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("InlineSwitchOp");
+            meta_json(meta, builder);
             builder.add_string("condition");
             expression_json_builder(cond, builder);
             builder.add_string("if_true");
@@ -389,18 +948,20 @@ pub fn expression_json_builder(exp: &Expression, builder: &mut Builder<Vec<u8>>)
             expression_json_builder(if_false, builder);
             builder.end_object();
         }
-        Expression::ParallelOp { rhe, .. } => {
+        Expression::ParallelOp { meta, rhe } => {
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("ParallelOp");
+            meta_json(meta, builder);
             builder.add_string("rhs");
             expression_json_builder(rhe, builder);
             builder.end_object();
         }
-        Expression::Variable { name, access, .. } => {
+        Expression::Variable { meta, name, access } => {
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("Variable");
+            meta_json(meta, builder);
             builder.add_string("name");
             builder.add_string(name);
             builder.add_string("access");
@@ -418,18 +979,20 @@ pub fn expression_json_builder(exp: &Expression, builder: &mut Builder<Vec<u8>>)
             builder.end_array();
             builder.end_object();
         }
-        Expression::Number(_, n) => {
+        Expression::Number(meta, n) => {
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("Number");
+            meta_json(meta, builder);
             builder.add_string("value");
             builder.add_string(&n.to_string());
             builder.end_object();
         }
-        Expression::Call { id, args, .. } => {
+        Expression::Call { meta, id, args } => {
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("Call");
+            meta_json(meta, builder);
             builder.add_string("id");
             builder.add_string(id);
             builder.add_string("args");
@@ -440,10 +1003,11 @@ pub fn expression_json_builder(exp: &Expression, builder: &mut Builder<Vec<u8>>)
             builder.end_array();
             builder.end_object();
         }
-        Expression::BusCall { id, args, .. } => {
+        Expression::BusCall { meta, id, args } => {
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("BusCall");
+            meta_json(meta, builder);
             builder.add_string("id");
             builder.add_string(id);
             builder.add_string("args");
@@ -454,12 +1018,15 @@ pub fn expression_json_builder(exp: &Expression, builder: &mut Builder<Vec<u8>>)
             builder.end_array();
             builder.end_object();
         }
-        Expression::AnonymousComp { id, params, signals, names, .. } => {
+        Expression::AnonymousComp { meta, id, is_parallel, params, signals, names } => {
             builder.begin_object();
             builder.add_string("expression");
             builder.add_string("AnonymousComp");
+            meta_json(meta, builder);
             builder.add_string("id");
             builder.add_string(id);
+            builder.add_string("is_parallel");
+            builder.add_bool(*is_parallel);
             builder.add_string("params");
             builder.begin_array();
             for p in params {
@@ -580,3 +1147,109 @@ pub fn variable_type_json(v: &VariableType, builder: &mut Builder<Vec<u8>>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{create_default_circuit_pkg, CircuitLibrary};
+    use crate::fixtures::{compile_main, main_pkg, program};
+
+    #[test]
+    fn export_ast_json() {
+        let square = indoc::indoc! {"
+            template custom Square(){
+                signal input a;
+                signal output out;
+                out <== a * a;
+            }
+        "};
+        let progs = vec![
+            program("main", "component main {public [a]} = Square();"),
+            program("square", square),
+        ];
+        let ast = match CircuitLibrary::default().export_ast(&create_default_circuit_pkg(&progs)) {
+            Ok(ast) => ast,
+            Err(_) => panic!("ast export failed"),
+        };
+        let ast: serde_json::Value = serde_json::from_str(&ast).unwrap();
+        assert_eq!(ast[0]["identity"], "main");
+        assert_eq!(ast[0]["main_component"]["public"][0], "a");
+        assert_eq!(ast[0]["main_component"]["call"]["expression"], "Call");
+        assert_eq!(ast[0]["main_component"]["call"]["id"], "Square");
+
+        let definition = &ast[1]["definitions"][0];
+        assert_eq!(definition["definition"], "Template");
+        assert_eq!(definition["name"], "Square");
+        assert_eq!(definition["is_custom_gate"], true);
+        assert_eq!(definition["meta"]["start"], 0);
+        assert_eq!(definition["meta"]["file_id"], 1);
+        let substitution = &definition["body"]["statements"][2];
+        assert_eq!(substitution["statement"], "Substitution");
+        assert_eq!(substitution["rhs"]["operator"], "*");
+        let start = substitution["meta"]["start"].as_u64().unwrap() as usize;
+        assert_eq!(&square[start..start + 3], "out");
+
+        assert!(CircuitLibrary::default().export_ast(&main_pkg("template A( {}")).is_err());
+    }
+
+    #[test]
+    fn public_signals_and_outputs() {
+        let src = indoc::indoc! {"
+            bus Point(){
+                signal x;
+                signal y[2];
+            }
+            template Shapes(){
+                signal input a;
+                signal input b;
+                signal input c;
+                signal output grid[2][2];
+                output Point() p;
+                signal output sum;
+                for (var i = 0; i < 2; i++) {
+                    for (var j = 0; j < 2; j++) {
+                        grid[i][j] <== a * (2 * i + j);
+                    }
+                }
+                p.x <== b;
+                p.y[0] <== b * c;
+                p.y[1] <== c;
+                sum <== a + b + c;
+            }
+            component main {public [b]} = Shapes();
+        "};
+        let library = compile_main(src);
+        let (witness, records, logs) = match library.execute(r#"{"a": 2, "b": 3, "c": 4}"#) {
+            Ok(evaluation) => evaluation,
+            Err(_) => panic!("execution failed"),
+        };
+        let (x, y) = library.get_signals();
+        let json = produce_constraint_evaluation_json(
+            library.field(),
+            &records,
+            &x,
+            &y,
+            &library.get_public_signals(),
+            &witness,
+            &logs,
+        );
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        // outputs then the public input
+        assert_eq!(
+            json["public"],
+            serde_json::json!(["0", "2", "4", "6", "3", "12", "4", "9", "3"])
+        );
+        assert_eq!(
+            json["outputs"],
+            serde_json::json!({
+                "grid": [["0", "2"], ["4", "6"]],
+                "p": {"x": "3", "y": ["12", "4"]},
+                "sum": "9"
+            })
+        );
+        // shared as a bool, as in the batch evaluation
+        let constraints = json["constraints"].as_array().unwrap();
+        assert!(!constraints.is_empty());
+        assert!(constraints.iter().all(|c| c["satisfied"] == true));
+    }
+}
@@ -5,3 +5,11 @@ pub mod json_export;
 pub mod circuit;
 pub mod circuit_wasm;
 pub mod constraint_system;
+pub mod snapshot;
+pub mod binary_export;
+pub mod underconstrained;
+pub mod fuzz;
+pub mod statistics;
+
+#[cfg(test)]
+mod fixtures;
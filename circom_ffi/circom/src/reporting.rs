@@ -1,5 +1,6 @@
 use codespan_reporting::diagnostic::{Diagnostic, Severity};
 use program_structure::{
+    constants::UsefulConstants,
     error_code::ReportCode,
     error_definition::Report,
     file_definition::{FileID, FileLocation},
@@ -94,6 +95,159 @@ pub fn produce_report(error_code: ReportCode, location: FileLocation, file_id: F
     report
 }
 
+pub fn produce_report_with_message(error_code: ReportCode, msg: String) -> Report {
+    match error_code {
+        ReportCode::UnsupportedPrimeField => {
+            let mut r = Report::error(
+                format!("The prime field {} is not supported", msg),
+                ReportCode::UnsupportedPrimeField,
+            );
+            r.add_note(format!(
+                "Supported prime fields: {}",
+                UsefulConstants::supported_primes().join(", ")
+            ));
+            r.add_note(
+                "A custom prime between 2^31 and 2^256 can be given in decimal or 0x prefixed hex"
+                    .to_string(),
+            );
+            r
+        }
+        ReportCode::NonPrimeField => Report::error(
+            format!("The custom field modulus {} is not a prime", msg),
+            ReportCode::NonPrimeField,
+        ),
+        ReportCode::UnsupportedPrimeSize => {
+            let mut r = Report::error(
+                format!("The size of the custom field modulus {} is not supported", msg),
+                ReportCode::UnsupportedPrimeSize,
+            );
+            r.add_note(
+                "The witness generator supports primes of 32 to 64 bits or of 193 to 256 bits"
+                    .to_string(),
+            );
+            r
+        }
+        ReportCode::MalformedPackageJson => {
+            let mut r = Report::error(
+                "The circuit package could not be decoded".to_string(),
+                ReportCode::MalformedPackageJson,
+            );
+            r.add_note(msg);
+            r
+        }
+        ReportCode::MalformedInputJson => {
+            let mut r = Report::error(
+                "The circuit inputs could not be decoded".to_string(),
+                ReportCode::MalformedInputJson,
+            );
+            r.add_note(msg);
+            r
+        }
+        ReportCode::InternalPanic => {
+            let mut r = Report::error(
+                "Veritas panicked while handling the call".to_string(),
+                ReportCode::InternalPanic,
+            );
+            r.add_note(msg);
+            r
+        }
+        ReportCode::ExportFailure => Report::error(
+            format!("Unable to export the {}", msg),
+            ReportCode::ExportFailure,
+        ),
+        ReportCode::MalformedLibrary => {
+            let mut r = Report::error(
+                "The compiled library could not be restored".to_string(),
+                ReportCode::MalformedLibrary,
+            );
+            r.add_note(msg);
+            r
+        }
+        ReportCode::WitnessSignalNotFound => Report::error(
+            format!("{} is not an input signal of the main component", msg),
+            ReportCode::WitnessSignalNotFound,
+        ),
+        ReportCode::WitnessTooManyInputs => Report::error(
+            format!("Unable to set {}, every input signal has already been set", msg),
+            ReportCode::WitnessTooManyInputs,
+        ),
+        ReportCode::WitnessSignalAlreadySet => Report::error(
+            format!("The input signal {} is set more than once", msg),
+            ReportCode::WitnessSignalAlreadySet,
+        ),
+        ReportCode::WitnessAssertFailed => {
+            Report::error(format!("Assert failed in {}", msg), ReportCode::WitnessAssertFailed)
+        }
+        ReportCode::WitnessOutOfMemory => Report::error(
+            format!("The witness calculation ran out of memory in {}", msg),
+            ReportCode::WitnessOutOfMemory,
+        ),
+        ReportCode::WitnessInputOutOfBounds => Report::error(
+            format!("{} is out of the bounds of its input signal", msg),
+            ReportCode::WitnessInputOutOfBounds,
+        ),
+        ReportCode::WitnessMissingInputs => Report::error(
+            format!("Not all the input signals have been set, {}", msg),
+            ReportCode::WitnessMissingInputs,
+        ),
+        ReportCode::WitnessDivisionByZero => Report::error(
+            format!("Integer division by zero in {}", msg),
+            ReportCode::WitnessDivisionByZero,
+        ),
+        ReportCode::MissingInputSignal => Report::error(
+            format!("The input signal {} is missing", msg),
+            ReportCode::MissingInputSignal,
+        ),
+        ReportCode::UnknownInputSignal => {
+            Report::error(format!("Unknown input signal {}", msg), ReportCode::UnknownInputSignal)
+        }
+        ReportCode::InputSignalSizeMismatch => {
+            Report::error(format!("The input signal {}", msg), ReportCode::InputSignalSizeMismatch)
+        }
+        ReportCode::UnknownComponent => Report::error(
+            format!("{} is not a component of the circuit", msg),
+            ReportCode::UnknownComponent,
+        ),
+        ReportCode::UnderconstrainedSignal => Report::warning(
+            format!("The witness of {} is not the only one satisfying the constraints", msg),
+            ReportCode::UnderconstrainedSignal,
+        ),
+        ReportCode::UnknownWitnessSignal => Report::error(
+            format!("{} is not a signal of the witness", msg),
+            ReportCode::UnknownWitnessSignal,
+        ),
+        ReportCode::UnsupportedOptimization => Report::error(
+            format!("The optimization {} is not supported", msg),
+            ReportCode::UnsupportedOptimization,
+        ),
+        ReportCode::UnsupportedGroupSize => {
+            let mut r = Report::error(
+                format!("Mutating the signals in groups of {} is not supported", msg),
+                ReportCode::UnsupportedGroupSize,
+            );
+            r.add_note("The signals are mutated alone (1) or alone & in pairs (2)".to_string());
+            r
+        }
+        ReportCode::MalformedWitness => {
+            let mut r = Report::error(
+                "The given witness does not fit the circuit".to_string(),
+                ReportCode::MalformedWitness,
+            );
+            r.add_note(msg);
+            r
+        }
+        ReportCode::UnsupportedTargetVersion => {
+            let mut r = Report::error(
+                format!("The target version {} is not a valid compiler version", msg),
+                ReportCode::UnsupportedTargetVersion,
+            );
+            r.add_note("Target version should be in the form major.minor.patch".to_string());
+            r
+        }
+        _ => unreachable!(),
+    }
+}
+
 pub fn produce_report_with_main_components(
     main_components: &crate::circuit::MainComponents,
 ) -> Report {
@@ -0,0 +1,379 @@
+// Binary snapshot of a compiled circuit library, restored without recompiling.
+//
+// Everything is little endian:
+//   "vlib" | format version (u32) | fingerprint of the build (string)
+//   | sha256 of the sections (32 bytes) | sections written by the library
+// byte & string fields are prefixed with their length (u64),
+// bigints are a sign byte followed by the bytes of their magnitude.
+//
+// The sections hold the native code of the witness module, so a snapshot is
+// only read when it was saved by the same build & its sections are intact.
+pub extern crate num_bigint_dig as num_bigint;
+
+use crate::constraint_system::{ComponentInstance, ConstraintSystem, C};
+use constraint_list::{ConstraintOrigin, ConstraintSource};
+use constraint_writers::sym_writer::SymElem;
+use num_bigint::{BigInt, Sign};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+
+const MAGIC: &[u8] = b"vlib";
+const FORMAT_VERSION: u32 = 9;
+
+// identifies the snapshots this build can read, e.g. to key a cache of snapshots
+pub fn snapshot_fingerprint() -> String {
+    format!("vlib {} {}", FORMAT_VERSION, crate::witness::engine_fingerprint())
+}
+
+pub struct SnapshotWriter {
+    bytes: Vec<u8>,
+}
+
+impl Default for SnapshotWriter {
+    fn default() -> Self {
+        SnapshotWriter::new()
+    }
+}
+
+impl SnapshotWriter {
+    pub fn new() -> SnapshotWriter {
+        SnapshotWriter { bytes: Vec::new() }
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    pub fn write_i64(&mut self, value: i64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.bytes.push(value as u8);
+    }
+
+    pub fn write_bytes(&mut self, value: &[u8]) {
+        self.write_usize(value.len());
+        self.bytes.extend_from_slice(value);
+    }
+
+    pub fn write_str(&mut self, value: &str) {
+        self.write_bytes(value.as_bytes());
+    }
+
+    pub fn write_bigint(&mut self, value: &BigInt) {
+        let (sign, magnitude) = value.to_bytes_le();
+        self.bytes.push(match sign {
+            Sign::Minus => 0,
+            Sign::NoSign => 1,
+            Sign::Plus => 2,
+        });
+        self.write_bytes(&magnitude);
+    }
+
+    pub fn write_constraint_system(&mut self, cs: &ConstraintSystem) {
+        self.write_bigint(&cs.field);
+        for count in [
+            cs.no_labels,
+            cs.no_wires,
+            cs.no_private_inputs,
+            cs.no_private_inputs_witness,
+            cs.no_public_inputs,
+            cs.no_public_outputs,
+            cs.no_linear,
+            cs.no_non_linear,
+            cs.num_constraints,
+        ] {
+            self.write_usize(count);
+        }
+
+        self.write_usize(cs.symbols.len());
+        for s in cs.symbols.iter() {
+            self.write_i64(s.original);
+            self.write_i64(s.witness);
+            self.write_i64(s.node_id);
+            self.write_str(&s.symbol);
+        }
+
+        self.write_usize(cs.components.len());
+        for component in cs.components.iter() {
+            self.write_usize(component.node_id);
+            self.write_str(&component.path);
+            self.write_usize(component.signal_start);
+        }
+
+        self.write_usize(cs.constraints.len());
+        for c in cs.constraints.iter() {
+            for lc in [c.a(), c.b(), c.c()] {
+                self.write_usize(lc.len());
+                for (signal, coefficient) in lc.iter() {
+                    self.write_usize(*signal);
+                    self.write_bigint(coefficient);
+                }
+            }
+            self.write_usize(c.origins().len());
+            for origin in c.origins() {
+                self.write_usize(*origin);
+            }
+        }
+
+        self.write_usize(cs.origins.len());
+        for origin in cs.origins.iter() {
+            self.write_usize(origin.source.file_id);
+            self.write_usize(origin.source.location.start);
+            self.write_usize(origin.source.location.end);
+            self.write_str(&origin.template);
+            self.write_str(&origin.path);
+        }
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        let fingerprint = crate::witness::engine_fingerprint();
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(fingerprint.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(fingerprint.as_bytes());
+        bytes.extend_from_slice(&Sha256::digest(&self.bytes));
+        bytes.extend_from_slice(&self.bytes);
+        bytes
+    }
+}
+
+// errors are plain messages, reported as MalformedLibrary by the caller
+pub struct SnapshotReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SnapshotReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<SnapshotReader<'a>, String> {
+        let mut reader = SnapshotReader { bytes, pos: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err("not a library snapshot".to_string());
+        }
+        let version = u32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+        if version != FORMAT_VERSION {
+            return Err(format!(
+                "snapshot format version {} is not supported, expected {}",
+                version, FORMAT_VERSION
+            ));
+        }
+        let fingerprint = reader.read_string()?;
+        let expected = crate::witness::engine_fingerprint();
+        if fingerprint != expected {
+            return Err(format!("snapshot was saved by {}, expected {}", fingerprint, expected));
+        }
+        let digest = reader.take(32)?;
+        let sections = &bytes[reader.pos..];
+        if Sha256::digest(sections).as_slice() != digest {
+            return Err("snapshot is corrupted, its digest does not match".to_string());
+        }
+        Ok(SnapshotReader { bytes: sections, pos: 0 })
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.bytes.len());
+        match end {
+            Some(end) => {
+                let slice = &self.bytes[self.pos..end];
+                self.pos = end;
+                Ok(slice)
+            }
+            None => Err(format!("snapshot is truncated at byte {}", self.pos)),
+        }
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn read_usize(&mut self) -> Result<usize, String> {
+        let value = self.read_u64()?;
+        usize::try_from(value).map_err(|_| format!("{} does not fit in a usize", value))
+    }
+
+    pub fn read_i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, String> {
+        Ok(self.take(1)?[0] != 0)
+    }
+
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], String> {
+        let len = self.read_usize()?;
+        self.take(len)
+    }
+
+    pub fn read_string(&mut self) -> Result<String, String> {
+        String::from_utf8(self.read_bytes()?.to_vec()).map_err(|e| e.to_string())
+    }
+
+    pub fn read_bigint(&mut self) -> Result<BigInt, String> {
+        let sign = match self.take(1)?[0] {
+            0 => Sign::Minus,
+            1 => Sign::NoSign,
+            2 => Sign::Plus,
+            s => return Err(format!("{} is not a valid sign", s)),
+        };
+        Ok(BigInt::from_bytes_le(sign, self.read_bytes()?))
+    }
+
+    pub fn read_constraint_system(&mut self) -> Result<ConstraintSystem, String> {
+        let mut cs = ConstraintSystem { field: self.read_bigint()?, ..Default::default() };
+        for count in [
+            &mut cs.no_labels,
+            &mut cs.no_wires,
+            &mut cs.no_private_inputs,
+            &mut cs.no_private_inputs_witness,
+            &mut cs.no_public_inputs,
+            &mut cs.no_public_outputs,
+            &mut cs.no_linear,
+            &mut cs.no_non_linear,
+            &mut cs.num_constraints,
+        ] {
+            *count = self.read_usize()?;
+        }
+
+        for _ in 0..self.read_usize()? {
+            let symbol = SymElem {
+                original: self.read_i64()?,
+                witness: self.read_i64()?,
+                node_id: self.read_i64()?,
+                symbol: self.read_string()?,
+            };
+            if symbol.witness < -1 || symbol.witness >= cs.no_wires as i64 {
+                return Err(format!("{} is not mapped to a wire of the circuit", symbol.symbol));
+            }
+            cs.symbols.push(symbol);
+        }
+
+        for _ in 0..self.read_usize()? {
+            cs.components.push(ComponentInstance {
+                node_id: self.read_usize()?,
+                path: self.read_string()?,
+                signal_start: self.read_usize()?,
+            });
+        }
+
+        for _ in 0..self.read_usize()? {
+            let mut lcs = Vec::with_capacity(3);
+            for _ in 0..3 {
+                let mut lc = HashMap::new();
+                for _ in 0..self.read_usize()? {
+                    let signal = self.read_usize()?;
+                    if signal >= cs.no_wires {
+                        return Err(format!("signal {} is not a wire of the circuit", signal));
+                    }
+                    lc.insert(signal, self.read_bigint()?);
+                }
+                lcs.push(lc);
+            }
+            let c = lcs.pop().unwrap();
+            let b = lcs.pop().unwrap();
+            let a = lcs.pop().unwrap();
+            let mut constraint = C::new(a, b, c);
+            let mut origins = Vec::new();
+            for _ in 0..self.read_usize()? {
+                origins.push(self.read_usize()?);
+            }
+            constraint.set_origins(origins);
+            cs.constraints.push(constraint);
+        }
+
+        for _ in 0..self.read_usize()? {
+            let file_id = self.read_usize()?;
+            let start = self.read_usize()?;
+            let end = self.read_usize()?;
+            cs.origins.push(ConstraintOrigin {
+                source: ConstraintSource { file_id, location: start..end },
+                template: self.read_string()?,
+                path: self.read_string()?,
+            });
+        }
+        let unknown =
+            cs.constraints.iter().flat_map(|c| c.origins()).find(|o| **o >= cs.origins.len());
+        if let Some(origin) = unknown {
+            return Err(format!("constraint origin {} is not in the snapshot", origin));
+        }
+        Ok(cs)
+    }
+
+    pub fn finish(self) -> Result<(), String> {
+        if self.pos != self.bytes.len() {
+            return Err(format!("{} trailing bytes", self.bytes.len() - self.pos));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::CircuitLibrary;
+    use crate::constraint_system::EvalOptions;
+    use crate::fixtures::compile_main;
+
+    #[test]
+    fn save_and_load_library() {
+        let src = indoc::indoc! {"
+            template IsSquare(){
+                signal input a;
+                signal input b;
+                signal sqrd <== a * a;
+                sqrd === b;
+            }
+            component main = IsSquare();
+        "};
+        assert!(CircuitLibrary::default().save().is_err());
+        let library = compile_main(src);
+        let snapshot = match library.save() {
+            Ok(snapshot) => snapshot,
+            Err(_) => panic!("save failed"),
+        };
+
+        let restored = match CircuitLibrary::load(&snapshot) {
+            Ok(restored) => restored,
+            Err(r) => panic!("{}", r[0].get_message()),
+        };
+        assert_eq!(restored.programs(), library.programs());
+        assert_eq!(restored.get_circuit_design(0), library.get_circuit_design(0));
+        assert_eq!(restored.export_sym().ok(), library.export_sym().ok());
+        assert_eq!(restored.export_r1cs().ok(), library.export_r1cs().ok());
+        assert_eq!(restored.export_wasm().ok(), library.export_wasm().ok());
+        let carry_on = EvalOptions { skip_constraint_asserts: true, ..Default::default() };
+        match restored.execute_with(r#"{"a": 3, "b": 10}"#, &carry_on) {
+            Ok((witness, records, _)) => {
+                assert_eq!(witness[1], BigInt::from(3));
+                assert!(records[0].report.is_some());
+            }
+            Err(_) => panic!("execution failed"),
+        }
+
+        // truncated snapshots & other bytes are reported
+        for bytes in [&snapshot[..snapshot.len() - 1], b"\0asm"] {
+            match CircuitLibrary::load(bytes) {
+                Ok(_) => panic!("loaded a malformed snapshot"),
+                Err(v) => assert_eq!(v[0].get_code().to_string(), "VE08"),
+            }
+        }
+
+        // so are corrupted sections & snapshots of another build,
+        // before the native code they hold is deserialized
+        let flipped = |at: usize| {
+            let mut bytes = snapshot.clone();
+            bytes[at] ^= 1;
+            match CircuitLibrary::load(&bytes) {
+                Ok(_) => panic!("loaded a malformed snapshot"),
+                Err(v) => v[0].get_notes()[0].clone(),
+            }
+        };
+        assert!(flipped(snapshot.len() - 100).contains("digest"));
+        assert!(flipped(16).contains("saved by"));
+    }
+}
@@ -0,0 +1,157 @@
+// Statistics of the template instances of a compiled main, to track the
+// size of a circuit template by template.
+//
+// The generated constraints are the ones of the constraint generation, the
+// simplified ones are the constraints left by the simplification, counted
+// for the instance of their first origin. The cumulative counts add up the
+// ones of an instance & of every instance below it. Only the statistics of
+// the nodes are kept by a compiled library, the instances are expanded on
+// request.
+use dag::{DAGStatistics, InstanceStatistics};
+use std::collections::HashMap;
+
+use crate::constraint_system::{ConstraintSystem, C};
+
+#[derive(Default, Clone)]
+pub struct Counts {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub intermediates: usize,
+    pub generated_linear: usize,
+    pub generated_non_linear: usize,
+    pub simplified_linear: usize,
+    pub simplified_non_linear: usize,
+}
+
+impl Counts {
+    fn add(&mut self, other: &Counts) {
+        self.inputs += other.inputs;
+        self.outputs += other.outputs;
+        self.intermediates += other.intermediates;
+        self.generated_linear += other.generated_linear;
+        self.generated_non_linear += other.generated_non_linear;
+        self.simplified_linear += other.simplified_linear;
+        self.simplified_non_linear += other.simplified_non_linear;
+    }
+}
+
+pub struct TemplateStatistics {
+    pub instance: InstanceStatistics,
+    pub own: Counts,
+    pub cumulative: Counts,
+}
+
+/// Statistics of the instances of the dag, with the constraints of the constraint system they kept.
+pub fn template_statistics(dag: &DAGStatistics, cs: &ConstraintSystem) -> Vec<TemplateStatistics> {
+    let mut statistics: Vec<TemplateStatistics> = dag
+        .instances()
+        .into_iter()
+        .map(|instance| {
+            let own = Counts {
+                inputs: instance.inputs,
+                outputs: instance.outputs,
+                intermediates: instance.intermediates,
+                generated_linear: instance.linear,
+                generated_non_linear: instance.non_linear,
+                ..Counts::default()
+            };
+            TemplateStatistics { instance, own, cumulative: Counts::default() }
+        })
+        .collect();
+    let by_path: HashMap<String, usize> =
+        statistics.iter().enumerate().map(|(i, s)| (s.instance.path.clone(), i)).collect();
+
+    for c in cs.constraints.iter() {
+        let path = c.origins().first().map(|origin| cs.origins[*origin].path.as_str());
+        let Some(i) = path.and_then(|path| by_path.get(path)) else {
+            continue;
+        };
+        if C::is_linear(c) {
+            statistics[*i].own.simplified_linear += 1;
+        } else {
+            statistics[*i].own.simplified_non_linear += 1;
+        }
+    }
+
+    // every instance adds its own counts to the ones of its ancestors
+    for i in 0..statistics.len() {
+        let own = statistics[i].own.clone();
+        let path = statistics[i].instance.path.clone();
+        let mut path = path.as_str();
+        loop {
+            if let Some(ancestor) = by_path.get(path) {
+                statistics[*ancestor].cumulative.add(&own);
+            }
+            match path.rfind('.') {
+                Some(end) => path = &path[..end],
+                None => break,
+            }
+        }
+    }
+    statistics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::CircuitLibrary;
+    use crate::fixtures::compile_main;
+    use num_bigint_dig::BigInt;
+    use std::collections::HashSet;
+
+    #[test]
+    fn template_statistics() {
+        let src = indoc::indoc! {"
+            template Scaled(n){
+                signal input x;
+                signal output y;
+                signal t <== x * x;
+                y <== t * n;
+            }
+            template Sum(){
+                signal input a;
+                signal input b;
+                signal output out;
+                component s[2];
+                s[0] = Scaled(2);
+                s[1] = Scaled(3);
+                s[0].x <== a;
+                s[1].x <== b;
+                out <== s[0].y + s[1].y;
+            }
+            component main = Sum();
+        "};
+        assert!(CircuitLibrary::default().template_statistics().is_err());
+        let library = compile_main(src);
+        let Ok(statistics) = library.template_statistics() else { panic!("no statistics") };
+        let paths: Vec<&str> = statistics.iter().map(|s| s.instance.path.as_str()).collect();
+        assert_eq!(paths, vec!["main", "main.s[0]", "main.s[1]"]);
+        assert_eq!(statistics[2].instance.parameters, vec![BigInt::from(3)]);
+
+        // the equalities of the inputs are simplified away, the rest is kept at O1
+        let counts = |c: &Counts| {
+            (
+                (c.inputs, c.outputs, c.intermediates),
+                (c.generated_linear, c.generated_non_linear),
+                (c.simplified_linear, c.simplified_non_linear),
+            )
+        };
+        assert_eq!(counts(&statistics[0].own), ((2, 1, 0), (3, 0), (1, 0)));
+        assert_eq!(counts(&statistics[1].own), ((1, 1, 1), (1, 1), (1, 1)));
+        assert_eq!(counts(&statistics[0].cumulative), ((4, 3, 2), (5, 2), (3, 2)));
+        assert_eq!(counts(&statistics[1].cumulative), counts(&statistics[1].own));
+        let json = crate::json_export::produce_template_statistics_json(&statistics);
+
+        // the instances are expanded from the nodes, the two Scaled ones are distinct
+        assert_eq!(statistics.iter().map(|s| s.instance.node).collect::<HashSet<_>>().len(), 3);
+
+        // the snapshots leave the statistics out
+        let Ok(loaded) = CircuitLibrary::load(&library.save().ok().unwrap()) else {
+            panic!("load failed")
+        };
+        assert!(loaded.template_statistics().is_err());
+        let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(json[1]["parameters"][0], "2");
+        assert_eq!(json[0]["cumulative"]["generated"]["non_linear"], 2);
+    }
+}
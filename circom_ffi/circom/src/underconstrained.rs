@@ -0,0 +1,276 @@
+// Detection of underconstrained signals by witness mutation.
+//
+// Starting from the witness of valid inputs, the wires that are not inputs
+// (the outputs & the intermediate signals) are given other values, alone &
+// in pairs of wires sharing a constraint. A mutated witness that still
+// satisfies every constraint is a concrete counterexample: the inputs do not
+// determine the mutated signals.
+//
+// Only a fixed set of 6 alternative values is tried for each wire (value ± 1,
+// 0, 1, -value & 2 * value), so the search is not exhaustive: finding no
+// counterexample does not prove the constraints determine every signal.
+pub extern crate num_bigint_dig as num_bigint;
+
+use num_bigint::BigInt;
+use program_structure::error_code::ReportCode;
+use program_structure::error_definition::{Report, ReportCollection};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+use crate::constraint_system::ConstraintSystem;
+use crate::reporting::produce_report_with_message;
+
+/// Options of an underconstrained signal analysis.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct MutationOptions {
+    // mutate the signals alone (1) or alone & in pairs (2)
+    pub group_size: usize,
+    // stop once this many counterexamples are found
+    pub max_counterexamples: usize,
+}
+
+impl Default for MutationOptions {
+    fn default() -> Self {
+        MutationOptions { group_size: 2, max_counterexamples: 16 }
+    }
+}
+
+impl MutationOptions {
+    // the signals are mutated alone or in pairs, larger groups are not searched
+    pub fn check(&self) -> Result<(), ReportCollection> {
+        match self.group_size {
+            1 | 2 => Ok(()),
+            size => Err(vec![produce_report_with_message(
+                ReportCode::UnsupportedGroupSize,
+                size.to_string(),
+            )]),
+        }
+    }
+}
+
+/// A mutated signal, with its value in the witness & the alternative one.
+pub struct MutatedSignal {
+    pub symbol: String,
+    pub witness: usize,
+    pub value: BigInt,
+    pub alternative: BigInt,
+}
+
+/// Another witness of the same inputs that satisfies every constraint.
+pub struct Counterexample {
+    pub signals: Vec<MutatedSignal>,
+}
+
+impl Counterexample {
+    pub fn report(&self) -> Report {
+        let names: Vec<&str> = self.signals.iter().map(|s| s.symbol.as_str()).collect();
+        let mut report =
+            produce_report_with_message(ReportCode::UnderconstrainedSignal, names.join(", "));
+        for s in self.signals.iter() {
+            report.add_note(format!(
+                "{} = {} satisfies the constraints as well as {}",
+                s.symbol, s.alternative, s.value
+            ));
+        }
+        report
+    }
+}
+
+impl ConstraintSystem {
+    // the wires the inputs do not fix: the outputs & the intermediate signals
+    fn mutable_wires(&self) -> Vec<usize> {
+        let inputs = self.no_public_outputs + 1
+            ..self.no_public_outputs + 1 + self.no_public_inputs + self.no_private_inputs_witness;
+        (1..self.no_wires).filter(|w| !inputs.contains(w)).collect()
+    }
+
+    // the values tried in place of value, the only ones the search covers
+    fn alternatives(&self, value: &BigInt) -> Vec<BigInt> {
+        let reduce = |v: BigInt| ((v % &self.field) + &self.field) % &self.field;
+        let mut alternatives: Vec<BigInt> = Vec::new();
+        for alternative in [
+            reduce(value + 1),
+            reduce(value - 1),
+            BigInt::from(0),
+            BigInt::from(1),
+            reduce(-value),
+            reduce(value * 2),
+        ] {
+            if &alternative != value && !alternatives.contains(&alternative) {
+                alternatives.push(alternative);
+            }
+        }
+        alternatives
+    }
+
+    // look for counterexamples around a witness satisfying every constraint,
+    // a wire found underconstrained alone is not mutated in pairs anymore
+    pub fn find_underconstrained(
+        &self,
+        witness: &[BigInt],
+        options: &MutationOptions,
+    ) -> Vec<Counterexample> {
+        let mut occurrences: Vec<Vec<usize>> = vec![Vec::new(); self.no_wires];
+        for (id, c) in self.constraints.iter().enumerate() {
+            let wires: HashSet<usize> =
+                c.a().keys().chain(c.b().keys()).chain(c.c().keys()).cloned().collect();
+            for w in wires {
+                occurrences[w].push(id);
+            }
+        }
+        let (signals, _) = self.signals();
+        let mutable = self.mutable_wires();
+        let mut assignments = witness.to_vec();
+        let mut counterexamples = Vec::new();
+        let mut found = HashSet::new();
+
+        // whether the witness with the mutations satisfies the constraints on their wires
+        let mut satisfies = |mutations: &[(usize, &BigInt)]| {
+            for (w, value) in mutations {
+                assignments[*w] = (*value).clone();
+            }
+            let satisfied = mutations
+                .iter()
+                .flat_map(|(w, _)| occurrences[*w].iter())
+                .all(|id| self.is_satisfied(&assignments, &self.constraints[*id]));
+            for (w, _) in mutations {
+                assignments[*w] = witness[*w].clone();
+            }
+            satisfied
+        };
+        let counterexample = |mutations: &[(usize, &BigInt)]| Counterexample {
+            signals: mutations
+                .iter()
+                .map(|(w, alternative)| MutatedSignal {
+                    symbol: signals[*w].symbol.clone(),
+                    witness: *w,
+                    value: witness[*w].clone(),
+                    alternative: (*alternative).clone(),
+                })
+                .collect(),
+        };
+
+        for w in mutable.iter() {
+            if counterexamples.len() >= options.max_counterexamples {
+                return counterexamples;
+            }
+            let alternatives = self.alternatives(&witness[*w]);
+            if let Some(alternative) = alternatives.iter().find(|v| satisfies(&[(*w, v)])) {
+                counterexamples.push(counterexample(&[(*w, alternative)]));
+                found.insert(*w);
+            }
+        }
+        if options.group_size < 2 {
+            return counterexamples;
+        }
+
+        let is_mutable: HashSet<usize> = mutable.into_iter().collect();
+        let mut tried = HashSet::new();
+        for c in self.constraints.iter() {
+            let mut wires: Vec<usize> = c
+                .a()
+                .keys()
+                .chain(c.b().keys())
+                .chain(c.c().keys())
+                .filter(|w| is_mutable.contains(w) && !found.contains(*w))
+                .cloned()
+                .collect();
+            wires.sort_unstable();
+            wires.dedup();
+            for (i, first) in wires.iter().enumerate() {
+                for second in wires[i + 1..].iter() {
+                    if found.contains(first) || found.contains(second) {
+                        continue;
+                    }
+                    if !tried.insert((*first, *second)) {
+                        continue;
+                    }
+                    if counterexamples.len() >= options.max_counterexamples {
+                        return counterexamples;
+                    }
+                    let firsts = self.alternatives(&witness[*first]);
+                    let seconds = self.alternatives(&witness[*second]);
+                    let pair = firsts.iter().find_map(|a| {
+                        seconds
+                            .iter()
+                            .find(|b| satisfies(&[(*first, a), (*second, b)]))
+                            .map(|b| [(*first, a), (*second, b)])
+                    });
+                    if let Some(pair) = pair {
+                        counterexamples.push(counterexample(&pair));
+                        found.insert(*first);
+                        found.insert(*second);
+                    }
+                }
+            }
+        }
+        counterexamples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::compile_main;
+
+    #[test]
+    fn underconstrained_signals_by_mutation() {
+        let src = indoc::indoc! {"
+            template Loose(){
+                signal input a;
+                signal output out;
+                signal b;
+                signal x;
+                signal y;
+                b <-- a * 2;
+                out <== b * b;
+                x <-- a;
+                y <-- a;
+                x + y === 2 * a;
+            }
+            component main = Loose();
+        "};
+        let library = compile_main(src);
+        let found = |options: &MutationOptions| {
+            let Ok(counterexamples) = library.detect_underconstrained(r#"{"a": 3}"#, options)
+            else {
+                panic!("analysis failed")
+            };
+            counterexamples
+                .iter()
+                .map(|c| c.signals.iter().map(|s| s.symbol.clone()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+
+        // b is only fixed up to its sign, x & y only by their sum
+        let counterexamples = found(&MutationOptions::default());
+        assert_eq!(counterexamples, vec![vec!["main.b"], vec!["main.x", "main.y"]]);
+        let alone = MutationOptions { group_size: 1, ..MutationOptions::default() };
+        assert_eq!(found(&alone), vec![vec!["main.b"]]);
+        for group_size in [0, 3] {
+            let options = MutationOptions { group_size, ..MutationOptions::default() };
+            match library.detect_underconstrained(r#"{"a": 3}"#, &options) {
+                Ok(_) => panic!("groups of {} are not searched", group_size),
+                Err(reports) => {
+                    assert!(matches!(reports[0].get_code(), ReportCode::UnsupportedGroupSize))
+                }
+            }
+        }
+
+        let Ok(counterexamples) =
+            library.detect_underconstrained(r#"{"a": 3}"#, &MutationOptions::default())
+        else {
+            panic!("analysis failed")
+        };
+        let b = &counterexamples[0].signals[0];
+        assert_eq!(b.value, BigInt::from(6));
+        assert_eq!(b.alternative, library.field() - 6);
+        let report = counterexamples[0].report();
+        assert!(matches!(report.get_code(), ReportCode::UnderconstrainedSignal));
+        assert!(report.get_notes()[0].starts_with("main.b = "));
+        let json = crate::json_export::produce_counterexamples_json(&counterexamples);
+        let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(json[1]["signals"][1]["symbol"], "main.y");
+    }
+}
@@ -6,25 +6,83 @@ use num::ToPrimitive;
 pub extern crate num_bigint_dig as num_bigint;
 use num_bigint::BigInt;
 use num_traits::Zero;
-use program_structure::{error_code::ReportCode, error_definition::Report};
-use std::{collections::HashMap, fmt::Debug, hash::Hasher};
+use program_structure::{
+    error_code::ReportCode,
+    error_definition::{Report, ReportCollection},
+};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hasher,
+    sync::{Arc, Mutex, OnceLock},
+};
 use wasmer::{
-    Instance, imports, AsEngineRef, AsStoreMut, Exports, Function, Memory, MemoryType, Module,
-    RuntimeError, Store, StoreMut, Value,
+    Instance, imports, AsEngineRef, AsStoreMut, Engine, Exports, Function, FunctionEnv,
+    FunctionEnvMut, Memory, MemoryType, Module, RuntimeError, Store, StoreMut, Value,
 };
 
+use wasmer_types::TrapCode;
+
 use crate::circuit_wasm::generate_circuit_wasm;
+use crate::reporting::produce_report_with_message;
 
 #[derive(thiserror::Error, Debug, Clone, Copy)]
 #[error("{0}")]
 struct ExitCode(u32);
 
+/// A line written by log(), with the path of the component instance
+/// that wrote it.
+#[derive(Clone)]
+pub struct LogRecord {
+    pub message: String,
+    pub component: Option<String>,
+}
+
+pub type Logs = Vec<LogRecord>;
+
+/// Paths of the component instances, by the index of their first signal.
+pub type ComponentPaths = HashMap<usize, String>;
+
+// state of the runtime host functions, for the evaluation in progress
+#[derive(Default)]
+struct Runtime {
+    exports: Option<Exports>,
+    paths: Arc<ComponentPaths>,
+    // first signals of the components being run, the innermost last.
+    // Only the evaluation module reports them.
+    components: Vec<usize>,
+    // what the log in progress wrote so far
+    line: Vec<String>,
+    logs: Logs,
+    // messages printed while unwinding a failure,
+    // from the failing template up to the main component
+    errors: Vec<String>,
+    // code passed to the exception handler
+    exception: Option<u32>,
+    // input signal being set, with the index of the value
+    input: Option<(String, usize)>,
+    // the evaluation module carries on past a === the witness breaks
+    skip_constraint_asserts: bool,
+}
+
+impl Runtime {
+    fn end_log(&mut self) {
+        let component = self.components.last().and_then(|start| self.paths.get(start)).cloned();
+        let message = std::mem::take(&mut self.line).join(" ");
+        self.logs.push(LogRecord { message, component });
+    }
+}
+
 pub struct WitnessCalculator {
     store: Store,
     exports: Exports,
+    runtime: Option<FunctionEnv<Runtime>>,
+    paths: Arc<ComponentPaths>,
     pub n64: u32,
     pub circom_version: u32,
     pub prime: BigInt,
+    // the loaded witness calculation module
+    binary: Vec<u8>,
 }
 
 impl Default for WitnessCalculator {
@@ -32,9 +90,12 @@ impl Default for WitnessCalculator {
         WitnessCalculator {
             store: Store::default(),
             exports: Exports::default(),
+            runtime: None,
+            paths: Arc::default(),
             n64: 0,
             circom_version: 0,
             prime: BigInt::default(),
+            binary: Vec::new(),
         }
     }
 }
@@ -66,17 +127,33 @@ impl WitnessCalculator {
             }
         }
     }
+    pub fn binary(&self) -> &[u8] {
+        &self.binary
+    }
+
     pub fn load(&mut self, binary: &[u8]) -> Result<(), Report> {
-        let mut module = match Module::new(&self.get_store(), binary) {
-            Ok(m) => m,
-            Err(e) => {
-                let mut err = Report::error(e.to_string(), ReportCode::RuntimeError);
-                err.add_note(format!("Was not able to load the witness calculation wasm module"));
-                return Err(err);
-            }
+        let module = compile_module(&self.get_store().as_engine_ref(), binary)?;
+        self.instantiate(&module)?;
+        self.binary = binary.to_vec();
+        Ok(())
+    }
+
+    // a calculator with its own store & memory for an already compiled module
+    pub fn from_module(
+        engine: &Engine,
+        module: &Module,
+        paths: &Arc<ComponentPaths>,
+    ) -> Result<WitnessCalculator, Report> {
+        let mut wc = WitnessCalculator {
+            store: Store::new(engine.clone()),
+            paths: paths.clone(),
+            ..Default::default()
         };
-        module.set_name("calculateWitness");
+        wc.instantiate(module)?;
+        Ok(wc)
+    }
 
+    fn instantiate(&mut self, module: &Module) -> Result<(), Report> {
         let memory = Memory::new(&mut self.get_mut_store(), MemoryType::new(65536, None, false));
         match memory {
             Ok(_) => {}
@@ -89,22 +166,35 @@ impl WitnessCalculator {
             }
         }
 
+        let runtime = FunctionEnv::new(
+            &mut self.store,
+            Runtime { paths: self.paths.clone(), ..Default::default() },
+        );
         let import_object = imports! {
             "env" => {
                 "memory" => memory.unwrap(),
             },
             // Host function callbacks from the WASM
             "runtime" => {
-                "exceptionHandler" => exception_handler(&mut self.store),
-                "printErrorMessage" => print_error_message(&mut self.store),
-                "writeBufferMessage" => write_buffer_message(&mut self.store),
-                "showSharedRWMemory" => show_memory(&mut self.store),
+                "exceptionHandler" => exception_handler(&mut self.store, &runtime),
+                "printErrorMessage" => print_error_message(&mut self.store, &runtime),
+                "writeBufferMessage" => write_buffer_message(&mut self.store, &runtime),
+                "showSharedRWMemory" => show_memory(&mut self.store, &runtime),
                 "printDebug" => print_debug(&mut self.store),
+            },
+            "veritas" => {
+                "enterComponent" => enter_component(&mut self.store, &runtime),
+                "exitComponent" => exit_component(&mut self.store, &runtime),
+                "skipConstraintAssert" => skip_constraint_assert(&mut self.store, &runtime),
             }
         };
 
-        match Instance::new(&mut self.store, &module, &import_object) {
-            Ok(instance) => self.exports.clone_from(&instance.exports),
+        match Instance::new(&mut self.store, module, &import_object) {
+            Ok(instance) => {
+                self.exports.clone_from(&instance.exports);
+                runtime.as_mut(&mut self.store).exports = Some(instance.exports.clone());
+                self.runtime = Some(runtime);
+            }
             Err(e) => {
                 let mut err = Report::error(format!("{:?}", e), ReportCode::RuntimeError);
                 err.add_note(format!(
@@ -130,16 +220,73 @@ impl WitnessCalculator {
                     }
                 }
                 let prime = from_array32(arr);
-                self.n64 = ((prime.bits() - 1) / 64 + 1) as u32;
+                self.n64 = n32 / 2;
+                self.prime = prime;
+                self.circom_version = self.get_version()?;
                 Ok(())
             }
             Err(e) => Err(e),
         }
     }
 
+    // the witness & what the circuit logged while calculating it.
+    // Logs & error messages of a failed calculation are added to its report.
     pub fn calculate_witness<I: IntoIterator<Item = (String, Vec<BigInt>)>>(
         &mut self,
         inputs: I,
+    ) -> Result<(Vec<BigInt>, Logs), Report> {
+        self.calculate_witness_with(inputs, false)
+    }
+
+    // calculate_witness, the evaluation module carrying on past the ===
+    // the witness breaks when skip_constraint_asserts is set
+    pub fn calculate_witness_with<I: IntoIterator<Item = (String, Vec<BigInt>)>>(
+        &mut self,
+        inputs: I,
+        skip_constraint_asserts: bool,
+    ) -> Result<(Vec<BigInt>, Logs), Report> {
+        if let Some(runtime) = &self.runtime {
+            runtime.as_mut(&mut self.store).skip_constraint_asserts = skip_constraint_asserts;
+        }
+        let witness = self.run(inputs);
+        let (logs, errors, exception, input) = match &self.runtime {
+            Some(runtime) => {
+                let runtime = runtime.as_mut(&mut self.store);
+                runtime.components.clear();
+                runtime.line.clear();
+                (
+                    std::mem::take(&mut runtime.logs),
+                    std::mem::take(&mut runtime.errors),
+                    runtime.exception.take(),
+                    runtime.input.take(),
+                )
+            }
+            None => (Vec::new(), Vec::new(), None, None),
+        };
+        match witness {
+            Ok(witness) => Ok((witness, logs)),
+            Err(err) => {
+                let mut err = match exception {
+                    Some(code) => exception_report(code, input, &errors),
+                    None => {
+                        let mut err = err;
+                        for error in errors {
+                            err.add_note(error);
+                        }
+                        err
+                    }
+                };
+                for log in logs {
+                    err.add_note(format!("log: {}", log.message));
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn run<I: IntoIterator<Item = (String, Vec<BigInt>)>>(
+        &mut self,
+        inputs: I,
     ) -> Result<Vec<BigInt>, Report> {
         let mut w = Vec::new();
 
@@ -151,9 +298,13 @@ impl WitnessCalculator {
 
         match n32 {
             Ok(n32) => {
+                let mut set = 0;
                 for (name, values) in inputs.into_iter() {
                     let (msb, lsb) = fnv(&name);
                     for (i, value) in values.into_iter().enumerate() {
+                        if let Some(runtime) = &self.runtime {
+                            runtime.as_mut(&mut self.store).input = Some((name.clone(), i));
+                        }
                         let f_arr = to_array32(&value, n32 as usize);
                         for j in 0..n32 {
                             match self
@@ -164,11 +315,19 @@ impl WitnessCalculator {
                             }
                         }
                         match self.set_input_signal(msb, lsb, i as u32) {
-                            Ok(_) => (),
+                            Ok(_) => set += 1,
                             Err(e) => return Err(e),
                         }
                     }
                 }
+                // the main component only runs once every input is set
+                let input_size = self.get_input_size()?;
+                if set < input_size {
+                    return Err(produce_report_with_message(
+                        ReportCode::WitnessMissingInputs,
+                        format!("got {} of {}", set, input_size),
+                    ));
+                }
                 match self.get_witness_size() {
                     Ok(witness_size) => {
                         for i in 0..witness_size {
@@ -201,6 +360,200 @@ impl WitnessCalculator {
     }
 }
 
+fn compile_module(engine: &impl AsEngineRef, binary: &[u8]) -> Result<Module, Report> {
+    match Module::new(engine, binary) {
+        Ok(mut module) => {
+            module.set_name("calculateWitness");
+            Ok(module)
+        }
+        Err(e) => {
+            let mut err = Report::error(e.to_string(), ReportCode::RuntimeError);
+            err.add_note(format!("Was not able to load the witness calculation wasm module"));
+            Err(err)
+        }
+    }
+}
+
+// the wasmer build & engine of the serialized modules, with the Veritas version
+pub fn engine_fingerprint() -> &'static str {
+    static FINGERPRINT: OnceLock<String> = OnceLock::new();
+    FINGERPRINT.get_or_init(|| {
+        format!(
+            "veritas {} wasmer {} {} {}-{}",
+            env!("CARGO_PKG_VERSION"),
+            wasmer::VERSION,
+            Engine::default().deterministic_id(),
+            std::env::consts::ARCH,
+            std::env::consts::OS
+        )
+    })
+}
+
+/// WitnessPool shares one compiled module between calculators,
+/// each with its own store & memory, so that witnesses can be
+/// calculated concurrently through a shared reference.
+pub struct WitnessPool {
+    engine: Engine,
+    module: Option<Module>,
+    idle: Mutex<Vec<WitnessCalculator>>,
+    // the module as circom --wasm writes it, the calculators
+    // run the evaluation module of the same circuit
+    binary: Vec<u8>,
+    paths: Arc<ComponentPaths>,
+    pub n64: u32,
+    pub circom_version: u32,
+    pub prime: BigInt,
+}
+
+impl Default for WitnessPool {
+    fn default() -> Self {
+        WitnessPool {
+            engine: Engine::default(),
+            module: None,
+            idle: Mutex::new(Vec::new()),
+            binary: Vec::new(),
+            paths: Arc::default(),
+            n64: 0,
+            circom_version: 0,
+            prime: BigInt::default(),
+        }
+    }
+}
+
+impl WitnessPool {
+    // the pool of a circuit, with the paths of its component instances
+    // to attribute the logs
+    pub fn new(circuit: &mut Circuit, paths: ComponentPaths) -> Result<WitnessPool, Report> {
+        let generate = |circuit: &Circuit| {
+            generate_circuit_wasm(circuit).map_err(|mut err| {
+                err.add_note(format!(
+                    "Was not able to generate the witness calculation wasm module"
+                ));
+                err
+            })
+        };
+        let wasmbin = generate(circuit)?;
+        circuit.wasm_producer.evaluation_module = true;
+        let evaluation = generate(circuit);
+        circuit.wasm_producer.evaluation_module = false;
+        WitnessPool::from_binary(&wasmbin, &evaluation?, paths)
+    }
+
+    pub fn from_binary(
+        binary: &[u8],
+        evaluation: &[u8],
+        paths: ComponentPaths,
+    ) -> Result<WitnessPool, Report> {
+        let engine = Engine::default();
+        if let Err(e) = Module::validate(&engine, evaluation) {
+            let mut err = Report::error(e.to_string(), ReportCode::RuntimeError);
+            err.add_note(format!("Was not able to validate the witness calculation wasm module"));
+            return Err(err);
+        }
+        let module = compile_module(&engine, evaluation)?;
+        let paths = Arc::new(paths);
+        let wc = WitnessCalculator::from_module(&engine, &module, &paths)?;
+        Ok(WitnessPool {
+            n64: wc.n64,
+            circom_version: wc.circom_version,
+            prime: wc.prime.clone(),
+            engine,
+            module: Some(module),
+            idle: Mutex::new(vec![wc]),
+            binary: binary.to_vec(),
+            paths,
+        })
+    }
+
+    // restore a pool from a module serialized by serialize_module,
+    // skipping the compilation of the wasm binary
+    pub fn from_serialized(
+        binary: &[u8],
+        serialized: &[u8],
+        paths: ComponentPaths,
+    ) -> Result<WitnessPool, Report> {
+        let engine = Engine::default();
+        // SAFETY: the serialized module holds native code, the caller must
+        // only pass modules serialized by a build of the same engine_fingerprint
+        // & check they are intact, as SnapshotReader does
+        let module = match unsafe { Module::deserialize(&engine, serialized.to_vec()) } {
+            Ok(module) => module,
+            Err(e) => {
+                let mut err = Report::error(e.to_string(), ReportCode::RuntimeError);
+                err.add_note(
+                    "Was not able to deserialize the witness calculation wasm module".to_string(),
+                );
+                return Err(err);
+            }
+        };
+        let paths = Arc::new(paths);
+        let wc = WitnessCalculator::from_module(&engine, &module, &paths)?;
+        Ok(WitnessPool {
+            n64: wc.n64,
+            circom_version: wc.circom_version,
+            prime: wc.prime.clone(),
+            engine,
+            module: Some(module),
+            idle: Mutex::new(vec![wc]),
+            binary: binary.to_vec(),
+            paths,
+        })
+    }
+
+    // the compiled module in wasmer's native format
+    pub fn serialize_module(&self) -> Result<Vec<u8>, Report> {
+        let module = self.module.as_ref().ok_or_else(|| {
+            Report::error(
+                "The witness calculation wasm module has not been loaded".to_string(),
+                ReportCode::RuntimeError,
+            )
+        })?;
+        module.serialize().map(|bytes| bytes.to_vec()).map_err(|e| {
+            let mut err = Report::error(e.to_string(), ReportCode::RuntimeError);
+            err.add_note(
+                "Was not able to serialize the witness calculation wasm module".to_string(),
+            );
+            err
+        })
+    }
+
+    pub fn binary(&self) -> &[u8] {
+        &self.binary
+    }
+
+    // number of calculators that have been instantiated & are not in use
+    pub fn idle(&self) -> usize {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn calculate_witness<I: IntoIterator<Item = (String, Vec<BigInt>)>>(
+        &self,
+        inputs: I,
+        skip_constraint_asserts: bool,
+    ) -> Result<(Vec<BigInt>, Logs), Report> {
+        let mut wc = self.checkout()?;
+        let witness = wc.calculate_witness_with(inputs, skip_constraint_asserts);
+        // an instance that trapped is dropped rather than reused
+        if witness.is_ok() {
+            self.idle.lock().unwrap_or_else(|e| e.into_inner()).push(wc);
+        }
+        witness
+    }
+
+    fn checkout(&self) -> Result<WitnessCalculator, Report> {
+        if let Some(wc) = self.idle.lock().unwrap_or_else(|e| e.into_inner()).pop() {
+            return Ok(wc);
+        }
+        match &self.module {
+            Some(module) => WitnessCalculator::from_module(&self.engine, module, &self.paths),
+            None => Err(Report::error(
+                "The witness calculation wasm module has not been loaded".to_string(),
+                ReportCode::RuntimeError,
+            )),
+        }
+    }
+}
+
 pub trait WitnessFunctions {
     fn init(&mut self, sanity_check: bool) -> Result<(), Report>;
 
@@ -218,6 +571,7 @@ pub trait WitnessFunctions {
     fn set_input_signal(&mut self, hmsb: u32, hlsb: u32, pos: u32) -> Result<(), Report>;
     fn get_witness(&mut self, i: u32) -> Result<(), Report>;
     fn get_witness_size(&mut self) -> Result<u32, Report>;
+    fn get_input_size(&mut self) -> Result<u32, Report>;
 }
 
 impl WitnessFunctions for WitnessCalculator {
@@ -276,6 +630,9 @@ impl WitnessFunctions for WitnessCalculator {
     fn get_witness_size(&mut self) -> Result<u32, Report> {
         self.get_u32("getWitnessSize", &[])
     }
+    fn get_input_size(&mut self) -> Result<u32, Report> {
+        self.get_u32("getInputSize", &[])
+    }
 
     fn get_u32(&mut self, name: &str, params: &[Value]) -> Result<u32, Report> {
         match self.do_call(name, params) {
@@ -289,6 +646,19 @@ impl WitnessFunctions for WitnessCalculator {
         match func {
             Ok(f) => match f.clone().call(&mut self.store, params) {
                 Ok(ret) => Ok(ret),
+                Err(e) if e.clone().to_trap() == Some(TrapCode::IntegerDivisionByZero) => {
+                    // the innermost template of the trace did the division
+                    let template = e.trace().iter().find_map(|frame| {
+                        frame.function_name().and_then(|name| name.strip_suffix("_run"))
+                    });
+                    Err(produce_report_with_message(
+                        ReportCode::WitnessDivisionByZero,
+                        match template {
+                            Some(template) => format!("template {}", template),
+                            None => "the main component".to_string(),
+                        },
+                    ))
+                }
                 Err(e) => {
                     let mut err = Report::error(e.to_string(), ReportCode::RuntimeError);
                     err.add_note(format!(
@@ -335,32 +705,139 @@ pub fn print_debug(store: &mut Store) -> Function {
     Function::new_typed(store, func)
 }
 
-// Circom 2.0
-pub fn exception_handler(store: &mut Store) -> Function {
-    #[allow(unused)]
-    fn func(a: i32) {}
-    Function::new_typed(store, func)
+// the evaluation module runs the component with its first signal at the index
+fn enter_component(store: &mut Store, runtime: &FunctionEnv<Runtime>) -> Function {
+    fn func(mut env: FunctionEnvMut<Runtime>, signal_start: i32) {
+        env.data_mut().components.push(signal_start as u32 as usize);
+    }
+    Function::new_typed_with_env(store, runtime, func)
 }
 
-// Circom 2.0
-pub fn show_memory(store: &mut Store) -> Function {
-    #[allow(unused)]
-    fn func() {}
-    Function::new_typed(store, func)
+// the evaluation module is done with the innermost component
+fn exit_component(store: &mut Store, runtime: &FunctionEnv<Runtime>) -> Function {
+    fn func(mut env: FunctionEnvMut<Runtime>) {
+        env.data_mut().components.pop();
+    }
+    Function::new_typed_with_env(store, runtime, func)
 }
 
-// Circom 2.0
-pub fn print_error_message(store: &mut Store) -> Function {
-    #[allow(unused)]
-    fn func() {}
-    Function::new_typed(store, func)
+// the evaluation module met a === the witness breaks, 1 to carry on
+fn skip_constraint_assert(store: &mut Store, runtime: &FunctionEnv<Runtime>) -> Function {
+    fn func(env: FunctionEnvMut<Runtime>) -> i32 {
+        env.data().skip_constraint_asserts as i32
+    }
+    Function::new_typed_with_env(store, runtime, func)
 }
 
-// Circom 2.0
-pub fn write_buffer_message(store: &mut Store) -> Function {
-    #[allow(unused)]
-    fn func() {}
-    Function::new_typed(store, func)
+// Circom 2.0, the calculation failed with one of the exception codes
+// of the module, stop it there
+fn exception_handler(store: &mut Store, runtime: &FunctionEnv<Runtime>) -> Function {
+    fn func(mut env: FunctionEnvMut<Runtime>, code: i32) -> Result<(), RuntimeError> {
+        env.data_mut().exception = Some(code as u32);
+        Err(RuntimeError::user(Box::new(ExitCode(code as u32))))
+    }
+    Function::new_typed_with_env(store, runtime, func)
+}
+
+// report of the exception raised by the module, with the input signal being set
+// & the messages printed from the failing template up to the main component
+fn exception_report(code: u32, input: Option<(String, usize)>, errors: &[String]) -> Report {
+    let signal = match input {
+        Some((name, 0)) if code == 1 => name,
+        Some((name, i)) => format!("{}[{}]", name, i),
+        None => "an input signal".to_string(),
+    };
+    let component = match errors.first() {
+        Some(error) => error.trim_start_matches("Error in ").to_string(),
+        None => "the main component".to_string(),
+    };
+    let mut err = match code {
+        1 => produce_report_with_message(ReportCode::WitnessSignalNotFound, signal),
+        2 => produce_report_with_message(ReportCode::WitnessTooManyInputs, signal),
+        3 => produce_report_with_message(ReportCode::WitnessSignalAlreadySet, signal),
+        4 => produce_report_with_message(ReportCode::WitnessAssertFailed, component),
+        5 => produce_report_with_message(ReportCode::WitnessOutOfMemory, component),
+        6 => produce_report_with_message(ReportCode::WitnessInputOutOfBounds, signal),
+        _ => Report::error(
+            format!("The witness calculation failed with exception code {}", code),
+            ReportCode::RuntimeError,
+        ),
+    };
+    for error in errors.iter().skip(1) {
+        err.add_note(format!("called from {}", error.trim_start_matches("Error in ")));
+    }
+    err
+}
+
+// Circom 2.0, logs the value copied to the shared memory
+fn show_memory(store: &mut Store, runtime: &FunctionEnv<Runtime>) -> Function {
+    fn func(mut env: FunctionEnvMut<Runtime>) {
+        let (runtime, mut store) = env.data_and_store_mut();
+        let Some(exports) = runtime.exports.as_ref() else {
+            return;
+        };
+        let (Ok(n32), Ok(read)) =
+            (exports.get_function("getFieldNumLen32"), exports.get_function("readSharedRWMemory"))
+        else {
+            return;
+        };
+        let Some(n32) = n32.call(&mut store, &[]).ok().and_then(|r| r[0].i32()) else {
+            return;
+        };
+        let mut arr = vec![0; n32 as usize];
+        for j in 0..n32 {
+            match read.call(&mut store, &[Value::I32(j)]).ok().and_then(|r| r[0].i32()) {
+                Some(v) => arr[(n32 - 1 - j) as usize] = v as u32,
+                None => return,
+            }
+        }
+        runtime.line.push(from_array32(arr).to_string());
+    }
+    Function::new_typed_with_env(store, runtime, func)
+}
+
+// Circom 2.0, an assert or an access failed with the message in the buffer
+fn print_error_message(store: &mut Store, runtime: &FunctionEnv<Runtime>) -> Function {
+    fn func(mut env: FunctionEnvMut<Runtime>) {
+        let msg = read_message(&mut env);
+        env.data_mut().errors.push(msg);
+    }
+    Function::new_typed_with_env(store, runtime, func)
+}
+
+// Circom 2.0, logs the string in the buffer, a new line ends the log
+fn write_buffer_message(store: &mut Store, runtime: &FunctionEnv<Runtime>) -> Function {
+    fn func(mut env: FunctionEnvMut<Runtime>) {
+        let msg = read_message(&mut env);
+        let runtime = env.data_mut();
+        if msg == "\n" {
+            runtime.end_log();
+        } else {
+            runtime.line.push(msg);
+        }
+    }
+    Function::new_typed_with_env(store, runtime, func)
+}
+
+// size of the messages & of the message buffer of the module
+const MESSAGE_SIZE: u64 = 256;
+
+// drain the message buffer through getMessageChar
+fn read_message(env: &mut FunctionEnvMut<Runtime>) -> String {
+    let (runtime, mut store) = env.data_and_store_mut();
+    let Some(get_char) =
+        runtime.exports.as_ref().and_then(|e| e.get_function("getMessageChar").ok())
+    else {
+        return String::new();
+    };
+    let mut msg = Vec::new();
+    for _ in 0..MESSAGE_SIZE {
+        match get_char.call(&mut store, &[]).ok().and_then(|r| r[0].i32()) {
+            Some(0) | None => break,
+            Some(c) => msg.push(c as u8),
+        }
+    }
+    String::from_utf8_lossy(&msg).into_owned()
 }
 
 pub fn log_signal(store: &mut Store) -> Function {
@@ -405,59 +882,627 @@ fn fnv(inp: &str) -> (u32, u32) {
     ((h >> 32) as u32, h as u32)
 }
 
-pub fn value_to_bigint(v: serde_json::Value) -> BigInt {
+/// value_to_bigint parses a decimal or 0x prefixed hex string, a 64 bit integer or a boolean.
+/// Negative values are kept as is, they are reduced modulo the prime of the circuit.
+pub fn value_to_bigint(v: serde_json::Value) -> Result<BigInt, Report> {
     match v {
         serde_json::Value::String(inner) => {
-            if inner.starts_with("0x") {
-                BigInt::parse_bytes(&inner[2..].as_bytes(), 16).unwrap()
-            } else {
-                BigInt::parse_bytes(inner.as_bytes(), 10).unwrap()
+            let (negative, digits) = match inner.strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, inner.as_str()),
+            };
+            let parsed = match digits.strip_prefix("0x") {
+                Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16),
+                None => BigInt::parse_bytes(digits.as_bytes(), 10),
+            };
+            match parsed {
+                // parse_bytes takes a sign of its own
+                Some(value) if !digits.starts_with(['-', '+']) => {
+                    Ok(if negative { -value } else { value })
+                }
+                _ => Err(produce_report_with_message(
+                    ReportCode::MalformedInputJson,
+                    format!("\"{}\" is not a decimal or 0x prefixed hex number", inner),
+                )),
             }
         }
-        serde_json::Value::Number(inner) => BigInt::from(inner.as_u64().expect("not a u32")),
-        _ => BigInt::zero(),
+        serde_json::Value::Number(inner) => {
+            let parsed = match inner.as_i64() {
+                Some(value) => Some(BigInt::from(value)),
+                None => inner.as_u64().map(BigInt::from),
+            };
+            parsed.ok_or_else(|| {
+                produce_report_with_message(
+                    ReportCode::MalformedInputJson,
+                    format!("{} is not a 64 bit integer, pass it as a string", inner),
+                )
+            })
+        }
+        serde_json::Value::Bool(inner) => Ok(BigInt::from(inner as u8)),
+        v => Err(produce_report_with_message(
+            ReportCode::MalformedInputJson,
+            format!("{} is not a valid input value", v),
+        )),
     }
 }
 
-/// parse_inputs accepts a JSON string and returns a HashMap of BigInts.
-pub fn parse_inputs(inputs_str: &str) -> HashMap<String, Vec<BigInt>> {
-    let inputs: std::collections::HashMap<String, serde_json::Value> =
-        serde_json::from_str(&inputs_str).unwrap();
-    inputs
+pub type CircuitInputs = HashMap<String, InputValues>;
+
+/// The values given to an input signal, row-major, & the shape of the arrays they came in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputValues {
+    // length of each nesting level, empty for a single value
+    pub shape: Vec<usize>,
+    pub values: Vec<BigInt>,
+}
+
+// e.g. 6 values as [2][3]
+fn display_shape(shape: &[usize], values: usize) -> String {
+    if shape.is_empty() {
+        return "a single value".to_string();
+    }
+    let dimensions: String = shape.iter().map(|n| format!("[{}]", n)).collect();
+    format!("{} values as {}", values, dimensions)
+}
+
+/// An input signal of the main component, the inputs are checked against.
+/// Inputs of a bus are listed as a whole & field by field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputSignal {
+    pub name: String,
+    pub dimensions: Vec<usize>,
+    pub public: bool,
+    // position of its first value among the signals of the main component
+    pub start: usize,
+    // number of values, of all the fields for a bus
+    pub size: usize,
+}
+
+impl InputSignal {
+    // the shape of the arrays its values come in: the dimensions of the signal,
+    // followed by the number of values of each element for a bus
+    pub fn shape(&self) -> Vec<usize> {
+        let elements: usize = self.dimensions.iter().product();
+        let mut shape = self.dimensions.clone();
+        if elements > 0 && self.size / elements > 1 {
+            shape.push(self.size / elements);
+        }
+        shape
+    }
+
+    // whether the values are given in its shape, or listed flat
+    // as the input files of circom may list them
+    fn fits(&self, input: &InputValues) -> bool {
+        input.values.len() == self.size
+            && (input.shape == self.shape()
+                || input.shape == [self.size]
+                || (input.shape.is_empty() && self.size == 1))
+    }
+}
+
+impl std::fmt::Display for InputSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        for dimension in self.dimensions.iter() {
+            write!(f, "[{}]", dimension)?;
+        }
+        write!(f, " ({})", if self.public { "public" } else { "private" })
+    }
+}
+
+/// input_signature lists the input signals of the main component.
+/// The public signals (outputs & public inputs) follow the constant signal.
+pub fn input_signature(circuit: &Circuit, no_public: usize) -> Vec<InputSignal> {
+    circuit
+        .wasm_producer
+        .get_main_input_list()
         .iter()
-        .map(|(key, value)| {
-            let res = match value {
-                serde_json::Value::String(inner) => {
-                    if inner.starts_with("0x") {
-                        vec![BigInt::parse_bytes(&inner[2..].as_bytes(), 16).unwrap()]
-                    } else {
-                        vec![BigInt::parse_bytes(inner.as_bytes(), 10).unwrap()]
-                    }
+        .map(|input| InputSignal {
+            name: input.name.clone(),
+            dimensions: input.dimensions.clone(),
+            public: input.start <= no_public,
+            start: input.start,
+            size: input.size,
+        })
+        .collect()
+}
+
+/// check_inputs reports every input that is not an input signal of the main component,
+/// every input signal without inputs & every input with the wrong number or shape of values.
+pub fn check_inputs(inputs: &CircuitInputs, signature: &[InputSignal]) -> ReportCollection {
+    let mut reports = Vec::new();
+    // signals given a value
+    let mut given = Vec::new();
+    let mut names: Vec<&String> = inputs.keys().collect();
+    names.sort();
+    for name in names {
+        let input = &inputs[name];
+        match signature.iter().find(|signal| &signal.name == name) {
+            Some(signal) => {
+                if !signal.fits(input) {
+                    reports.push(produce_report_with_message(
+                        ReportCode::InputSignalSizeMismatch,
+                        format!(
+                            "{} expects {}, got {}",
+                            signal,
+                            display_shape(&signal.shape(), signal.size),
+                            display_shape(&input.shape, input.values.len())
+                        ),
+                    ));
                 }
-                serde_json::Value::Number(inner) => {
-                    vec![BigInt::from(inner.as_u64().expect("not a u32"))]
+                given.push(signal.start..signal.start + signal.size);
+            }
+            None => {
+                let mut report =
+                    produce_report_with_message(ReportCode::UnknownInputSignal, name.clone());
+                let expected: Vec<String> = signature.iter().map(|s| s.to_string()).collect();
+                report.add_note(format!("The input signals are {}", expected.join(", ")));
+                reports.push(report);
+            }
+        }
+    }
+
+    // a bus without any input is missing as a whole, rather than field by field
+    let mut signals: Vec<&InputSignal> = signature.iter().collect();
+    signals.sort_by_key(|signal| (signal.start, std::cmp::Reverse(signal.size)));
+    let mut missing: Vec<&InputSignal> = Vec::new();
+    for signal in signals {
+        let end = signal.start + signal.size;
+        let is_given = given.iter().any(|range| range.start < end && signal.start < range.end);
+        let in_missing =
+            missing.iter().any(|bus| bus.start <= signal.start && end <= bus.start + bus.size);
+        if !is_given && !in_missing {
+            missing.push(signal);
+        }
+    }
+    for signal in signature.iter() {
+        if missing.contains(&signal) {
+            reports.push(produce_report_with_message(
+                ReportCode::MissingInputSignal,
+                signal.to_string(),
+            ));
+        }
+    }
+    reports
+}
+
+/// parse_inputs accepts a JSON string and returns a HashMap of BigInts.
+pub fn parse_inputs(inputs_str: &str) -> Result<CircuitInputs, Report> {
+    let inputs: std::collections::HashMap<String, serde_json::Value> =
+        serde_json::from_str(inputs_str).map_err(|e| {
+            produce_report_with_message(ReportCode::MalformedInputJson, e.to_string())
+        })?;
+    parse_input_object(inputs)
+}
+
+/// parse_batch_inputs accepts a JSON array of input objects.
+/// A malformed entry is reported on its own without failing the batch.
+pub fn parse_batch_inputs(inputs_str: &str) -> Result<Vec<Result<CircuitInputs, Report>>, Report> {
+    let batch: Vec<serde_json::Value> = serde_json::from_str(inputs_str)
+        .map_err(|e| produce_report_with_message(ReportCode::MalformedInputJson, e.to_string()))?;
+    Ok(batch
+        .into_iter()
+        .map(|inputs| {
+            serde_json::from_value(inputs)
+                .map_err(|e| {
+                    produce_report_with_message(ReportCode::MalformedInputJson, e.to_string())
+                })
+                .and_then(parse_input_object)
+        })
+        .collect())
+}
+
+/// A witness given by the caller: the full witness, or the values of some
+/// signals over the witness of the inputs (or over the full witness).
+pub struct SuppliedWitness {
+    pub inputs: Option<CircuitInputs>,
+    pub witness: Option<Vec<BigInt>>,
+    // values by symbol, sorted by symbol
+    pub signals: Vec<(String, BigInt)>,
+}
+
+/// parse_supplied_witness accepts a JSON object of "inputs", the "witness"
+/// as an array & the "signals" overridden, as an object keyed by symbol.
+pub fn parse_supplied_witness(witness_str: &str) -> Result<SuppliedWitness, Report> {
+    let malformed = |msg: String| produce_report_with_message(ReportCode::MalformedInputJson, msg);
+    let fields: HashMap<String, serde_json::Value> =
+        serde_json::from_str(witness_str).map_err(|e| malformed(e.to_string()))?;
+    let mut supplied = SuppliedWitness { inputs: None, witness: None, signals: Vec::new() };
+    for (field, value) in fields {
+        match (field.as_str(), value) {
+            ("inputs", serde_json::Value::Object(inputs)) => {
+                supplied.inputs = Some(parse_input_object(inputs.into_iter().collect())?);
+            }
+            ("witness", serde_json::Value::Array(values)) => {
+                let values: Result<Vec<BigInt>, Report> =
+                    values.into_iter().map(value_to_bigint).collect();
+                supplied.witness = Some(values?);
+            }
+            ("signals", serde_json::Value::Object(signals)) => {
+                for (symbol, value) in signals {
+                    supplied.signals.push((symbol, value_to_bigint(value)?));
                 }
-                serde_json::Value::Array(inner) => {
-                    inner.iter().cloned().map(value_to_bigint).collect()
+                supplied.signals.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+            ("inputs" | "witness" | "signals", value) => {
+                return Err(malformed(format!("unexpected {} of {}", value, field)));
+            }
+            (field, _) => return Err(malformed(format!("unknown field {}", field))),
+        }
+    }
+    Ok(supplied)
+}
+
+fn parse_input_object(inputs: HashMap<String, serde_json::Value>) -> Result<CircuitInputs, Report> {
+    let mut parsed = HashMap::new();
+    for (name, value) in inputs {
+        parse_input(name, value, &mut parsed)?;
+    }
+    Ok(parsed)
+}
+
+// the values of a signal, or the fields of a bus as inputs of their own:
+// {"in": {"a": {"b": 1}}} sets in.a.b & {"in": [{"a": 1}, {"a": 2}]} sets in[0].a & in[1].a
+fn parse_input(
+    name: String,
+    value: serde_json::Value,
+    inputs: &mut CircuitInputs,
+) -> Result<(), Report> {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (field, value) in fields {
+                parse_input(format!("{}.{}", name, field), value, inputs)?;
+            }
+        }
+        serde_json::Value::Array(elements) if elements.iter().any(has_fields) => {
+            for (i, element) in elements.into_iter().enumerate() {
+                if !has_fields(&element) {
+                    return Err(produce_report_with_message(
+                        ReportCode::MalformedInputJson,
+                        format!("{} mixes values & bus fields", name),
+                    ));
                 }
-                _ => vec![BigInt::zero()],
-            };
+                parse_input(format!("{}[{}]", name, i), element, inputs)?;
+            }
+        }
+        value => {
+            let mut values = Vec::new();
+            let shape = flatten_values(&name, value, &mut values)?;
+            if inputs.insert(name.clone(), InputValues { shape, values }).is_some() {
+                return Err(produce_report_with_message(
+                    ReportCode::MalformedInputJson,
+                    format!("{} is given more than once", name),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
 
-            (key.clone(), res)
-        })
-        .collect::<HashMap<_, _>>()
+fn has_fields(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(_) => true,
+        serde_json::Value::Array(elements) => elements.iter().any(has_fields),
+        _ => false,
+    }
+}
+
+// values of a multi-dimensional array, row-major, & the shape of the array,
+// which must be rectangular: its elements all have the shape of the first one
+fn flatten_values(
+    name: &str,
+    value: serde_json::Value,
+    values: &mut Vec<BigInt>,
+) -> Result<Vec<usize>, Report> {
+    match value {
+        serde_json::Value::Array(elements) => {
+            let mut shape = vec![elements.len()];
+            let mut first: Option<Vec<usize>> = None;
+            for element in elements {
+                let inner = flatten_values(name, element, values)?;
+                match &first {
+                    None => first = Some(inner),
+                    Some(first) if *first == inner => (),
+                    Some(first) => {
+                        let msg = if first.is_empty() || inner.is_empty() {
+                            format!("{} mixes values & arrays", name)
+                        } else {
+                            format!("{} is not a rectangular array", name)
+                        };
+                        return Err(produce_report_with_message(
+                            ReportCode::MalformedInputJson,
+                            msg,
+                        ));
+                    }
+                }
+            }
+            shape.extend(first.unwrap_or_default());
+            Ok(shape)
+        }
+        value => {
+            values.push(value_to_bigint(value)?);
+            Ok(Vec::new())
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::circuit::CircuitLibrary;
+    use crate::fixtures::compile_main;
     #[test]
     fn test_parse_inputs() {
         let inputs_str = r#"{"a": "1", "b": 2, "c": [3, 4], "d": "0x011"}"#;
-        let inputs = parse_inputs(inputs_str);
-        assert_eq!(inputs["a"], vec![BigInt::from(1)]);
-        assert_eq!(inputs["b"], vec![BigInt::from(2)]);
-        assert_eq!(inputs["c"], vec![BigInt::from(3), BigInt::from(4)]);
-        assert_eq!(inputs["d"], vec![BigInt::from(0x11)]);
+        let inputs = match parse_inputs(inputs_str) {
+            Ok(inputs) => inputs,
+            Err(r) => panic!("failed to parse inputs: {}", r.get_message()),
+        };
+        assert_eq!(inputs["a"].values, vec![BigInt::from(1)]);
+        assert_eq!(inputs["b"].values, vec![BigInt::from(2)]);
+        assert_eq!(inputs["c"].values, vec![BigInt::from(3), BigInt::from(4)]);
+        assert_eq!(inputs["d"].values, vec![BigInt::from(0x11)]);
+    }
+
+    #[test]
+    fn test_parse_structured_inputs() {
+        let inputs_str = r#"{
+            "a": [[1, 2, 3], [4, 5, 6]],
+            "b": "-21888242871839275222246405745257275088548364400416034343698204186575808495617",
+            "c": [-1, true, false, "-0x10", 18446744073709551615],
+            "p": {"x": 1, "y": {"z": [2, 3]}},
+            "q": [[{"x": 4}], [{"x": 5}]]
+        }"#;
+        let inputs = match parse_inputs(inputs_str) {
+            Ok(inputs) => inputs,
+            Err(r) => panic!("failed to parse inputs: {}", r.get_message()),
+        };
+        let values = |v: &[i64]| v.iter().map(|v| BigInt::from(*v)).collect::<Vec<_>>();
+        assert_eq!(inputs["a"].values, values(&[1, 2, 3, 4, 5, 6]));
+        assert_eq!(inputs["a"].shape, vec![2, 3]);
+        assert_eq!(
+            inputs["b"].values,
+            vec![-BigInt::parse_bytes(
+                b"21888242871839275222246405745257275088548364400416034343698204186575808495617",
+                10
+            )
+            .unwrap()]
+        );
+        assert_eq!(inputs["c"].values[..4], values(&[-1, 1, 0, -16])[..]);
+        assert_eq!(inputs["c"].values[4], BigInt::from(u64::MAX));
+        assert_eq!(inputs["p.x"].values, values(&[1]));
+        assert_eq!(inputs["p.y.z"].values, values(&[2, 3]));
+        assert_eq!(inputs["q[0][0].x"].values, values(&[4]));
+        assert_eq!(inputs["q[1][0].x"].values, values(&[5]));
+        assert_eq!(inputs.len(), 7);
+    }
+
+    #[test]
+    fn test_parse_batch_inputs() {
+        let batch = match parse_batch_inputs(r#"[{"a": "1"}, 2, {"a": [3, "0x4"]}]"#) {
+            Ok(batch) => batch,
+            Err(r) => panic!("failed to parse the batch: {}", r.get_message()),
+        };
+        assert_eq!(batch.len(), 3);
+        assert!(matches!(&batch[0], Ok(inputs) if inputs["a"].values == vec![BigInt::from(1)]));
+        assert!(matches!(&batch[1], Err(r) if r.get_code().to_string() == "VE05"));
+        assert!(
+            matches!(&batch[2], Ok(inputs) if inputs["a"].values == vec![BigInt::from(3), BigInt::from(4)])
+        );
+        assert!(parse_batch_inputs(r#"{"a": "1"}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_malformed_inputs() {
+        for inputs_str in [
+            r#"{"a": "1""#,
+            r#"{"a": "0xzz"}"#,
+            r#"{"a": "--2"}"#,
+            r#"{"a": [1, null]}"#,
+            r#"{"a": 1.5}"#,
+            r#"{"a": 18446744073709551616}"#,
+            r#"{"a": [{"x": 1}, 2]}"#,
+            r#"{"a": {"x": 1}, "a.x": 2}"#,
+            r#"{"a": [[1, 2], 3]}"#,
+            r#"{"a": [[[1], [2]], [[3], 4]]}"#,
+        ] {
+            let err = parse_inputs(inputs_str).unwrap_err();
+            assert_eq!(err.get_code().to_string(), "VE05");
+        }
+    }
+
+    #[test]
+    fn test_parse_ragged_inputs() {
+        for (inputs_str, msg) in [
+            (r#"{"a": [[1, 2, 3, 4], [5, 6]]}"#, "a is not a rectangular array"),
+            (r#"{"p": {"x": [[1], [2, 3]]}}"#, "p.x is not a rectangular array"),
+            (r#"{"a": [1, [2, 3]]}"#, "a mixes values & arrays"),
+        ] {
+            let err = parse_inputs(inputs_str).unwrap_err();
+            assert_eq!(err.get_code().to_string(), "VE05");
+            assert_eq!(err.get_notes()[0], msg);
+        }
+        // the empty arrays are rectangular
+        let inputs = parse_inputs(r#"{"a": [[], []]}"#).ok().unwrap();
+        assert_eq!(inputs["a"].shape, vec![2, 0]);
+    }
+
+    #[test]
+    fn capture_logs() {
+        let src = indoc::indoc! {"
+            function double(x) {
+                log(\"double\", x);
+                return 2 * x;
+            }
+            template Square(){
+                signal input a;
+                signal output out;
+                log(\"square of\", a);
+                out <== a * a;
+                log(out, double(a));
+            }
+            template Squares(){
+                signal input a;
+                signal output out;
+                component sq[2];
+                sq[0] = Square();
+                sq[0].a <== a;
+                sq[1] = Square();
+                sq[1].a <== sq[0].out;
+                out <== sq[1].out;
+                log(\"done\");
+            }
+            component main = Squares();
+        "};
+        let library = compile_main(src);
+        // a log calling a function that logs is written on one line, as circom does
+        let expected = vec![
+            ("square of 3", Some("main.sq[0]")),
+            ("9 double 3", Some("main.sq[0]")),
+            ("6", Some("main.sq[0]")),
+            ("square of 9", Some("main.sq[1]")),
+            ("81 double 9", Some("main.sq[1]")),
+            ("18", Some("main.sq[1]")),
+            ("done", Some("main")),
+        ];
+        match library.execute(r#"{"a": 3}"#) {
+            Ok((_, _, logs)) => {
+                let logs: Vec<(&str, Option<&str>)> =
+                    logs.iter().map(|l| (l.message.as_str(), l.component.as_deref())).collect();
+                assert_eq!(logs, expected);
+            }
+            Err(_) => panic!("execution failed"),
+        }
+        // the logs are kept per evaluation
+        match library.execute(r#"{"a": 5}"#) {
+            Ok((_, _, logs)) => {
+                assert_eq!(logs.len(), 7);
+                assert_eq!(logs[0].message, "square of 5");
+            }
+            Err(_) => panic!("execution failed"),
+        }
+
+        // the exported module is the one circom writes, without the hooks
+        // that tell the host which component runs
+        let wasm = library.export_wasm().ok().unwrap();
+        let module = wasmer::Module::new(&wasmer::Engine::default(), &wasm).unwrap();
+        assert!(module.imports().all(|i| i.module() == "runtime" || i.module() == "env"));
+
+        // a restored library attributes the logs the same way
+        let restored = CircuitLibrary::load(&library.save().ok().unwrap()).ok().unwrap();
+        match restored.execute(r#"{"a": 3}"#) {
+            Ok((_, _, logs)) => {
+                assert_eq!(logs[3].component.as_deref(), Some("main.sq[1]"));
+            }
+            Err(_) => panic!("execution failed"),
+        }
+    }
+
+    #[test]
+    fn check_inputs_against_signature() {
+        let src = indoc::indoc! {"
+            bus Point(){
+                signal x;
+                signal y;
+            }
+            template Inputs(){
+                signal input a;
+                signal input b[2][3];
+                input Point() p;
+                signal input c;
+                signal output out;
+                out <== a * c + b[1][2] + p.y;
+            }
+            component main {public [c, b]} = Inputs();
+        "};
+        let library = compile_main(src);
+        let signature: Vec<String> =
+            library.get_signature().iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            signature,
+            vec![
+                "b[2][3] (public)",
+                "c (public)",
+                "a (private)",
+                "p.x (private)",
+                "p.y (private)",
+                "p (private)",
+            ]
+        );
+
+        let b = "[1, 2, 3, 4, 5, 6]";
+        for input in [
+            format!(r#"{{"a": 1, "b": {}, "c": 2, "p": [3, 4]}}"#, b),
+            format!(r#"{{"a": 1, "b": {}, "c": 2, "p.x": 3, "p.y": 4}}"#, b),
+            r#"{"a": 1, "b": [[1, 2, 3], [4, 5, 6]], "c": 2, "p": {"x": 3, "y": 4}}"#.to_string(),
+        ] {
+            assert!(library.execute(&input).is_ok());
+        }
+        // negative inputs are reduced modulo the prime
+        let input = format!(r#"{{"a": -1, "b": {}, "c": "-0x2", "p": [3, true]}}"#, b);
+        match library.execute(&input) {
+            Ok((witness, _, _)) => {
+                let field = library.field().clone();
+                // out, b, c, a, p.y
+                assert_eq!(witness[1], BigInt::from(2) + BigInt::from(6) + BigInt::from(1));
+                assert_eq!(witness[8], &field - 2);
+                assert_eq!(witness[9], &field - 1);
+            }
+            Err(_) => panic!("execution failed"),
+        }
+
+        let failures = |input: &str| match library.execute(input) {
+            Ok(_) => panic!("execution of {} should fail", input),
+            Err(reports) => reports
+                .iter()
+                .map(|r| (r.get_code().to_string(), r.get_message().clone()))
+                .collect::<Vec<_>>(),
+        };
+        let reports = failures(r#"{"a": 1, "b": [1, 2, 3, 4], "p.x": 3, "d": 5}"#);
+        assert_eq!(
+            reports,
+            vec![
+                (
+                    "VE19".to_string(),
+                    "The input signal b[2][3] (public) expects 6 values as [2][3], got 4 values as [4]"
+                        .to_string()
+                ),
+                ("VE18".to_string(), "Unknown input signal d".to_string()),
+                ("VE17".to_string(), "The input signal c (public) is missing".to_string()),
+                ("VE17".to_string(), "The input signal p.y (private) is missing".to_string()),
+            ]
+        );
+        // the arrays must have the dimensions of the signal, unless listed flat
+        let reports = failures(r#"{"a": 1, "b": [[1, 2], [3, 4], [5, 6]], "c": 2, "p": [3, 4]}"#);
+        assert_eq!(
+            reports,
+            vec![(
+                "VE19".to_string(),
+                "The input signal b[2][3] (public) expects 6 values as [2][3], got 6 values as [3][2]"
+                    .to_string()
+            )]
+        );
+        let reports = failures(&format!(r#"{{"a": [[1]], "b": {}, "c": 2, "p": [[3], [4]]}}"#, b));
+        assert_eq!(
+            reports,
+            vec![
+                (
+                    "VE19".to_string(),
+                    "The input signal a (private) expects a single value, got 1 values as [1][1]"
+                        .to_string()
+                ),
+                (
+                    "VE19".to_string(),
+                    "The input signal p (private) expects 2 values as [2], got 2 values as [2][1]"
+                        .to_string()
+                ),
+            ]
+        );
+        // a bus without any input is missing as a whole
+        let reports = failures(&format!(r#"{{"a": 1, "b": {}, "c": 2}}"#, b));
+        assert_eq!(
+            reports,
+            vec![("VE17".to_string(), "The input signal p (private) is missing".to_string())]
+        );
     }
 }
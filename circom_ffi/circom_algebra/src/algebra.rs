@@ -838,6 +838,8 @@ where
 {
     pub(crate) from: C,
     pub(crate) to: HashMap<C, BigInt>,
+    // origins of the constraints the substitution was cleared from
+    pub(crate) origins: Vec<usize>,
 }
 impl<C: Default + Clone + Display + Hash + Eq> Substitution<C> {
     // Substitution public utils
@@ -847,15 +849,15 @@ impl<C: Default + Clone + Display + Hash + Eq> Substitution<C> {
             Number { value } => {
                 let mut to = HashMap::new();
                 to.insert(ArithmeticExpression::constant_coefficient(), value);
-                Option::Some(Substitution { from, to })
+                Option::Some(Substitution { from, to, origins: Vec::new() })
             }
             Signal { symbol } => {
                 let mut to = HashMap::new();
                 to.insert(symbol, BigInt::from(1));
-                Option::Some(Substitution { from, to })
+                Option::Some(Substitution { from, to, origins: Vec::new() })
             }
             Linear { coefficients: to } if !to.contains_key(&from) => {
-                Option::Some(Substitution { from, to })
+                Option::Some(Substitution { from, to, origins: Vec::new() })
             }
             _ => Option::None,
         }
@@ -884,10 +886,13 @@ impl<C: Default + Clone + Display + Hash + Eq> Substitution<C> {
     {
         let from = symbol_correspondence.get(&substitution.from).unwrap().clone();
         let to = apply_raw_correspondence(&substitution.to, symbol_correspondence);
-        Substitution { to, from }
+        Substitution { to, from, origins: substitution.origins.clone() }
     }
 
     pub fn apply_substitution(src: &mut Substitution<C>, change: &Substitution<C>, field: &BigInt) {
+        if src.to.contains_key(&change.from) {
+            merge_origins(&mut src.origins, &change.origins);
+        }
         raw_substitution(&mut src.to, change, field);
     }
 
@@ -900,7 +905,10 @@ impl<C: Default + Clone + Display + Hash + Eq> Substitution<C> {
         ArithmeticExpression::initialize_hashmap_for_expression(&mut coefficients);
         coefficients.insert(symbol, BigInt::from(-1 % field));
         let arith = ArithmeticExpression::Linear { coefficients };
-        ArithmeticExpression::transform_expression_to_constraint_form(arith, field).unwrap()
+        let mut constraint =
+            ArithmeticExpression::transform_expression_to_constraint_form(arith, field).unwrap();
+        constraint.origins = substitution.origins;
+        constraint
     }
 
     pub fn decompose(substitution: Substitution<C>) -> (C, ArithmeticExpression<C>) {
@@ -944,6 +952,14 @@ impl<C: Default + Clone + Display + Hash + Eq> Substitution<C> {
         &self.to
     }
 
+    pub fn origins(&self) -> &[usize] {
+        &self.origins
+    }
+
+    pub fn set_origins(&mut self, origins: Vec<usize>) {
+        self.origins = origins;
+    }
+
     pub fn take_cloned_signals(&self) -> HashSet<C> {
         let cq: C = ArithmeticExpression::constant_coefficient();
         let mut signals = HashSet::new();
@@ -990,7 +1006,7 @@ impl Substitution<usize> {
         debug_assert_ne!(self.from, constant);
         let from = self.from + offset;
         let to = apply_raw_offset(&self.to, offset);
-        Substitution { from, to }
+        Substitution { from, to, origins: self.origins.clone() }
     }
 }
 
@@ -1006,11 +1022,18 @@ where
     pub(crate) a: HashMap<C, BigInt>,
     pub(crate) b: HashMap<C, BigInt>,
     pub(crate) c: HashMap<C, BigInt>,
+    // ids of the statements the constraint comes from, its own first &
+    // then the ones of the constraints merged into it by the simplification
+    pub(crate) origins: Vec<usize>,
 }
 
 impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
-    fn new(a: HashMap<C, BigInt>, b: HashMap<C, BigInt>, c: HashMap<C, BigInt>) -> Constraint<C> {
-        Constraint { a, b, c }
+    pub fn new(
+        a: HashMap<C, BigInt>,
+        b: HashMap<C, BigInt>,
+        c: HashMap<C, BigInt>,
+    ) -> Constraint<C> {
+        Constraint { a, b, c, origins: Vec::new() }
     }
 
     pub fn empty() -> Constraint<C> {
@@ -1044,7 +1067,7 @@ impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
         let a = apply_raw_correspondence(&constraint.a, symbol_correspondence);
         let b = apply_raw_correspondence(&constraint.b, symbol_correspondence);
         let c = apply_raw_correspondence(&constraint.c, symbol_correspondence);
-        Constraint::new(a, b, c)
+        Constraint { a, b, c, origins: constraint.origins.clone() }
     }
 
     // Constraint simplifications
@@ -1061,7 +1084,7 @@ impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
         debug_assert!(Constraint::is_linear(&constraint));
         debug_assert!(constraint.c.contains_key(signal));
         let raw_expression = Constraint::clear_signal(constraint.c, &signal, field);
-        Substitution { from: signal.clone(), to: raw_expression }
+        Substitution { from: signal.clone(), to: raw_expression, origins: constraint.origins }
     }
 
     pub fn clear_signal_from_linear_not_normalized(
@@ -1072,7 +1095,7 @@ impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
         debug_assert!(Constraint::is_linear(&constraint));
         debug_assert!(constraint.c.contains_key(signal));
         let (coefficient, raw_expression) = Constraint::clear_signal_not_normalized(constraint.c, &signal, field);
-        (coefficient, Substitution {from: signal.clone(), to: raw_expression})
+        (coefficient, Substitution {from: signal.clone(), to: raw_expression, origins: constraint.origins})
     }
 
     pub fn take_cloned_signals(&self) -> HashSet<C> {
@@ -1140,6 +1163,13 @@ impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
         substitution: &Substitution<C>,
         field: &BigInt,
     ) {
+        let from = &substitution.from;
+        if constraint.a.contains_key(from)
+            || constraint.b.contains_key(from)
+            || constraint.c.contains_key(from)
+        {
+            merge_origins(&mut constraint.origins, &substitution.origins);
+        }
         raw_substitution(&mut constraint.a, substitution, field);
         raw_substitution(&mut constraint.b, substitution, field);
         raw_substitution(&mut constraint.c, substitution, field);
@@ -1177,6 +1207,18 @@ impl<C: Default + Clone + Display + Hash + Eq> Constraint<C> {
         &self.c
     }
 
+    pub fn origins(&self) -> &[usize] {
+        &self.origins
+    }
+
+    pub fn set_origins(&mut self, origins: Vec<usize>) {
+        self.origins = origins;
+    }
+
+    pub fn add_origins(&mut self, origins: &[usize]) {
+        merge_origins(&mut self.origins, origins);
+    }
+
     pub fn is_equality(&self, field: &BigInt) -> bool {
         signal_equals_signal(&self.a, &self.b, &self.c, field)
     }
@@ -1218,13 +1260,13 @@ impl Constraint<usize> {
         let a = apply_raw_offset(&self.a, offset);
         let b = apply_raw_offset(&self.b, offset);
         let c = apply_raw_offset(&self.c, offset);
-        Constraint::new(a, b, c)
+        Constraint { a, b, c, origins: self.origins.clone() }
     }
     pub fn apply_witness(&self, witness: &Vec<usize>) -> Constraint<usize> {
         let a = apply_vectored_correspondence(&self.a, witness);
         let b = apply_vectored_correspondence(&self.b, witness);
         let c = apply_vectored_correspondence(&self.c, witness);
-        Constraint::new(a, b, c)
+        Constraint { a, b, c, origins: self.origins.clone() }
     }
 }
 
@@ -1276,6 +1318,44 @@ fn apply_raw_offset(h: &HashMap<usize, BigInt>, offset: usize) -> HashMap<usize,
     new
 }
 
+// the first origin is the own one, the rest are kept sorted & unique
+// so that merging two lists is a single pass over both of them
+pub fn merge_origins(origins: &mut Vec<usize>, other: &[usize]) {
+    if origins.is_empty() {
+        origins.extend_from_slice(other);
+        return;
+    }
+    if other.is_empty() {
+        return;
+    }
+    let own = origins[0];
+    let mut incoming = other[1..].to_vec();
+    if let Err(pos) = incoming.binary_search(&other[0]) {
+        incoming.insert(pos, other[0]);
+    }
+    let current = &origins[1..];
+    let mut merged = Vec::with_capacity(current.len() + incoming.len() + 1);
+    merged.push(own);
+    let (mut i, mut j) = (0, 0);
+    while i < current.len() || j < incoming.len() {
+        let next = if j == incoming.len() || (i < current.len() && current[i] <= incoming[j]) {
+            current[i]
+        } else {
+            incoming[j]
+        };
+        while i < current.len() && current[i] == next {
+            i += 1;
+        }
+        while j < incoming.len() && incoming[j] == next {
+            j += 1;
+        }
+        if next != own {
+            merged.push(next);
+        }
+    }
+    *origins = merged;
+}
+
 fn raw_substitution<C>(
     change: &mut HashMap<C, BigInt>,
     substitution: &Substitution<C>,
@@ -1491,4 +1571,20 @@ mod test {
         assert_eq!(*y_c, expected_y_c);
         assert_eq!(*constant_c, expected_constant_c);
     }
+
+    #[test]
+    fn algebra_merge_origins() {
+        use crate::algebra::merge_origins;
+        let mut origins = Vec::new();
+        merge_origins(&mut origins, &[5, 2, 9]);
+        assert_eq!(origins, vec![5, 2, 9]);
+
+        // the own origin stays first, the rest are sorted & unique
+        merge_origins(&mut origins, &[7, 1, 5, 9]);
+        assert_eq!(origins, vec![5, 1, 2, 7, 9]);
+        merge_origins(&mut origins, &[2]);
+        assert_eq!(origins, vec![5, 1, 2, 7, 9]);
+        merge_origins(&mut origins, &[]);
+        assert_eq!(origins, vec![5, 1, 2, 7, 9]);
+    }
 }
@@ -15,7 +15,7 @@ pub fn code_constraint(constraint: C, tracker: &mut FieldTracker) -> CompressedC
     let a = code_expression(constraint.a, tracker);
     let b = code_expression(constraint.b, tracker);
     let c = code_expression(constraint.c, tracker);
-    (a, b, c)
+    (a, b, c, constraint.origins)
 }
 
 pub fn decode_expr(c_expr: &CompressedExpr, tracker: &FieldTracker) -> HashMap<S, BigInt> {
@@ -29,6 +29,11 @@ pub fn decode_expr(c_expr: &CompressedExpr, tracker: &FieldTracker) -> HashMap<S
 }
 
 pub fn decode_constraint(constraint: &CompressedConstraint, tracker: &FieldTracker) -> C {
-    let (a, b, c) = constraint;
-    C { a: decode_expr(a, tracker), b: decode_expr(b, tracker), c: decode_expr(c, tracker) }
+    let (a, b, c, origins) = constraint;
+    C {
+        a: decode_expr(a, tracker),
+        b: decode_expr(b, tracker),
+        c: decode_expr(c, tracker),
+        origins: origins.clone(),
+    }
 }
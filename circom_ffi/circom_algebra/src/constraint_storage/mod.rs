@@ -11,7 +11,7 @@ type S = usize;
 type C = Constraint<usize>;
 
 type CompressedExpr = Vec<(CID, S)>;
-type CompressedConstraint = (CompressedExpr, CompressedExpr, CompressedExpr); // A, B, C
+type CompressedConstraint = (CompressedExpr, CompressedExpr, CompressedExpr, Vec<usize>); // A, B, C, origins
 
 pub type ConstraintID = usize;
 pub struct ConstraintStorage {
@@ -68,6 +68,47 @@ pub fn prefix_sub(elem: &BigInt, field: &BigInt) -> BigInt {
     mul(elem, &minus_one, field)
 }
 
+// Miller-Rabin test with the first 20 primes as bases.
+// Deterministic for every n < 3.3 * 10^24, a strong probable prime test otherwise
+pub fn is_probable_prime(n: &BigInt) -> bool {
+    let bases = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71];
+    let one = BigInt::from(1);
+    let two = BigInt::from(2);
+    if n < &two {
+        return false;
+    }
+    for b in bases.iter() {
+        let b = BigInt::from(*b);
+        if n == &b {
+            return true;
+        }
+        if modulus(n, &b) == BigInt::from(0) {
+            return false;
+        }
+    }
+    let n_minus_one = n - &one;
+    let mut d = n_minus_one.clone();
+    let mut s = 0;
+    while modulus(&d, &two) == BigInt::from(0) {
+        d /= &two;
+        s += 1;
+    }
+    'witness: for b in bases.iter() {
+        let mut x = BigInt::from(*b).modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
 pub fn multi_inv(values: &Vec<BigInt>, field: &BigInt) -> Vec<BigInt>{
     let one : BigInt = BigInt::from(1);
     let mut partials : Vec<BigInt> = Vec::new();
@@ -247,6 +288,23 @@ mod tests {
         }
     }
     #[test]
+    fn probable_prime_check() {
+        let field = BigInt::parse_bytes(FIELD.as_bytes(), 10)
+            .expect("generating the big int was not possible");
+        let bn128 = BigInt::parse_bytes(
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617"
+                .as_bytes(),
+            10,
+        )
+        .expect("generating the big int was not possible");
+        assert!(is_probable_prime(&field));
+        assert!(is_probable_prime(&BigInt::from(2)));
+        assert!(is_probable_prime(&bn128));
+        assert!(!is_probable_prime(&BigInt::from(1)));
+        assert!(!is_probable_prime(&BigInt::from(561)));
+        assert!(!is_probable_prime(&(&bn128 * &field)));
+    }
+    #[test]
     fn complement_of_complement_is_the_original_test() {
         let field = BigInt::parse_bytes(FIELD.as_bytes(), 10)
             .expect("generating the big int was not possible");
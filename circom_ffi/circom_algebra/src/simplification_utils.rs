@@ -144,11 +144,12 @@ fn substitution_process_3(
     signals: &mut SignalDefinition,
     constraints: &mut LinkedList<C>,
     substitutions: &mut SHNotNormalized,
+    track_origins: bool,
     field: &BigInt,
 ) {
     let mut lconst = LinkedList::new();
     while let Option::Some(actual_constraint) = LinkedList::pop_back(constraints) {
-        treat_constraint_3(signals, substitutions, &mut lconst, actual_constraint, field);
+        treat_constraint_3(signals, substitutions, &mut lconst, actual_constraint, track_origins, field);
     }
     *constraints = lconst;
 }
@@ -158,6 +159,7 @@ fn substitution_process_4(
     constraints: &mut LinkedList<C>,
     substitutions: &mut SHNotNormalized,
     num_signals: usize,
+    track_origins: bool,
     field: &BigInt,
 ) {
     let mut lconst = LinkedList::new();
@@ -171,14 +173,14 @@ fn substitution_process_4(
         if !vec_constraints[index].is_empty(){
             let actual_constraint = replace(&mut vec_constraints[index], C::empty());
             info_ocurrences.remove_constraint(&actual_constraint, signals);  
-            treat_unique_constraint_4(signals, substitutions, &mut lconst, actual_constraint, &mut info_ocurrences, signal, field);
+            treat_unique_constraint_4(signals, substitutions, actual_constraint, &mut info_ocurrences, signal, track_origins, field);
         }
     }
 
     while !vec_constraints.is_empty(){
         if let Option::Some(actual_constraint) = Vec::pop(&mut vec_constraints) {
             info_ocurrences.remove_constraint(&actual_constraint, signals);    
-            treat_constraint_4(signals, substitutions, &mut lconst, actual_constraint, &mut info_ocurrences, field);
+            treat_constraint_4(signals, substitutions, &mut lconst, actual_constraint, &mut info_ocurrences, track_origins, field);
         }
     }
     *constraints = lconst;
@@ -210,10 +212,12 @@ fn treat_constraint_1(
             break;
         }
         let in_conflict = in_conflict.unwrap();
+        let origins = merged_origins(&substitution, &in_conflict);
         let right = S::decompose(in_conflict).1;
         let left = S::decompose(substitution).1;
         let merge = A::sub(&left, &right, field);
         work = A::transform_expression_to_constraint_form(merge, field).unwrap();
+        work.set_origins(origins);
         C::remove_zero_value_coefficients(&mut work);
     }
 }
@@ -244,6 +248,7 @@ fn treat_constraint_2(
             break;
         }
         let (in_conflict_coef, in_conflict_subs) = in_conflict.unwrap();
+        let origins = merged_origins(&substitution, &in_conflict_subs);
         let right = S::decompose(in_conflict_subs).1;
         let left = S::decompose(substitution).1;
         let exp_coef_right = A::Number {value : in_conflict_coef};
@@ -252,6 +257,7 @@ fn treat_constraint_2(
         let new_right  = A::mul(&exp_coef_left,&right,field);
         let merge = A::sub(&new_left, &new_right, field);
         work = A::transform_expression_to_constraint_form(merge, field).unwrap();
+        work.set_origins(origins);
         C::remove_zero_value_coefficients(&mut work);
     }
 }
@@ -261,6 +267,7 @@ fn treat_constraint_3(
     substitutions: &mut SHNotNormalized,
     lconst: &mut LinkedList<C>,
     mut work: C,
+    track_origins: bool,
     field: &BigInt,
 ) {
     loop {
@@ -277,10 +284,11 @@ fn treat_constraint_3(
         let (coefficient, substitution) = C::clear_signal_from_linear_not_normalized(work, &out, field);
         let in_conflict = substitutions.get(&substitution.from()).cloned();
         if in_conflict.is_none() {
-            substitutions.insert(*substitution.from(), (coefficient, substitution));
+            substitutions.insert(*substitution.from(), (coefficient, held(substitution, track_origins)));
             break;
         }
         let (in_conflict_coef, in_conflict_subs) = in_conflict.unwrap();
+        let origins = merged_origins(&substitution, &in_conflict_subs);
         let right = S::decompose(in_conflict_subs).1;
         let left = S::decompose(substitution).1;
         let exp_coef_right = A::Number {value : in_conflict_coef};
@@ -289,6 +297,7 @@ fn treat_constraint_3(
         let new_right  = A::mul(&exp_coef_left,&right,field);
         let merge = A::sub(&new_left, &new_right, field);
         work = A::transform_expression_to_constraint_form(merge, field).unwrap();
+        work.set_origins(origins);
         C::remove_zero_value_coefficients(&mut work);
     }
 }
@@ -296,15 +305,15 @@ fn treat_constraint_3(
 fn treat_unique_constraint_4(
     signals: &mut SignalDefinition4,
     substitutions: &mut SHNotNormalized,
-    _lconst: &mut LinkedList<C>,
     work: C,
     info_ocurrences: &mut SignalsInformation,
     signal: usize,
+    track_origins: bool,
     field: &BigInt,
 ) {
 
     let (coefficient, substitution) = C::clear_signal_from_linear_not_normalized(work, &signal, field);
-    substitutions.insert(*substitution.from(), (coefficient, substitution));
+    substitutions.insert(*substitution.from(), (coefficient, held(substitution, track_origins)));
     info_ocurrences.remove_signal(signal);
     signals.delete(signal);
 }
@@ -315,6 +324,7 @@ fn treat_constraint_4(
     lconst: &mut LinkedList<C>,
     mut work: C,
     info_ocurrences: &mut SignalsInformation,
+    track_origins: bool,
     field: &BigInt,
 ) {
     loop {
@@ -332,10 +342,11 @@ fn treat_constraint_4(
         if in_conflict.is_none() {
             signals.delete(out);
             info_ocurrences.remove_signal(out);
-            substitutions.insert(*substitution.from(), (coefficient, substitution));
+            substitutions.insert(*substitution.from(), (coefficient, held(substitution, track_origins)));
             break;
         }
         let (in_conflict_coef, in_conflict_subs) = in_conflict.unwrap();
+        let origins = merged_origins(&substitution, &in_conflict_subs);
         let right = S::decompose(in_conflict_subs).1;
         let left = S::decompose(substitution).1;
         let exp_coef_right = A::Number {value : in_conflict_coef};
@@ -344,10 +355,27 @@ fn treat_constraint_4(
         let new_right  = A::mul(&exp_coef_left,&right,field);
         let merge = A::sub(&new_left, &new_right, field);
         work = A::transform_expression_to_constraint_form(merge, field).unwrap();
+        work.set_origins(origins);
         C::remove_zero_value_coefficients(&mut work);
     }
 }
 
+// the substitutions only carry the origins of their constraints when they are
+// tracked, the constraint left by a conflict keeps its own one either way
+fn held(mut substitution: S, track_origins: bool) -> S {
+    if !track_origins {
+        substitution.set_origins(Vec::new());
+    }
+    substitution
+}
+
+// origins of the constraint left when two substitutions clear the same signal
+fn merged_origins(substitution: &S, in_conflict: &S) -> Vec<usize> {
+    let mut origins = substitution.origins().to_vec();
+    crate::algebra::merge_origins(&mut origins, in_conflict.origins());
+    origins
+}
+
 #[allow(dead_code)]
 fn take_signal_1(signals: &SignalDefinition, constraint: &C) -> Option<usize> {
     let mut ret = Option::None;
@@ -429,7 +457,8 @@ fn normalize_substitutions(substitutions: SHNotNormalized, field: &BigInt) -> SH
             &A::Number {value : inv.clone()}, 
             field
         );
-        let new_sub = S::new(signal.clone(), mult_by_inverse).unwrap(); 
+        let mut new_sub = S::new(signal.clone(), mult_by_inverse).unwrap();
+        new_sub.set_origins(sub.origins().to_vec());
         tree.insert(signal, new_sub);
         i = i + 1;
     }
@@ -517,6 +546,29 @@ pub fn fast_encoded_substitution_substitution(s: &mut S, enc: &HashMap<usize, A>
     S::rmv_zero_coefficients(s)
 }
 
+// the origins of the substitutions that are going to be applied to c,
+// none when the origins are not tracked
+pub fn merge_encoded_origins(c: &mut C, origins: &HashMap<usize, Vec<usize>>) {
+    if origins.is_empty() {
+        return;
+    }
+    for signal in C::take_cloned_signals(c) {
+        if let Some(merged) = origins.get(&signal) {
+            c.add_origins(merged);
+        }
+    }
+}
+
+pub fn build_encoded_origins(fast_sub: &LinkedList<S>) -> HashMap<usize, Vec<usize>> {
+    let mut encoded = HashMap::new();
+    for sub in fast_sub {
+        if !sub.origins().is_empty() {
+            encoded.insert(*sub.from(), sub.origins().to_vec());
+        }
+    }
+    encoded
+}
+
 pub fn build_encoded_fast_substitutions(fast_sub: LinkedList<S>) -> HashMap<usize, A> {
     let mut encoded = HashMap::with_capacity(LinkedList::len(&fast_sub));
     for sub in fast_sub {
@@ -532,6 +584,8 @@ pub struct Config<T> {
     pub forbidden: T,
     pub num_signals: usize,
     pub use_old_heuristics: bool,
+    // the substitutions keep the origins of the constraints they come from
+    pub track_origins: bool,
 }
 
 pub struct Simplified {
@@ -560,13 +614,13 @@ where
 
     if apply_less_ocurrences{
         let mut signals = SignalDefinition4 { forbidden: config.forbidden.as_ref(), deleted_symbols: HashSet::new(),  order_signals: LinkedList::new() };
-        substitution_process_4(&mut signals, &mut constraints, &mut holder, config.num_signals, &field);
+        substitution_process_4(&mut signals, &mut constraints, &mut holder, config.num_signals, config.track_origins, &field);
         normalized_holder = normalize_substitutions(holder, &field);
         non_overlapping = create_nonoverlapping_substitutions_4(normalized_holder, &signals, &field);
     }
     else{
         let mut signals = SignalDefinition { forbidden: config.forbidden.as_ref(), deleted_symbols: HashSet::new() };
-        substitution_process_3(&mut signals, &mut constraints, &mut holder, &field);
+        substitution_process_3(&mut signals, &mut constraints, &mut holder, config.track_origins, &field);
         normalized_holder = normalize_substitutions(holder, &field);
         non_overlapping = create_nonoverlapping_substitutions(normalized_holder, &field);
     }
@@ -589,7 +643,7 @@ where
     let mut signals_4 = SignalDefinition4 { forbidden: config.forbidden.as_ref(), deleted_symbols: HashSet::new(),  order_signals: LinkedList::new() };
     let mut constraints_4 = config.constraints.clone();
     let mut holder_4 = SHNotNormalized::new();
-    substitution_process_4(&mut signals_4, &mut constraints_4, &mut holder_4, config.num_signals, &field);
+    substitution_process_4(&mut signals_4, &mut constraints_4, &mut holder_4, config.num_signals, config.track_origins, &field);
     let normalized_holder_4 = normalize_substitutions(holder_4, &field);
     let non_overlapping_4 = create_nonoverlapping_substitutions_4(normalized_holder_4, &signals_4, &field);
     let mut substitutions_4 = LinkedList::new();
@@ -604,7 +658,7 @@ where
         SignalDefinition { forbidden: config.forbidden.as_ref(), deleted_symbols: HashSet::new() };
     let mut constraints_3 = config.constraints.clone();
     let mut holder_3 = SHNotNormalized::new();
-    substitution_process_3(&mut signals_3, &mut constraints_3, &mut holder_3, &field);
+    substitution_process_3(&mut signals_3, &mut constraints_3, &mut holder_3, config.track_origins, &field);
     let normalized_holder_3 = normalize_substitutions(holder_3, &field);
     let non_overlapping_3 = create_nonoverlapping_substitutions(normalized_holder_3, &field);
     let mut substitutions_3 = LinkedList::new();
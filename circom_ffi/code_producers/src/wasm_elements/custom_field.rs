@@ -0,0 +1,189 @@
+// Fr arithmetic for primes that are not shipped as a precompiled ffwasm module.
+//
+// The ffwasm modules only depend on the prime through a few constants:
+// the montgomery factor np32, the 2-adicity used by sqrt and the mask of the
+// most significant limb. Everything else lives in the data section.
+// A custom prime reuses the module with the same number of 64 bit limbs
+// (goldilocks for 1 limb, bn128 for 4 limbs) and patches those constants.
+
+use super::*;
+use super::wasm_code_generator::wasm_hexa;
+use num_bigint_dig::{BigInt, ModInverse};
+
+const FR_CODE_1: &str = include_str!("goldilocks/fr-code.wat");
+const FR_CODE_4: &str = include_str!("bn128/fr-code.wat");
+const FR_TYPES: &str = include_str!("bn128/fr-types.wat");
+
+// offset of the bit length, offset of the integer one,
+// offset of the first field constant and offset of the last copy of (p-1)/2
+const LAYOUT_1: (usize, usize, usize, usize) = (40, 56, 176, 408);
+const LAYOUT_4: (usize, usize, usize, usize) = (88, 128, 608, 1920);
+
+// the sizes the modules can be patched for: the field elements below 2^31 are
+// kept in a short form that is never reduced, so p needs 32 bits at least,
+// and the most significant limb of p can't be empty
+pub fn is_supported_prime(p: &BigInt) -> bool {
+    (32..=64).contains(&p.bits()) || (193..=256).contains(&p.bits())
+}
+
+// custom primes are identified by their decimal representation
+pub fn parse_custom_prime(prime: &str) -> BigInt {
+    BigInt::parse_bytes(prime.as_bytes(), 10).expect("custom prime should be in decimal")
+}
+
+// number of 64 bit limbs of a field element, for a supported prime
+pub fn n64(p: &BigInt) -> usize {
+    if p.bits() <= 64 {
+        1
+    } else {
+        4
+    }
+}
+
+pub fn fr_memory_size(p: &BigInt) -> usize {
+    if n64(p) == 1 {
+        412
+    } else {
+        1948
+    }
+}
+
+pub fn size_32_bit(p: &BigInt) -> usize {
+    2 * n64(p)
+}
+
+pub fn fr_types() -> Vec<WasmInstruction> {
+    FR_TYPES.lines().map(|line| line.to_string()).collect()
+}
+
+pub fn fr_code(p: &BigInt) -> Vec<WasmInstruction> {
+    let n64 = n64(p);
+    let template = if n64 == 1 { FR_CODE_1 } else { FR_CODE_4 };
+
+    // -p^-1 mod 2^32
+    let r32 = BigInt::from(1) << 32;
+    let inv = p.mod_inverse(&r32).expect("custom prime should be odd");
+    let np32 = (&r32 - inv) % &r32;
+    let (s, _) = two_adicity(p);
+    let mask = (BigInt::from(1) << (p.bits() - 64 * (n64 - 1))) - BigInt::from(1);
+
+    let lines: Vec<&str> = template.lines().collect();
+    let mut instructions = Vec::with_capacity(lines.len());
+    let mut func = "";
+    for (i, line) in lines.iter().enumerate() {
+        let instr = line.trim();
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let next = lines.get(i + 1).map(|l| l.trim()).unwrap_or("");
+        if instr.starts_with("(func ") {
+            func = instr.split_whitespace().nth(1).unwrap_or("");
+        }
+        if instr.starts_with("i64.const") && next == "set_local $np32" {
+            instructions.push(format!("{}i64.const {}", indent, np32));
+        } else if func == "$Fr_F1m_sqrt" && instr.starts_with("i32.const") && next == "set_local $m"
+        {
+            instructions.push(format!("{}i32.const {}", indent, s));
+        } else if func == "$Fr_adjustBinResult" && instr.starts_with("i64.const") && next == "i64.and"
+        {
+            instructions.push(format!("{}i64.const {}", indent, mask));
+        } else {
+            instructions.push(line.to_string());
+        }
+    }
+    instructions
+}
+
+pub fn fr_data(p: &BigInt) -> Vec<WasmInstruction> {
+    let n64 = n64(p);
+    let n8 = 8 * n64;
+    let (bits_offset, one_offset, start, e_offset) = if n64 == 1 { LAYOUT_1 } else { LAYOUT_4 };
+
+    let r = (BigInt::from(1) << (64 * n64)) % p;
+    let r2 = (&r * &r) % p;
+    let r3 = (&r2 * &r) % p;
+    let e = (p - BigInt::from(1)) >> 1;
+    let (_, t) = two_adicity(p);
+    let nr = non_residue(p);
+    let c = nr.modpow(&t, p);
+
+    let constants = vec![
+        p.clone(),
+        r.clone(),
+        r2,
+        r3,
+        r.clone(),
+        BigInt::from(0),
+        e.clone(),
+        &e + BigInt::from(1),
+        (&nr * &r) % p,
+        t.clone(),
+        (&c * &r) % p,
+        (&t + BigInt::from(1)) >> 1,
+    ];
+
+    let mut instructions = vec![];
+    instructions.push(data(bits_offset, 8, &BigInt::from(p.bits())));
+    instructions.push(data(one_offset, n8, &BigInt::from(1)));
+    for (i, constant) in constants.iter().enumerate() {
+        instructions.push(data(start + i * n8, n8, constant));
+    }
+    instructions.push(data(e_offset, n8, &e));
+    instructions
+}
+
+fn data(offset: usize, nbytes: usize, value: &BigInt) -> WasmInstruction {
+    format!("(data (i32.const {}) \"{}\")", offset, wasm_hexa(nbytes, value))
+}
+
+// p - 1 = t * 2^s with t odd
+fn two_adicity(p: &BigInt) -> (usize, BigInt) {
+    let two = BigInt::from(2);
+    let mut t = p - BigInt::from(1);
+    let mut s = 0;
+    while &t % &two == BigInt::from(0) {
+        t >>= 1;
+        s += 1;
+    }
+    (s, t)
+}
+
+// smallest quadratic non residue of p
+fn non_residue(p: &BigInt) -> BigInt {
+    let minus_one = p - BigInt::from(1);
+    let e = &minus_one >> 1;
+    let mut n = BigInt::from(2);
+    while n.modpow(&e, p) != minus_one {
+        n += 1;
+    }
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(file: &str) -> Vec<String> {
+        file.lines().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn custom_fr_code_matches_precompiled_modules() {
+        let bn128 = parse_custom_prime(
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617",
+        );
+        assert_eq!(fr_code(&bn128), lines(FR_CODE_4));
+
+        let goldilocks = parse_custom_prime("18446744069414584321");
+        assert_eq!(fr_code(&goldilocks), lines(FR_CODE_1));
+    }
+
+    #[test]
+    fn custom_fr_data_layout() {
+        let p = BigInt::from(2147483659u64);
+        let data = fr_data(&p);
+        assert_eq!(data.len(), 15);
+        assert_eq!(data[0], "(data (i32.const 40) \"\\20\\00\\00\\00\\00\\00\\00\\00\")");
+        assert_eq!(data[2], "(data (i32.const 176) \"\\0b\\00\\00\\80\\00\\00\\00\\00\")");
+        assert_eq!(fr_memory_size(&p), 412);
+        assert_eq!(size_32_bit(&p), 2);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod wasm_code_generator;
+pub mod custom_field;
 
 use crate::components::*;
 
@@ -25,6 +26,9 @@ pub struct WASMProducer {
     pub message_list: MessageList,
     pub field_tracking: Vec<String>,
     pub wat_flag: bool,
+    // module only run by Veritas, which tells the host the component
+    // it enters & leaves & asks it whether to stop at a broken ===
+    pub evaluation_module: bool,
     pub major_version: usize,
     pub minor_version: usize,
     pub patch_version: usize,
@@ -107,6 +111,7 @@ impl Default for WASMProducer {
             template_instance_list: [].to_vec(),
             field_tracking: [].to_vec(),
             wat_flag: true,
+            evaluation_module: false,
             major_version: 0,
             minor_version: 0,
             patch_version: 0,
@@ -492,6 +497,9 @@ impl WASMProducer {
     pub fn needs_comments(&self) -> bool{
         self.wat_flag
     }
+    pub fn is_evaluation_module(&self) -> bool {
+        self.evaluation_module
+    }
 
     pub fn get_string_table(&self) -> &Vec<String> {
         &self.string_table
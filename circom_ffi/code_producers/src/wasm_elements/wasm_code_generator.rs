@@ -637,6 +637,26 @@ pub fn generate_imports_list() -> Vec<WasmInstruction> {
     imports
 }
 
+// imports of the evaluation module, the host keeps track
+// of the component instance that is running & tells whether
+// to carry on past a === the witness breaks
+pub fn generate_evaluation_imports_list() -> Vec<WasmInstruction> {
+    let mut imports = vec![];
+    imports.push(
+        "(import \"veritas\" \"enterComponent\" (func $enterComponent (type $_t_i32)))"
+            .to_string(),
+    );
+    imports.push(
+        "(import \"veritas\" \"exitComponent\" (func $exitComponent (type $_t_void)))"
+            .to_string(),
+    );
+    imports.push(
+        "(import \"veritas\" \"skipConstraintAssert\" (func $skipConstraintAssert (type $_t_ri32)))"
+            .to_string(),
+    );
+    imports
+}
+
 pub fn generate_memory_def_list(producer: &WASMProducer) -> Vec<WasmInstruction> {
     let mut wmemory = vec![];
     wmemory.push(format!("(memory {})", get_initial_size_of_memory(&producer)));
@@ -1722,7 +1742,7 @@ pub fn fr_types(prime: &String) -> Vec<WasmInstruction> {
         "pallas" => include_str!("pallas/fr-types.wat"),
         "vesta" => include_str!("vesta/fr-types.wat"),
         "secq256r1" => include_str!("secq256r1/fr-types.wat"),
-        _ => unreachable!(),
+        _ => return custom_field::fr_types(),
     };    
     for line in file.lines() {
         instructions.push(line.to_string());
@@ -1740,7 +1760,7 @@ pub fn fr_data(prime: &String) -> Vec<WasmInstruction> {
         "pallas" => include_str!("pallas/fr-data.wat"),
         "vesta" => include_str!("vesta/fr-data.wat"),
         "secq256r1" => include_str!("secq256r1/fr-data.wat"),
-        _ => unreachable!(),
+        _ => return custom_field::fr_data(&custom_field::parse_custom_prime(prime)),
     };    
     for line in file.lines() {
         instructions.push(line.to_string());
@@ -1757,7 +1777,7 @@ pub fn fr_code(prime: &String) -> Vec<WasmInstruction> {
         "pallas" => include_str!("pallas/fr-code.wat"),
         "vesta" => include_str!("vesta/fr-code.wat"),
         "secq256r1" => include_str!("secq256r1/fr-code.wat"),
-        _ => unreachable!(),
+        _ => return custom_field::fr_code(&custom_field::parse_custom_prime(prime)),
     };    
     for line in file.lines() {
         instructions.push(line.to_string());
@@ -213,14 +213,19 @@ fn initialize_wasm_producer(
         "pallas" => 1948,
         "vesta" => 1948,
         "secq256r1" => 1948,
-        _ => unreachable!(),
+        _ => custom_field::fr_memory_size(&prime),
     };
     //producer.fr_memory_size = 412 if goldilocks and 1948 for bn128 and bls12381
     // for each created component we store three u32, for each son we store a u32 in its father
     producer.size_of_component_tree =
         stats.all_created_components * 3 + stats.all_needed_subcomponents_indexes;
     producer.total_number_of_signals = stats.all_signals + 1;
-    producer.size_32_bit = prime.bits() / 32 + if prime.bits() % 32 != 0 { 1 } else { 0 };
+    producer.size_32_bit = if UsefulConstants::is_named_prime(&vcp.prime) {
+        prime.bits() / 32 + if prime.bits() % 32 != 0 { 1 } else { 0 }
+    } else {
+        // custom primes use the limbs of the module they are patched into
+        custom_field::size_32_bit(&prime)
+    };
     producer.size_32_shift = 0;
     let mut pow = 1;
     while pow < producer.size_32_bit {
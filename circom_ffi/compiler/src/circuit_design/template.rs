@@ -132,6 +132,16 @@ impl WriteWasm for TemplateCodeInfo {
         instructions.push(add32());
         instructions.push(load32(None));
         instructions.push(set_local(producer.get_signal_start_tag()));
+        if producer.is_evaluation_module() {
+            //tell the host the index of the first signal of the component
+            instructions.push(get_local(producer.get_signal_start_tag()));
+            instructions.push(set_constant(&producer.get_signal_memory_start().to_string()));
+            instructions.push(sub32());
+            let signal_size = producer.get_size_32_bits_in_memory() * 4;
+            instructions.push(set_constant(&signal_size.to_string()));
+            instructions.push(div32_u());
+            instructions.push(call("$enterComponent"));
+        }
         //generate code
 
         for t in &self.body {
@@ -139,6 +149,9 @@ impl WriteWasm for TemplateCodeInfo {
             instructions.append(&mut instructions_body);
         }
 
+        if producer.is_evaluation_module() {
+            instructions.push(call("$exitComponent"));
+        }
         //free stack
         let mut free_stack_code = free_stack(producer);
         instructions.append(&mut free_stack_code);
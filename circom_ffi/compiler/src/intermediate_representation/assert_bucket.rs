@@ -8,6 +8,8 @@ pub struct AssertBucket {
     pub line: usize,
     pub message_id: usize,
     pub evaluate: InstructionPointer,
+    // checks a === constraint
+    pub constraint: bool,
 }
 
 impl IntoInstruction for AssertBucket {
@@ -44,6 +46,10 @@ impl WriteWasm for AssertBucket {
     fn produce_wasm(&self, producer: &WASMProducer) -> Vec<String> {
         use code_producers::wasm_elements::wasm_code_generator::*;
         let mut instructions = vec![];
+        // at a === the witness breaks, the evaluation module asks the host
+        // whether to carry on & leave it to the evaluation of the constraints.
+        // It stops there like the module circom writes unless told otherwise
+        let skippable = self.constraint && producer.is_evaluation_module();
         if producer.needs_comments() {
             instructions.push(";; assert bucket".to_string());
 	}
@@ -52,6 +58,11 @@ impl WriteWasm for AssertBucket {
         instructions.push(call("$Fr_isTrue"));
         instructions.push(eqz32());
         instructions.push(add_if());
+        if skippable {
+            instructions.push(call("$skipConstraintAssert"));
+            instructions.push(eqz32());
+            instructions.push(add_if());
+        }
         instructions.push(set_constant(&self.message_id.to_string()));
         instructions.push(set_constant(&self.line.to_string()));
         instructions.push(call("$buildBufferMessage"));
@@ -59,6 +70,9 @@ impl WriteWasm for AssertBucket {
         instructions.push(set_constant(&exception_code_assert_fail().to_string()));
         instructions.push(add_return());
         instructions.push(add_end());
+        if skippable {
+            instructions.push(add_end());
+        }
         if producer.needs_comments() {
             instructions.push(";; end of assert bucket".to_string());
 	}
@@ -688,9 +688,13 @@ fn translate_constraint_equality(stmt: Statement, state: &mut State, context: &C
             stack,
         }
         .allocate();
-        let assert_instruction =
-            AssertBucket { line: starts_at, message_id: state.message_id, evaluate: equality }
-                .allocate();
+        let assert_instruction = AssertBucket {
+            line: starts_at,
+            message_id: state.message_id,
+            evaluate: equality,
+            constraint: true,
+        }
+        .allocate();
         state.code.push(assert_instruction);
     } else {
         unimplemented!()
@@ -702,7 +706,9 @@ fn translate_assert(stmt: Statement, state: &mut State, context: &Context) {
     if let Assert { meta, arg, .. } = stmt {
         let line = context.files.get_line(meta.start, meta.get_file_id()).unwrap();
         let code = translate_expression(arg, state, context);
-        let assert = AssertBucket { line, message_id: state.message_id, evaluate: code }.allocate();
+        let assert =
+            AssertBucket { line, message_id: state.message_id, evaluate: code, constraint: false }
+                .allocate();
         state.code.push(assert);
     }
 }
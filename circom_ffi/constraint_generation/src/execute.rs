@@ -26,6 +26,7 @@ use super::execution_data::type_definitions::{AccessingInformationBus, Accessing
 
 use super::{ast::*, ArithmeticError, FileID, ProgramArchive, Report, ReportCode, ReportCollection};
 use circom_algebra::num_bigint::BigInt;
+use constraint_list::ConstraintSource;
 use std::collections::{HashMap, BTreeMap};
 use crate::FlagsExecution;
 type AExpr = ArithmeticExpressionGen<String>;
@@ -439,7 +440,11 @@ fn execute_statement(
                                 let expr = AExpr::sub(&symbol, &value_right, &p);
                                 let ctr = AExpr::transform_expression_to_constraint_form(expr, &p)
                                     .unwrap();
-                                node.add_constraint(ctr);
+                                let source = ConstraintSource {
+                                    file_id: meta.get_file_id(),
+                                    location: meta.file_location(),
+                                };
+                                node.add_constraint(ctr, source);
                             }
                         } else if let AssignOp::AssignSignal = op {
                             // needs fix, check case arrays
@@ -633,7 +638,11 @@ fn execute_statement(
                 )
                 .unwrap();
                 if let Option::Some(node) = actual_node {
-                    node.add_constraint(constraint_expression);
+                    let source = ConstraintSource {
+                        file_id: meta.get_file_id(),
+                        location: meta.file_location(),
+                    };
+                    node.add_constraint(constraint_expression, source);
                 }
             }
             Option::None
@@ -3,6 +3,7 @@ use super::type_definitions::*;
 use super::ExecutedBus;
 use circom_algebra::algebra::ArithmeticExpression;
 use compiler::hir::very_concrete_program::*;
+use constraint_list::ConstraintSource;
 use dag::DAG;
 use num_bigint::BigInt;
 use program_structure::ast::{SignalType, Statement};
@@ -71,6 +72,8 @@ pub struct ExecutedTemplate {
     pub intermediates: WireCollector,
     pub ordered_signals: WireCollector,
     pub constraints: Vec<Constraint>,
+    // statement of each of the constraints
+    pub constraint_sources: Vec<ConstraintSource>,
     pub components: ComponentCollector,
     pub number_of_components: usize,
     pub public_inputs: HashSet<String>,
@@ -113,6 +116,7 @@ impl ExecutedTemplate {
             intermediates: WireCollector::new(),
             ordered_signals: WireCollector::new(),
             constraints: Vec::new(),
+            constraint_sources: Vec::new(),
             components: ComponentCollector::new(),
             number_of_components: 0,
             connexions: Vec::new(),
@@ -185,8 +189,9 @@ impl ExecutedTemplate {
         self.number_of_components += dimensions.iter().fold(1, |p, c| p * (*c));
     }
 
-    pub fn add_constraint(&mut self, constraint: Constraint) {
+    pub fn add_constraint(&mut self, constraint: Constraint, source: ConstraintSource) {
         self.constraints.push(constraint);
+        self.constraint_sources.push(source);
     }
 
     pub fn add_underscored_signal(&mut self, signal: &str) {
@@ -322,10 +327,10 @@ impl ExecutedTemplate {
     }
     fn build_constraints(&self, dag: &mut DAG) {
         
-        for c in &self.constraints {
+        for (c, source) in self.constraints.iter().zip(&self.constraint_sources) {
             let correspondence = dag.get_main().unwrap().correspondence();
             let cc = Constraint::apply_correspondence(c, correspondence);
-            dag.add_constraint(cc);
+            dag.add_constraint(cc, source.clone());
         }
         for s in &self.underscored_signals{
             let correspondence = dag.get_main().unwrap().correspondence();
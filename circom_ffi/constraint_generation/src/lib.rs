@@ -116,12 +116,14 @@ fn sync_dag_and_vcp(vcp: &mut VCP, dag: &mut DAG) {
 fn simplification_process(vcp: &mut VCP, dag: DAG, config: &BuildConfig) -> ConstraintList {
     use dag::SimplificationFlags;
     let flags = SimplificationFlags {
+        flag_f: config.flag_f,
         flag_s: config.flag_s,
         parallel_flag: config.flag_p,
         port_substitution: config.flag_json_sub,
         json_substitutions: config.json_substitutions.clone(),
         no_rounds: config.no_rounds,
         flag_old_heuristics: config.flag_old_heuristics,
+        track_origins: false,
         prime: config.prime.clone(),
     };
     let list = DAG::map_to_list(dag, flags);
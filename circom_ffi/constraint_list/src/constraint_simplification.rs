@@ -126,6 +126,7 @@ fn rebuild_witness(
 fn eq_cluster_simplification(
     mut cluster: Cluster,
     forbidden: &HashSet<usize>,
+    track_origins: bool,
     field: &BigInt,
 ) -> (LinkedList<S>, LinkedList<C>) {
     if Cluster::size(&cluster) == 1 {
@@ -157,7 +158,20 @@ fn eq_cluster_simplification(
         let mut subs = LinkedList::new();
         let (mut remains, mut min_remains) = (BTreeSet::new(), None);
         let (mut remove, mut min_remove) = (HashSet::new(), None);
+        // the constraints left come from the first constraint of the cluster,
+        // & from the rest of them when the origins are tracked
+        let (mut own, mut merged) = (None, BTreeSet::new());
         for c in cluster.constraints {
+            if let Some((first, rest)) = c.origins().split_first() {
+                if own.is_none() {
+                    own = Some(*first);
+                } else if track_origins {
+                    merged.insert(*first);
+                }
+                if track_origins {
+                    merged.extend(rest.iter().copied());
+                }
+            }
             for signal in C::take_cloned_signals_ordered(&c) {
                 if HashSet::contains(&forbidden, &signal) {
                     BTreeSet::insert(&mut remains, signal);
@@ -169,6 +183,9 @@ fn eq_cluster_simplification(
             }
         }
 
+        let origins: Vec<_> =
+            own.into_iter().chain(merged.into_iter().filter(|o| Some(*o) != own)).collect();
+
         let rh_signal = if let Some(signal) = min_remains {
             BTreeSet::remove(&mut remains, &signal);
             signal
@@ -182,7 +199,8 @@ fn eq_cluster_simplification(
             let l = A::Signal { symbol: signal };
             let r = A::Signal { symbol: rh_signal };
             let expr = A::sub(&l, &r, field);
-            let c = A::transform_expression_to_constraint_form(expr, field).unwrap();
+            let mut c = A::transform_expression_to_constraint_form(expr, field).unwrap();
+            c.set_origins(origins.clone());
             LinkedList::push_back(&mut cons, c);
         }
 
@@ -199,6 +217,7 @@ fn eq_simplification(
     equalities: LinkedList<C>,
     forbidden: Arc<HashSet<usize>>,
     no_vars: usize,
+    track_origins: bool,
     field: &BigInt,
     substitution_log: &mut Option<SubstitutionJSON>,
 ) -> (LinkedList<S>, LinkedList<C>) {
@@ -217,7 +236,8 @@ fn eq_simplification(
     let mut aux_constraints = vec![LinkedList::new(); clusters.len()];
     for cluster in clusters {
         if Cluster::size(&cluster) == 1 {
-            let (mut subs, cons) = eq_cluster_simplification(cluster, &forbidden, &field);
+            let (mut subs, cons) =
+                eq_cluster_simplification(cluster, &forbidden, track_origins, &field);
             aux_constraints[id] = cons;
             LinkedList::append(&mut substitutions, &mut subs);
             single_clusters += 1;
@@ -227,7 +247,7 @@ fn eq_simplification(
             let field = Arc::clone(&field);
             let job = move || {
                 //println!("Cluster: {}", id);
-                let result = eq_cluster_simplification(cluster, &forbidden, &field);
+                let result = eq_cluster_simplification(cluster, &forbidden, track_origins, &field);
                 //println!("End of cluster: {}", id);
                 cluster_tx.send((id, result)).unwrap();
             };
@@ -253,6 +273,7 @@ fn eq_simplification(
 fn constant_eq_simplification(
     c_eq: LinkedList<C>,
     forbidden: &HashSet<usize>,
+    track_origins: bool,
     field: &BigInt,
     substitution_log: &mut Option<SubstitutionJSON>,
 ) -> (LinkedList<S>, LinkedList<C>) {
@@ -264,7 +285,10 @@ fn constant_eq_simplification(
         if HashSet::contains(&forbidden, &signal) {
             LinkedList::push_back(&mut cons, constraint);
         } else {
-            let sub = C::clear_signal_from_linear(constraint, &signal, field);
+            let mut sub = C::clear_signal_from_linear(constraint, &signal, field);
+            if !track_origins {
+                sub.set_origins(Vec::new());
+            }
             LinkedList::push_back(&mut subs, sub);
         }
     }
@@ -279,6 +303,7 @@ fn linear_simplification(
     no_labels: usize,
     field: &BigInt,
     use_old_heuristics: bool,
+    track_origins: bool,
 ) -> (LinkedList<S>, LinkedList<C>) {
     use circom_algebra::simplification_utils::full_simplification;
     use circom_algebra::simplification_utils::Config;
@@ -302,6 +327,7 @@ fn linear_simplification(
             forbidden: Arc::clone(&forbidden),
             num_signals: cluster.num_signals,
             use_old_heuristics,
+            track_origins,
         };
         let job = move || {
             // println!("cluster: {}", id);
@@ -438,11 +464,42 @@ fn remove_not_relevant(substitutions: &mut SEncoded, relevant: &HashSet<usize>)
 }
 
 
+// keeps every constraint & signal as generated, the witness is the signals in order
+fn no_simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, usize) {
+    use super::non_linear_utils::obtain_and_simplify_non_linear;
+    let field = smp.field.clone();
+    let mut storage = ConstraintStorage::new();
+    let iter = EncodingIterator::new(&smp.dag_encoding);
+    let mut linear = obtain_and_simplify_non_linear(
+        iter,
+        &mut storage,
+        &LinkedList::new(),
+        &mut smp.origins,
+        &field,
+    );
+    crate::state_utils::empty_encoding_constraints(&mut smp.dag_encoding);
+    linear.append(&mut smp.equalities);
+    linear.append(&mut smp.cons_equalities);
+    linear.append(&mut smp.linear);
+    for mut constraint in linear {
+        C::fix_constraint(&mut constraint, &field);
+        storage.add_constraint(constraint);
+    }
+    let _trash = storage.extract_with(&|c| C::is_empty(c));
+    let signal_map = (0..smp.max_signal).map(|signal| (signal, signal)).collect();
+    (storage, signal_map, smp.no_private_inputs)
+}
+
 // returns the constraints, the assignment of the witness and the number of inputs in the witness
 pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, usize) {
+    if smp.flag_f {
+        return no_simplification(smp);
+    }
     use super::non_linear_utils::obtain_and_simplify_non_linear;
     use circom_algebra::simplification_utils::build_encoded_fast_substitutions;
+    use circom_algebra::simplification_utils::build_encoded_origins;
     use circom_algebra::simplification_utils::fast_encoded_constraint_substitution;
+    use circom_algebra::simplification_utils::merge_encoded_origins;
     use std::time::SystemTime;
 
     let mut substitution_log =
@@ -453,6 +510,7 @@ pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, us
         };
     let apply_linear = !smp.flag_s;
     let use_old_heuristics = smp.flag_old_heuristics;
+    let track_origins = smp.track_origins;
     let field = smp.field.clone();
     let forbidden = Arc::new(std::mem::replace(&mut smp.forbidden, HashSet::with_capacity(0)));
     let no_labels = Simplifier::no_labels(smp);
@@ -485,6 +543,7 @@ pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, us
             equalities,
             Arc::clone(&forbidden),
             no_labels,
+            track_origins,
             &field,
             &mut substitution_log,
         );
@@ -510,14 +569,24 @@ pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, us
         substitutions
     };
 
-    let cons_substitutions = {
+    // the signals renamed by the single substitutions keep the origins
+    // of the constraints, the constant & linear ones are merged into them
+    let (cons_substitutions, cons_origins) = {
         // println!("Start of constant assignment simplification");
         let now = SystemTime::now();
         let (subs, mut cons) =
-            constant_eq_simplification(cons_equalities, &forbidden, &field, &mut substitution_log);
+            constant_eq_simplification(
+            cons_equalities,
+            &forbidden,
+            track_origins,
+            &field,
+            &mut substitution_log,
+        );
         LinkedList::append(&mut lconst, &mut cons);
+        let origins = build_encoded_origins(&subs);
         let substitutions = build_encoded_fast_substitutions(subs);
         for constraint in &mut linear {
+            merge_encoded_origins(constraint, &origins);
             if fast_encoded_constraint_substitution(constraint, &substitutions, &field){
                 C::fix_constraint(constraint, &field);
             }
@@ -527,7 +596,7 @@ pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, us
         }
         let _dur = now.elapsed().unwrap().as_millis();
         // println!("End of constant assignment simplification: {} ms", dur);
-        substitutions
+        (substitutions, origins)
     };
 
     let relevant_signals = {
@@ -541,7 +610,7 @@ pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, us
         relevant
     };
 
-    let linear_substitutions = if apply_linear {
+    let (linear_substitutions, linear_origins) = if apply_linear {
         let now = SystemTime::now();
         let (subs, mut cons) = linear_simplification(
             &mut substitution_log,
@@ -550,6 +619,7 @@ pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, us
             no_labels,
             &field,
             use_old_heuristics,
+            track_origins,
         );
         // println!("Building substitution map");
         let now0 = SystemTime::now();
@@ -560,6 +630,7 @@ pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, us
                 only_relevant.push_back(substitution);
             }
         }
+        let origins = build_encoded_origins(&only_relevant);
         let substitutions = build_encoded_fast_substitutions(only_relevant);
         let _dur0 = now0.elapsed().unwrap().as_millis();
         // println!("End of substitution map: {} ms", dur0);
@@ -567,26 +638,28 @@ pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, us
         // println!("End of cluster simplification: {} ms", dur);
         LinkedList::append(&mut lconst, &mut cons);
         for constraint in &mut lconst {
+            merge_encoded_origins(constraint, &origins);
             if fast_encoded_constraint_substitution(constraint, &substitutions, &field){
                 C::fix_constraint(constraint, &field);
             }
         }
-        substitutions
+        (substitutions, origins)
     } else {
         LinkedList::append(&mut lconst, &mut linear);
-        HashMap::with_capacity(0)
+        (HashMap::with_capacity(0), HashMap::with_capacity(0))
     };
 
     let (with_linear, mut constraint_storage) = {
         // println!("Building constraint storage");
         let now = SystemTime::now();
         let mut frames = LinkedList::new();
-        LinkedList::push_back(&mut frames, single_substitutions);
-        LinkedList::push_back(&mut frames, cons_substitutions);
-        LinkedList::push_back(&mut frames, linear_substitutions);
+        LinkedList::push_back(&mut frames, (single_substitutions, HashMap::with_capacity(0)));
+        LinkedList::push_back(&mut frames, (cons_substitutions, cons_origins));
+        LinkedList::push_back(&mut frames, (linear_substitutions, linear_origins));
         let iter = EncodingIterator::new(&smp.dag_encoding);
         let mut storage = ConstraintStorage::new();
-        let with_linear = obtain_and_simplify_non_linear(iter, &mut storage, &frames, &field);
+        let with_linear =
+            obtain_and_simplify_non_linear(iter, &mut storage, &frames, &mut smp.origins, &field);
         crate::state_utils::empty_encoding_constraints(&mut smp.dag_encoding);
         let _dur = now.elapsed().unwrap().as_millis();
         // println!("Storages built in {} ms", dur);
@@ -618,6 +691,7 @@ pub fn simplification(smp: &mut Simplifier) -> (ConstraintStorage, SignalMap, us
             no_labels,
             &field,
             use_old_heuristics,
+            track_origins,
         );
 
         for sub in &substitutions {
@@ -4,6 +4,7 @@ use circom_algebra::constraint_storage::ConstraintStorage;
 use circom_algebra::num_bigint::BigInt;
 use constraint_writers::debug_writer::DebugWriter;
 use constraint_writers::ConstraintExporter;
+use program_structure::file_definition::{FileID, FileLocation};
 
 mod constraint_simplification;
 mod json_porting;
@@ -18,7 +19,25 @@ type S = circom_algebra::algebra::Substitution<usize>;
 type A = circom_algebra::algebra::ArithmeticExpression<usize>;
 type SignalMap = HashMap<usize, usize>;
 type SEncoded = HashMap<usize, A>;
-type SFrames = LinkedList<SEncoded>;
+type SOrigins = HashMap<usize, Vec<usize>>;
+type SFrames = LinkedList<(SEncoded, SOrigins)>;
+
+/// The statement that generated a constraint of a template.
+#[derive(Clone)]
+pub struct ConstraintSource {
+    pub file_id: FileID,
+    pub location: FileLocation,
+}
+
+/// The statement that generated a constraint, in one of the
+/// components of the circuit.
+#[derive(Clone)]
+pub struct ConstraintOrigin {
+    pub source: ConstraintSource,
+    pub template: String,
+    // path of the component, e.g. main.hasher
+    pub path: String,
+}
 
 pub struct SignalInfo {
     pub name: String,
@@ -31,6 +50,8 @@ pub struct EncodingNode {
     pub signals: Vec<SignalInfo>,
     pub ordered_signals: Vec<usize>,
     pub non_linear: LinkedList<C>,
+    // sources of the constraints, indexed by their origins
+    pub sources: Vec<ConstraintSource>,
     pub is_custom_gate: bool,
 }
 
@@ -100,6 +121,16 @@ impl<'a> EncodingIterator<'a> {
         &iterator.encoding.adjacency[iterator.node_id]
     }
 
+    // origin of a constraint of the node, from the index of its source
+    pub fn origin(iterator: &EncodingIterator, source: usize) -> ConstraintOrigin {
+        let node = &iterator.encoding.nodes[iterator.node_id];
+        ConstraintOrigin {
+            source: node.sources[source].clone(),
+            template: node.name.clone(),
+            path: iterator.path.clone(),
+        }
+    }
+
     pub fn take(iter: &mut EncodingIterator) -> (Vec<SignalInfo>, LinkedList<C>) {
         let ret = (std::mem::take(&mut iter.signals), std::mem::take(&mut iter.non_linear));
         state_utils::clear_encoding_iterator(iter);
@@ -117,13 +148,20 @@ pub struct Simplifier {
     pub cons_equalities: LinkedList<C>,
     pub equalities: LinkedList<C>,
     pub linear: LinkedList<C>,
+    // origins of the constraints, the ones of the non linear are added
+    // while the encoding is traversed
+    pub origins: Vec<ConstraintOrigin>,
     //  Signals in [witness_len, Vec::len(&signal_map)) are the ones deleted
     pub max_signal: usize,
     // Flags
     pub no_rounds: usize,
     pub parallel_flag: bool,
+    // no simplification at all (O0)
+    pub flag_f: bool,
     pub flag_s: bool,
     pub flag_old_heuristics: bool,
+    // the origins of the constraints merged by the simplification are kept
+    pub track_origins: bool,
     pub port_substitution: bool,
     pub json_substitutions: String,
 }
@@ -140,6 +178,7 @@ impl Simplifier {
             no_labels: self.max_signal,
             constraints: portable,
             signal_map: map,
+            origins: self.origins,
         }
     }
 
@@ -163,6 +202,8 @@ pub struct ConstraintList {
     pub no_labels: usize,
     //  Signals in [witness_len, Vec::len(&signal_map)) are the ones deleted
     pub signal_map: SignalMap,
+    // indexed by the origins of the constraints
+    pub origins: Vec<ConstraintOrigin>,
 }
 
 impl ConstraintExporter for ConstraintList {
@@ -184,6 +225,13 @@ impl ConstraintList {
         &self.signal_map
     }
 
+    pub fn r1cs_to_writer<W>(&self, writer: W, custom_gates: bool) -> Result<W, ()>
+    where
+        W: std::io::Write + std::io::Seek,
+    {
+        r1cs_porting::port_r1cs_to_writer(self, writer, custom_gates)
+    }
+
     pub fn get_witness_as_vec(&self) -> Vec<usize> {
         let mut witness = vec![0; self.no_wires()];
         for (key, value) in &self.signal_map {
@@ -1,18 +1,26 @@
-use super::{ConstraintStorage, EncodingIterator, SFrames, C};
+use super::{ConstraintOrigin, ConstraintStorage, EncodingIterator, SFrames, C};
 use circom_algebra::num_bigint::BigInt;
 use circom_algebra::simplification_utils::fast_encoded_constraint_substitution;
+use circom_algebra::simplification_utils::merge_encoded_origins;
 use std::collections::LinkedList;
 
 pub fn obtain_and_simplify_non_linear(
     mut iter: EncodingIterator,
     storage: &mut ConstraintStorage,
     frames: &SFrames,
+    origins: &mut Vec<ConstraintOrigin>,
     field: &BigInt,
 ) -> LinkedList<C> {
     let mut linear = LinkedList::new();
     let (_, non_linear) = EncodingIterator::take(&mut iter);
     for mut constraint in non_linear {
-        for frame in frames {
+        if let Some(source) = constraint.origins().first() {
+            let origin = EncodingIterator::origin(&iter, *source);
+            constraint.set_origins(vec![origins.len()]);
+            origins.push(origin);
+        }
+        for (frame, frame_origins) in frames {
+            merge_encoded_origins(&mut constraint, frame_origins);
             fast_encoded_constraint_substitution(&mut constraint, frame, &field);
         }
         C::fix_constraint(&mut constraint, &field);
@@ -24,7 +32,8 @@ pub fn obtain_and_simplify_non_linear(
     }
     for edge in EncodingIterator::edges(&iter) {
         let next = EncodingIterator::next(&iter, edge);
-        let mut linear_in_next = obtain_and_simplify_non_linear(next, storage, frames, field);
+        let mut linear_in_next =
+            obtain_and_simplify_non_linear(next, storage, frames, origins, field);
         linear.append(&mut linear_in_next);
     }
     linear
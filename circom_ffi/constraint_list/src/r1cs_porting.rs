@@ -1,13 +1,39 @@
 use super::{ConstraintList, C, EncodingIterator, SignalMap};
+use constraint_writers::log_writer::Log;
 use constraint_writers::r1cs_writer::{ConstraintSection, CustomGatesAppliedData, HeaderData, R1CSWriter, SignalSection};
+use std::io::{Seek, Write};
 
 pub fn port_r1cs(list: &ConstraintList, output: &str, custom_gates: bool) -> Result<(), ()> {
-    use constraint_writers::log_writer::Log;
-    let field_size = if list.field.bits() % 64 == 0 {
+    let r1cs = R1CSWriter::new(output.to_string(), field_size(list), custom_gates)?;
+    let (_, log) = write_r1cs(list, r1cs, custom_gates)?;
+    Log::print(&log);
+    Ok(())
+}
+
+// writes the r1cs into any seekable sink (i.e. an in memory buffer) and hands it back
+pub fn port_r1cs_to_writer<W: Write + Seek>(
+    list: &ConstraintList,
+    writer: W,
+    custom_gates: bool,
+) -> Result<W, ()> {
+    let r1cs = R1CSWriter::from_writer(writer, field_size(list), custom_gates)?;
+    let (writer, _) = write_r1cs(list, r1cs, custom_gates)?;
+    Ok(writer)
+}
+
+fn field_size(list: &ConstraintList) -> usize {
+    if list.field.bits() % 64 == 0 {
         list.field.bits() / 8
     } else{
         (list.field.bits() / 64 + 1) * 8
-    };
+    }
+}
+
+fn write_r1cs<W: Write + Seek>(
+    list: &ConstraintList,
+    r1cs: R1CSWriter<W>,
+    custom_gates: bool,
+) -> Result<(W, Log), ()> {
     let mut log = Log::new();
     log.no_labels = ConstraintList::no_labels(list);
     log.no_wires = ConstraintList::no_wires(list);
@@ -16,7 +42,6 @@ pub fn port_r1cs(list: &ConstraintList, output: &str, custom_gates: bool) -> Res
     log.no_public_inputs = list.no_public_inputs;
     log.no_public_outputs = list.no_public_outputs;
 
-    let r1cs = R1CSWriter::new(output.to_string(), field_size, custom_gates)?;
     let mut constraint_section = R1CSWriter::start_constraints_section(r1cs)?;
     let mut written = 0;
 
@@ -51,8 +76,8 @@ pub fn port_r1cs(list: &ConstraintList, output: &str, custom_gates: bool) -> Res
         SignalSection::write_signal_usize(&mut signal_section, id)?;
     }
     let r1cs = signal_section.end_section()?;
-    if !custom_gates {
-	R1CSWriter::finish_writing(r1cs)?;
+    let writer = if !custom_gates {
+	R1CSWriter::finish_writing(r1cs)?
     } else {
         let mut custom_gates_used_section = R1CSWriter::start_custom_gates_used_section(r1cs)?;
         let (usage_data, occurring_order) = {
@@ -117,8 +142,7 @@ pub fn port_r1cs(list: &ConstraintList, output: &str, custom_gates: bool) -> Res
         };
         custom_gates_applied_section.write_custom_gates_applications(application_data)?;
         let r1cs = custom_gates_applied_section.end_section()?;
-	R1CSWriter::finish_writing(r1cs)?;
-    }
-    Log::print(&log);
-    Ok(())
+	R1CSWriter::finish_writing(r1cs)?
+    };
+    Ok((writer, log))
 }
@@ -3,6 +3,7 @@ pub mod json_writer;
 pub mod log_writer;
 pub mod r1cs_writer;
 pub mod sym_writer;
+pub mod wtns_writer;
 
 pub trait ConstraintExporter {
     fn r1cs(&self, out: &str, custom_gates: bool) -> Result<(), ()>;
@@ -27,7 +27,7 @@ fn bigint_as_bytes(number: &BigInt, with_bytes: usize) -> (Vec<u8>, usize) {
     into_format(&value, with_bytes)
 }
 
-fn initialize_section(writer: &mut BufWriter<File>, header: &[u8]) -> Result<u64, ()> {
+fn initialize_section<W: Write + Seek>(writer: &mut W, header: &[u8]) -> Result<u64, ()> {
     writer.write_all(header).map_err(|_err| {})?;
     //writer.flush().map_err(|_err| {})?;
     let go_back = writer.seek(SeekFrom::Current(0)).map_err(|_err| {})?;
@@ -36,7 +36,7 @@ fn initialize_section(writer: &mut BufWriter<File>, header: &[u8]) -> Result<u64
     Result::Ok(go_back)
 }
 
-fn end_section(writer: &mut BufWriter<File>, go_back: u64, size: usize) -> Result<(), ()> {
+fn end_section<W: Write + Seek>(writer: &mut W, go_back: u64, size: usize) -> Result<(), ()> {
     let go_back_1 = writer.seek(SeekFrom::Current(0)).map_err(|_err| {})?;
     writer.seek(SeekFrom::Start(go_back)).map_err(|_err| {})?;
     let (stream, _) = bigint_as_bytes(&BigInt::from(size), 8);
@@ -71,8 +71,8 @@ fn obtain_linear_combination_block<T>(
     (block, size)
 }
 
-fn write_constraint<T>(
-    file: &mut BufWriter<File>,
+fn write_constraint<T, W: Write>(
+    file: &mut W,
     a: &HashMap<T, BigInt>,
     b: &HashMap<T, BigInt>,
     c: &HashMap<T, BigInt>,
@@ -90,7 +90,7 @@ fn write_constraint<T>(
     Result::Ok(size_a + size_b + size_c)
 }
 
-fn initialize_file(writer: &mut BufWriter<File>, num_sections: u8) -> Result<(), ()> {
+fn initialize_file<W: Write>(writer: &mut W, num_sections: u8) -> Result<(), ()> {
     writer.write_all(MAGIC).map_err(|_err| {})?;
     //writer.flush().map_err(|_err| {})?;
     writer.write_all(VERSION).map_err(|_err| {})?;
@@ -100,14 +100,14 @@ fn initialize_file(writer: &mut BufWriter<File>, num_sections: u8) -> Result<(),
     Result::Ok(())
 }
 
-pub struct R1CSWriter {
+pub struct R1CSWriter<W: Write + Seek = BufWriter<File>> {
     field_size: usize,
-    writer: BufWriter<File>,
+    writer: W,
     sections: [bool; SECTIONS as usize]
 }
 
-pub struct HeaderSection {
-    writer: BufWriter<File>,
+pub struct HeaderSection<W: Write + Seek = BufWriter<File>> {
+    writer: W,
     go_back: u64,
     size: usize,
     index: usize,
@@ -115,8 +115,8 @@ pub struct HeaderSection {
     sections: [bool; SECTIONS as usize]
 }
 
-pub struct ConstraintSection {
-    writer: BufWriter<File>,
+pub struct ConstraintSection<W: Write + Seek = BufWriter<File>> {
+    writer: W,
     number_of_constraints: usize,
     go_back: u64,
     size: usize,
@@ -125,8 +125,8 @@ pub struct ConstraintSection {
     sections: [bool; SECTIONS as usize]
 }
 
-pub struct SignalSection {
-    writer: BufWriter<File>,
+pub struct SignalSection<W: Write + Seek = BufWriter<File>> {
+    writer: W,
     go_back: u64,
     size: usize,
     index: usize,
@@ -134,8 +134,8 @@ pub struct SignalSection {
     sections: [bool; SECTIONS as usize]
 }
 
-pub struct CustomGatesUsedSection {
-    writer: BufWriter<File>,
+pub struct CustomGatesUsedSection<W: Write + Seek = BufWriter<File>> {
+    writer: W,
     go_back: u64,
     size: usize,
     index: usize,
@@ -143,8 +143,8 @@ pub struct CustomGatesUsedSection {
     sections: [bool; SECTIONS as usize]
 }
 
-pub struct CustomGatesAppliedSection {
-    writer: BufWriter<File>,
+pub struct CustomGatesAppliedSection<W: Write + Seek = BufWriter<File>> {
+    writer: W,
     go_back: u64,
     size: usize,
     index: usize,
@@ -158,15 +158,25 @@ impl R1CSWriter {
         field_size: usize,
         custom_gates: bool
     ) -> Result<R1CSWriter, ()> {
+        let writer =
+            File::create(&output_file).map_err(|_err| {}).map(|f| BufWriter::new(f))?;
+        R1CSWriter::from_writer(writer, field_size, custom_gates)
+    }
+}
+
+impl<W: Write + Seek> R1CSWriter<W> {
+    pub fn from_writer(
+        mut writer: W,
+        field_size: usize,
+        custom_gates: bool
+    ) -> Result<R1CSWriter<W>, ()> {
         let sections = [false; SECTIONS as usize];
         let num_sections: u8 = if custom_gates { 5 } else { 3 };
-        let mut writer =
-            File::create(&output_file).map_err(|_err| {}).map(|f| BufWriter::new(f))?;
         initialize_file(&mut writer, num_sections)?;
         Result::Ok(R1CSWriter { writer, sections, field_size })
     }
 
-    pub fn start_header_section(mut r1cs: R1CSWriter) -> Result<HeaderSection, ()> {
+    pub fn start_header_section(mut r1cs: R1CSWriter<W>) -> Result<HeaderSection<W>, ()> {
         let start = initialize_section(&mut r1cs.writer, HEADER_TYPE)?;
         Result::Ok(HeaderSection {
             writer: r1cs.writer,
@@ -178,7 +188,7 @@ impl R1CSWriter {
         })
     }
 
-    pub fn start_constraints_section(mut r1cs: R1CSWriter) -> Result<ConstraintSection, ()> {
+    pub fn start_constraints_section(mut r1cs: R1CSWriter<W>) -> Result<ConstraintSection<W>, ()> {
         let start = initialize_section(&mut r1cs.writer, CONSTRAINT_TYPE)?;
         Result::Ok(ConstraintSection {
             number_of_constraints: 0,
@@ -191,7 +201,7 @@ impl R1CSWriter {
         })
     }
 
-    pub fn start_signal_section(mut r1cs: R1CSWriter) -> Result<SignalSection, ()> {
+    pub fn start_signal_section(mut r1cs: R1CSWriter<W>) -> Result<SignalSection<W>, ()> {
         let start = initialize_section(&mut r1cs.writer, WIRE2LABEL_TYPE)?;
         Result::Ok(SignalSection {
             writer: r1cs.writer,
@@ -203,7 +213,7 @@ impl R1CSWriter {
         })
     }
 
-    pub fn start_custom_gates_used_section(mut r1cs: R1CSWriter) -> Result<CustomGatesUsedSection, ()> {
+    pub fn start_custom_gates_used_section(mut r1cs: R1CSWriter<W>) -> Result<CustomGatesUsedSection<W>, ()> {
         let start = initialize_section(&mut r1cs.writer, CUSTOM_GATES_USED_TYPE)?;
         Result::Ok(CustomGatesUsedSection {
             writer: r1cs.writer,
@@ -215,7 +225,7 @@ impl R1CSWriter {
         })
     }
 
-    pub fn start_custom_gates_applied_section(mut r1cs: R1CSWriter) -> Result<CustomGatesAppliedSection, ()> {
+    pub fn start_custom_gates_applied_section(mut r1cs: R1CSWriter<W>) -> Result<CustomGatesAppliedSection<W>, ()> {
         let start = initialize_section(&mut r1cs.writer, CUSTOM_GATES_APPLIED_TYPE)?;
         Result::Ok(CustomGatesAppliedSection {
             writer: r1cs.writer,
@@ -227,8 +237,9 @@ impl R1CSWriter {
         })
     }
 
-    pub fn finish_writing(mut r1cs: R1CSWriter) -> Result<(), ()> {
-	r1cs.writer.flush().map_err(|_err| {})
+    pub fn finish_writing(mut r1cs: R1CSWriter<W>) -> Result<W, ()> {
+        r1cs.writer.flush().map_err(|_err| {})?;
+        Result::Ok(r1cs.writer)
     }
 }
 
@@ -242,7 +253,7 @@ pub struct HeaderData {
     pub number_of_constraints: usize,
 }
 
-impl HeaderSection {
+impl<W: Write + Seek> HeaderSection<W> {
     pub fn write_section(&mut self, data: HeaderData) -> Result<(), ()> {
         let (field_stream, bytes_field) = bigint_as_bytes(&data.field, self.field_size);
         let (length_stream, bytes_size) = bigint_as_bytes(&BigInt::from(self.field_size), 4);
@@ -268,7 +279,7 @@ impl HeaderSection {
         Result::Ok(())
     }
 
-    pub fn end_section(mut self) -> Result<R1CSWriter, ()> {
+    pub fn end_section(mut self) -> Result<R1CSWriter<W>, ()> {
         end_section(&mut self.writer, self.go_back, self.size)?;
         let mut sections = self.sections;
         let index = self.index;
@@ -278,7 +289,7 @@ impl HeaderSection {
 }
 
 type Constraint = HashMap<usize, BigInt>;
-impl ConstraintSection {
+impl<W: Write + Seek> ConstraintSection<W> {
     pub fn write_constraint_usize(
         &mut self,
         a: &Constraint,
@@ -307,7 +318,7 @@ impl ConstraintSection {
         Result::Ok(())
     }
 
-    pub fn end_section(mut self) -> Result<R1CSWriter, ()> {
+    pub fn end_section(mut self) -> Result<R1CSWriter<W>, ()> {
         end_section(&mut self.writer, self.go_back, self.size)?;
         let mut sections = self.sections;
         let index = self.index;
@@ -324,7 +335,7 @@ impl ConstraintSection {
     }
 }
 
-impl SignalSection {
+impl<W: Write + Seek> SignalSection<W> {
     pub fn write_signal<T>(
         &mut self,
         bytes: &T
@@ -337,10 +348,10 @@ impl SignalSection {
 
     pub fn write_signal_usize(&mut self, signal: usize) -> Result<(), ()> {
         let (_, as_bytes) = BigInt::from(signal).to_bytes_le();
-        SignalSection::write_signal(self, &as_bytes)
+        SignalSection::<W>::write_signal(self, &as_bytes)
     }
 
-    pub fn end_section(mut self) -> Result<R1CSWriter, ()> {
+    pub fn end_section(mut self) -> Result<R1CSWriter<W>, ()> {
         end_section(&mut self.writer, self.go_back, self.size)?;
         let mut sections = self.sections;
         let index = self.index;
@@ -354,7 +365,7 @@ impl SignalSection {
 }
 
 pub type CustomGatesUsedData = Vec<(String, Vec<BigInt>)>;
-impl CustomGatesUsedSection {
+impl<W: Write + Seek> CustomGatesUsedSection<W> {
     pub fn write_custom_gates_usages(&mut self, data: CustomGatesUsedData) -> Result<(), ()> {
         let no_custom_gates = data.len();
         let (no_custom_gates_stream, no_custom_gates_size) =
@@ -382,7 +393,7 @@ impl CustomGatesUsedSection {
             for parameter in custom_gate_parameters {
                 let (parameter_stream, parameter_size) = bigint_as_bytes(&parameter, self.field_size);
                 self.size += parameter_size;
-                self.writer.write(&parameter_stream).map_err(|_err| {})?;
+                self.writer.write_all(&parameter_stream).map_err(|_err| {})?;
                 //self.writer.flush().map_err(|_err| {})?;
             }
         }
@@ -390,7 +401,7 @@ impl CustomGatesUsedSection {
         Result::Ok(())
     }
 
-    pub fn end_section(mut self) -> Result<R1CSWriter, ()> {
+    pub fn end_section(mut self) -> Result<R1CSWriter<W>, ()> {
         end_section(&mut self.writer, self.go_back, self.size)?;
         let mut sections = self.sections;
         let index = self.index;
@@ -404,7 +415,7 @@ impl CustomGatesUsedSection {
 }
 
 pub type CustomGatesAppliedData = Vec<(usize, Vec<usize>)>;
-impl CustomGatesAppliedSection {
+impl<W: Write + Seek> CustomGatesAppliedSection<W> {
     pub fn write_custom_gates_applications(&mut self, data: CustomGatesAppliedData) -> Result<(), ()> {
         let no_custom_gate_applications = data.len();
         let (no_custom_gate_applications_stream, no_custom_gate_applications_size) =
@@ -432,7 +443,7 @@ impl CustomGatesAppliedSection {
             for signal in custom_gate_signals {
                 let (signal_stream, signal_size) = bigint_as_bytes(&BigInt::from(signal), 8);
                 self.size += signal_size;
-                self.writer.write(&signal_stream).map_err(|_err| {})?;
+                self.writer.write_all(&signal_stream).map_err(|_err| {})?;
                 //self.writer.flush().map_err(|_err| {})?;
             }
         }
@@ -440,7 +451,7 @@ impl CustomGatesAppliedSection {
         Result::Ok(())
     }
 
-    pub fn end_section(mut self) -> Result<R1CSWriter, ()> {
+    pub fn end_section(mut self) -> Result<R1CSWriter<W>, ()> {
         end_section(&mut self.writer, self.go_back, self.size)?;
         let mut sections = self.sections;
         let index = self.index;
@@ -0,0 +1,66 @@
+use circom_algebra::num_bigint::BigInt;
+use std::io::Write;
+
+// iden3 binary witness format, as read by snarkjs & rapidsnark
+const MAGIC: &[u8] = b"wtns";
+const VERSION: &[u8] = &[2, 0, 0, 0];
+const SECTIONS: &[u8] = &[2, 0, 0, 0];
+const HEADER_TYPE: &[u8] = &[1, 0, 0, 0];
+const WITNESS_TYPE: &[u8] = &[2, 0, 0, 0];
+
+fn bigint_as_bytes(number: &BigInt, with_bytes: usize) -> Vec<u8> {
+    let (_, mut value) = number.to_bytes_le();
+    value.resize(with_bytes, 0);
+    value
+}
+
+pub struct WtnsWriter<W: Write> {
+    writer: W,
+    field: BigInt,
+    field_size: usize,
+    remaining: usize,
+}
+
+impl<W: Write> WtnsWriter<W> {
+    // writes the header section and opens the witness section,
+    // which must then receive exactly no_witness values
+    pub fn new(
+        mut writer: W,
+        field: &BigInt,
+        field_size: usize,
+        no_witness: usize,
+    ) -> Result<WtnsWriter<W>, ()> {
+        writer.write_all(MAGIC).map_err(|_err| {})?;
+        writer.write_all(VERSION).map_err(|_err| {})?;
+        writer.write_all(SECTIONS).map_err(|_err| {})?;
+
+        writer.write_all(HEADER_TYPE).map_err(|_err| {})?;
+        writer.write_all(&((4 + field_size + 4) as u64).to_le_bytes()).map_err(|_err| {})?;
+        writer.write_all(&(field_size as u32).to_le_bytes()).map_err(|_err| {})?;
+        writer.write_all(&bigint_as_bytes(field, field_size)).map_err(|_err| {})?;
+        writer.write_all(&(no_witness as u32).to_le_bytes()).map_err(|_err| {})?;
+
+        writer.write_all(WITNESS_TYPE).map_err(|_err| {})?;
+        writer.write_all(&((field_size * no_witness) as u64).to_le_bytes()).map_err(|_err| {})?;
+        Result::Ok(WtnsWriter { writer, field: field.clone(), field_size, remaining: no_witness })
+    }
+
+    pub fn write_witness(&mut self, value: &BigInt) -> Result<(), ()> {
+        if self.remaining == 0 {
+            return Result::Err(());
+        }
+        // values are stored in their canonical form, in [0, p)
+        let value = ((value % &self.field) + &self.field) % &self.field;
+        self.writer.write_all(&bigint_as_bytes(&value, self.field_size)).map_err(|_err| {})?;
+        self.remaining -= 1;
+        Result::Ok(())
+    }
+
+    pub fn finish_writing(mut wtns: WtnsWriter<W>) -> Result<W, ()> {
+        if wtns.remaining != 0 {
+            return Result::Err(());
+        }
+        wtns.writer.flush().map_err(|_err| {})?;
+        Result::Ok(wtns.writer)
+    }
+}
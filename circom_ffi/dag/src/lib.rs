@@ -2,15 +2,19 @@ mod constraint_correctness_analysis;
 mod json_porting;
 mod map_to_constraint_list;
 mod r1cs_porting;
+mod statistics_porting;
 mod sym_porting;
 mod witness_producer;
 use circom_algebra::num_bigint::BigInt;
-use constraint_list::ConstraintList;
+use constraint_list::{ConstraintList, ConstraintSource};
 use constraint_writers::debug_writer::DebugWriter;
 use constraint_writers::ConstraintExporter;
 use program_structure::constants::UsefulConstants;
 use program_structure::error_definition::ReportCollection;
 use std::collections::{HashMap, HashSet};
+
+pub use statistics_porting::{DAGStatistics, InstanceStatistics, NodeStatistics};
+
 type Signal = usize;
 type Constraint = circom_algebra::algebra::Constraint<usize>;
 type Substitution = circom_algebra::algebra::Substitution<usize>;
@@ -156,6 +160,8 @@ pub struct Node {
     forbidden_if_main: HashSet<usize>,
     io_signals: Vec<usize>,
     constraints: Vec<Constraint>,
+    // sources of the constraints, indexed by their origins
+    sources: Vec<ConstraintSource>,
     underscored_signals: Vec<usize>,
     is_parallel: bool,
     has_parallel_sub_cmp: bool,
@@ -225,7 +231,9 @@ impl Node {
         self.ordered_signals.push(name);
     }
 
-    fn add_constraint(&mut self, constraint: Constraint) {
+    fn add_constraint(&mut self, mut constraint: Constraint, source: ConstraintSource) {
+        constraint.set_origins(vec![self.sources.len()]);
+        self.sources.push(source);
         self.constraints.push(constraint)
     }
 
@@ -410,9 +418,9 @@ impl DAG {
         }
     }
 
-    pub fn add_constraint(&mut self, constraint: Constraint) {
+    pub fn add_constraint(&mut self, constraint: Constraint, source: ConstraintSource) {
         if let Option::Some(node) = self.get_mut_main() {
-            node.add_constraint(constraint);
+            node.add_constraint(constraint, source);
         }
     }
 
@@ -491,6 +499,10 @@ impl DAG {
         json_porting::port_constraints(self, debug)
     }
 
+    pub fn statistics(&self) -> DAGStatistics {
+        statistics_porting::dag_statistics(self)
+    }
+
     pub fn produce_witness(&self) -> Vec<usize> {
         witness_producer::produce_witness(self)
     }
@@ -546,10 +558,14 @@ impl DAG {
 
 pub struct SimplificationFlags {
     pub no_rounds: usize,
+    // no simplification at all (O0)
+    pub flag_f: bool,
     pub flag_s: bool,
     pub parallel_flag: bool,
     pub port_substitution: bool,
     pub json_substitutions: String,
     pub flag_old_heuristics: bool,
+    // the constraints left keep the origins of the ones merged into them
+    pub track_origins: bool,
     pub prime: String,
 }
@@ -1,5 +1,8 @@
 use super::{Constraint, Edge, Node, SimplificationFlags, Tree, DAG};
-use constraint_list::{ConstraintList, DAGEncoding, EncodingEdge, EncodingNode, SignalInfo, Simplifier};
+use constraint_list::{
+    ConstraintList, ConstraintOrigin, DAGEncoding, EncodingEdge, EncodingNode, SignalInfo,
+    Simplifier,
+};
 use program_structure::utils::constants::UsefulConstants;
 use std::collections::{HashSet, LinkedList};
 #[derive(Default)]
@@ -14,6 +17,7 @@ fn map_tree(
     witness: &mut Vec<usize>,
     c_holder: &mut CHolder,
     forbidden: &mut HashSet<usize>,
+    origins: &mut Vec<ConstraintOrigin>,
 ) -> usize {
     let mut no_constraints = 0;
 
@@ -24,13 +28,27 @@ fn map_tree(
         }
     }
 
+    // the origins of the non linear constraints are taken from the encoding
+    let node = &tree.dag.nodes[tree.node_id];
+    let mut with_origin = |constraint: &Constraint| {
+        let mut constraint = constraint.clone();
+        if let Some(source) = constraint.origins().first() {
+            origins.push(ConstraintOrigin {
+                source: node.sources[*source].clone(),
+                template: node.template_name.clone(),
+                path: tree.path.clone(),
+            });
+            constraint.set_origins(vec![origins.len() - 1]);
+        }
+        constraint
+    };
     for constraint in &tree.constraints {
         if Constraint::is_constant_equality(constraint) {
-            LinkedList::push_back(&mut c_holder.constant_equalities, constraint.clone());
+            LinkedList::push_back(&mut c_holder.constant_equalities, with_origin(constraint));
         } else if Constraint::is_equality(constraint, &tree.field) {
-            LinkedList::push_back(&mut c_holder.equalities, constraint.clone());
+            LinkedList::push_back(&mut c_holder.equalities, with_origin(constraint));
         } else if Constraint::is_linear(constraint) {
-            LinkedList::push_back(&mut c_holder.linear, constraint.clone());
+            LinkedList::push_back(&mut c_holder.linear, with_origin(constraint));
         } else {
             no_constraints += 1;
         }
@@ -38,7 +56,7 @@ fn map_tree(
 
     for edge in Tree::get_edges(tree) {
         let subtree = Tree::go_to_subtree(tree, edge);
-        no_constraints += map_tree(&subtree, witness, c_holder, forbidden);
+        no_constraints += map_tree(&subtree, witness, c_holder, forbidden, origins);
     }
     no_constraints
 }
@@ -99,6 +117,7 @@ fn map_node_to_encoding(id: usize, node: Node) -> EncodingNode {
         signals,
         ordered_signals,
         non_linear,
+        sources: node.sources,
         is_custom_gate: node.is_custom_gate,
     }
 }
@@ -120,7 +139,9 @@ pub fn map(dag: DAG, flags: SimplificationFlags) -> ConstraintList {
     let mut forbidden = dag.get_main().unwrap().forbidden_if_main.clone();
     let mut c_holder = CHolder::default();
     let mut signal_map = vec![0];
-    let no_constraints = map_tree(&Tree::new(&dag), &mut signal_map, &mut c_holder, &mut forbidden);
+    let mut origins = Vec::new();
+    let no_constraints =
+        map_tree(&Tree::new(&dag), &mut signal_map, &mut c_holder, &mut forbidden, &mut origins);
     let max_signal = Vec::len(&signal_map);
     let name_encoding = produce_encoding(no_constraints, init_id, dag.nodes, dag.adjacency);
     let _dur = now.elapsed().unwrap().as_millis();
@@ -136,10 +157,13 @@ pub fn map(dag: DAG, flags: SimplificationFlags) -> ConstraintList {
         linear: c_holder.linear,
         equalities: c_holder.equalities,
         cons_equalities: c_holder.constant_equalities,
+        origins,
         no_rounds: flags.no_rounds,
+        flag_f: flags.flag_f,
         flag_s: flags.flag_s,
         parallel_flag: flags.parallel_flag,
         flag_old_heuristics: flags.flag_old_heuristics,
+        track_origins: flags.track_origins,
         port_substitution: flags.port_substitution,
         json_substitutions: flags.json_substitutions,
     }
@@ -1,71 +1,107 @@
-use super::DAG;
-use constraint_writers::debug_writer::DebugWriter;
+use super::{Constraint, DAG};
+use circom_algebra::num_bigint::BigInt;
 
-struct SlotStatistics {
-    number_of_constraints: usize,
-    number_of_intermediates: usize,
-    number_of_inputs: usize,
-    number_of_outputs: usize,
-    number_of_components: usize,
-    times_reached: usize
+/// Statistics of a node, shared by the instances of a template with the same
+/// parameters, as generated before the simplification.
+pub struct NodeStatistics {
+    pub template: String,
+    pub parameters: Vec<BigInt>,
+    pub linear: usize,
+    pub non_linear: usize,
+    pub intermediates: usize,
+    pub inputs: usize,
+    pub outputs: usize,
+    // subcomponents of the node: the node they are & their label
+    pub components: Vec<(usize, String)>,
+    // edges of the dag reaching the node
+    pub times_reached: usize,
 }
-impl Default for SlotStatistics {
-    fn default() -> Self {
-        SlotStatistics {
-            number_of_constraints: 0,
-            number_of_intermediates: 0,
-            number_of_inputs: 0,
-            number_of_outputs: 0,
-            number_of_components: 0,
-            times_reached: 0
-        }
-    }
+
+/// Statistics of the nodes of a dag, the instances are expanded on request.
+#[derive(Default)]
+pub struct DAGStatistics {
+    // node & path of the main
+    main: Option<(usize, String)>,
+    pub nodes: Vec<NodeStatistics>,
 }
 
-pub fn write_statistics(dag: &DAG,debug: &DebugWriter) -> Result<(),()>{
-    use std::io::Write;
-    let mut writer = debug.build_statistics_file()?;
-    let mut node_statistics = Vec::with_capacity(dag.number_of_nodes());
-    for _i in 0..dag.number_of_nodes() {
-        node_statistics.push(SlotStatistics::default());
-    }
+/// Statistics of a template instance, as generated before the simplification.
+pub struct InstanceStatistics {
+    // path of the instance, e.g. main.hasher
+    pub path: String,
+    pub template: String,
+    pub parameters: Vec<BigInt>,
+    // node of the instance, shared by the instances of the template with the same parameters
+    pub node: usize,
+    pub linear: usize,
+    pub non_linear: usize,
+    pub intermediates: usize,
+    pub inputs: usize,
+    pub outputs: usize,
+    // subcomponents of the instance
+    pub components: usize,
+    // edges of the dag reaching the node
+    pub times_reached: usize,
+}
+
+pub fn dag_statistics(dag: &DAG) -> DAGStatistics {
+    let mut nodes: Vec<NodeStatistics> = dag
+        .nodes
+        .iter()
+        .zip(dag.adjacency.iter())
+        .map(|(node, edges)| NodeStatistics {
+            template: node.template_name.clone(),
+            parameters: node.parameters().clone(),
+            linear: 0,
+            non_linear: 0,
+            intermediates: node.number_of_intermediates(),
+            inputs: node.number_of_inputs(),
+            outputs: node.number_of_outputs(),
+            components: edges.iter().map(|edge| (edge.goes_to, edge.label.clone())).collect(),
+            times_reached: 0,
+        })
+        .collect();
     for i in 0..dag.number_of_nodes() {
-        node_statistics[i].number_of_outputs = dag.nodes[i].number_of_outputs();
-        node_statistics[i].number_of_inputs = dag.nodes[i].number_of_inputs();
-        node_statistics[i].number_of_intermediates = dag.nodes[i].number_of_intermediates();
-        node_statistics[i].number_of_constraints = dag.nodes[i].constraints().len();
-        node_statistics[i].number_of_components = dag.adjacency[i].len();
+        let constraints = dag.nodes[i].constraints().iter().filter(|c| !c.is_empty());
+        for constraint in constraints {
+            if Constraint::is_linear(constraint) {
+                nodes[i].linear += 1;
+            } else {
+                nodes[i].non_linear += 1;
+            }
+        }
         for arrow in &dag.adjacency[i] {
-            let pointer = arrow.goes_to;
-            node_statistics[pointer].times_reached += 1;
+            nodes[arrow.goes_to].times_reached += 1;
         }
     }
-    for (index,node_statistic) in node_statistics.iter().enumerate(){
-        let splitter = "***************************************\n".to_string();
-        let title = format!("NODE NUMBER: {}\n",index);
-        let number_of_constraints = format!("Number of constraints: {}\n",node_statistic.number_of_constraints);
-        let number_of_intermediates = format!("Number of intermediates: {}\n", node_statistic.number_of_intermediates);
-        let number_of_inputs = format!("Number of inputs: {}\n",node_statistic.number_of_inputs);
-        let number_of_outputs = format!("Number of outputs: {}\n",node_statistic.number_of_outputs);
-        let number_of_components = format!("Number of components: {}\n",node_statistic.number_of_components);
-        let times_reached = format!("Times reached: {}\n",node_statistic.times_reached);
+    let main = dag.get_entry().map(|entry| (dag.main_id(), entry.label.clone()));
+    DAGStatistics { main, nodes }
+}
 
-        writer.write_all(splitter.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(title.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(number_of_constraints.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(number_of_intermediates.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(number_of_inputs.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(number_of_outputs.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(number_of_components.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
-        writer.write_all(times_reached.as_bytes()).map_err(|_err| {})?;
-        writer.flush().map_err(|_err| {})?;
+impl DAGStatistics {
+    // the instances of the main & of its subcomponents, in depth first order
+    pub fn instances(&self) -> Vec<InstanceStatistics> {
+        let mut instances = Vec::new();
+        let mut pending: Vec<(usize, String)> = self.main.iter().cloned().collect();
+        while let Some((node, path)) = pending.pop() {
+            let statistics = &self.nodes[node];
+            for (goes_to, label) in statistics.components.iter().rev() {
+                pending.push((*goes_to, format!("{}.{}", path, label)));
+            }
+            instances.push(InstanceStatistics {
+                path,
+                template: statistics.template.clone(),
+                parameters: statistics.parameters.clone(),
+                node,
+                linear: statistics.linear,
+                non_linear: statistics.non_linear,
+                intermediates: statistics.intermediates,
+                inputs: statistics.inputs,
+                outputs: statistics.outputs,
+                components: statistics.components.len(),
+                times_reached: statistics.times_reached,
+            });
+        }
+        instances
     }
-    Result::Ok(())
-}
\ No newline at end of file
+}
@@ -2,7 +2,7 @@ use program_structure::ast::produce_report_with_message;
 use program_structure::error_code::ReportCode;
 use program_structure::error_definition::Report;
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 pub struct FileStack {
     current_location: PathBuf,
@@ -63,6 +63,41 @@ impl FileStack {
     }
 }
 
+// the path with its . & .. components resolved without the file system,
+// a .. above the start of a relative path is kept
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+// resolve an include against files that are not on disk, like add_include does:
+// relative to the location of the including file first, then to the libraries
+pub fn resolve_include<F: Fn(&Path) -> bool>(
+    includer: &Path,
+    name: &str,
+    libraries: &[PathBuf],
+    exists: F,
+) -> Option<PathBuf> {
+    let location = includer.parent().map(Path::to_path_buf).unwrap_or_default();
+    std::iter::once(&location)
+        .chain(libraries.iter())
+        .map(|lib| normalize_path(&lib.join(name)))
+        .find(|path| exists(path))
+}
+
 pub struct IncludesNode {
     pub path: PathBuf,
     pub custom_gates_pragma: bool,
@@ -92,9 +127,14 @@ impl IncludesGraph {
         crr.push(old_path.clone());
         let path = std::fs::canonicalize(crr)
             .map_err(|_e| produce_report_with_message(ReportCode::FileOs, old_path))?;
+        self.add_resolved_edge(path);
+        Ok(())
+    }
+
+    // an edge from the last node to a path resolved already, e.g. by resolve_include
+    pub fn add_resolved_edge(&mut self, path: PathBuf) {
         let edges = self.adjacency.entry(path).or_insert(vec![]);
         edges.push(self.nodes.len() - 1);
-        Ok(())
     }
 
     pub fn get_problematic_paths(&self) -> Vec<Vec<PathBuf>> {
@@ -11,7 +11,8 @@ mod include_logic;
 mod parser_logic;
 mod syntax_sugar_remover;
 
-use include_logic::{FileStack, IncludesGraph};
+use include_logic::FileStack;
+pub use include_logic::{normalize_path, resolve_include, IncludesGraph};
 use num_bigint::BigInt;
 use program_structure::ast::{
     produce_compiler_version_report, produce_report, produce_report_with_message,
@@ -110,9 +111,12 @@ pub fn run_parser(
         let includes = program.includes;
         definitions.push((file_id, program.definitions));
         for include in includes {
-            let path_include =
-                FileStack::add_include(&mut file_stack, include.clone(), &link_libraries.clone())
-                    .map_err(|e| (file_library.clone(), vec![e]))?;
+            let path_include = FileStack::add_include(
+                &mut file_stack,
+                include.path.clone(),
+                &link_libraries.clone(),
+            )
+            .map_err(|e| (file_library.clone(), vec![e]))?;
             includes_graph.add_edge(path_include).map_err(|e| (file_library.clone(), vec![e]))?;
         }
         warnings.append(
@@ -142,16 +146,7 @@ pub fn run_parser(
         warnings.push(report);
         Err((file_library, warnings))
     } else {
-        let mut errors: ReportCollection = includes_graph.get_problematic_paths().iter().map(|path|
-            Report::error(
-                format!(
-                    "Missing custom templates pragma in file {} because of the following chain of includes {}",
-                    path.last().unwrap().display(),
-                    IncludesGraph::display_path(path)
-                ),
-                ReportCode::CustomGatesPragmaError
-            )
-        ).collect();
+        let mut errors = check_custom_gates_pragmas(&includes_graph);
         if errors.len() > 0 {
             warnings.append(&mut errors);
             Err((file_library, warnings))
@@ -185,6 +180,20 @@ pub fn run_parser(
     }
 }
 
+// the files missing the custom templates pragma while including custom templates
+pub fn check_custom_gates_pragmas(includes_graph: &IncludesGraph) -> ReportCollection {
+    includes_graph.get_problematic_paths().iter().map(|path|
+        Report::error(
+            format!(
+                "Missing custom templates pragma in file {} because of the following chain of includes {}",
+                path.last().unwrap().display(),
+                IncludesGraph::display_path(path)
+            ),
+            ReportCode::CustomGatesPragmaError
+        )
+    ).collect()
+}
+
 fn produce_report_with_main_components(
     main_components: Vec<(usize, (Vec<String>, Expression), bool)>,
 ) -> Report {
@@ -211,13 +220,13 @@ fn produce_report_with_main_components(
 
 fn open_file(path: PathBuf) -> Result<(String, String), Report> /* path, src */ {
     use std::fs::read_to_string;
-    let path_str = format!("{:?}", path);
+    let path_str = path.display().to_string();
     read_to_string(path)
         .map(|contents| (path_str.clone(), contents))
         .map_err(|_| produce_report_with_message(ReportCode::FileOs, path_str.clone()))
 }
 
-fn parse_number_version(version: &str) -> Version {
+pub fn parse_number_version(version: &str) -> Version {
     let version_splitted: Vec<&str> = version.split(".").collect();
     (
         usize::from_str(version_splitted[0]).unwrap(),
@@ -226,7 +235,7 @@ fn parse_number_version(version: &str) -> Version {
     )
 }
 
-fn check_number_version(
+pub fn check_number_version(
     file_path: String,
     version_file: Option<Version>,
     version_compiler: Version,
@@ -247,7 +256,7 @@ fn check_number_version(
     }
 }
 
-fn check_custom_gates_version(
+pub fn check_custom_gates_version(
     file_path: String,
     version_file: Option<Version>,
     version_compiler: Version,
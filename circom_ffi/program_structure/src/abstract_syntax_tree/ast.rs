@@ -85,13 +85,20 @@ impl Meta {
     }
 }
 
+// include "path"; at the start of a file
+#[derive(Clone)]
+pub struct Include {
+    pub meta: Meta,
+    pub path: String,
+}
+
 #[derive(Clone)]
 pub struct AST {
     pub meta: Meta,
     pub compiler_version: Option<Version>,
     pub custom_gates: bool,
     pub custom_gates_declared: bool,
-    pub includes: Vec<String>,
+    pub includes: Vec<Include>,
     pub definitions: Vec<Definition>,
     pub main_component: Option<MainComponent>,
 }
@@ -100,7 +107,7 @@ impl AST {
     pub fn new(
         meta: Meta,
         pragmas: Vec<Pragma>,
-        includes: Vec<String>,
+        includes: Vec<Include>,
         definitions: Vec<Definition>,
         main_component: Option<MainComponent>,
     ) -> (AST, Vec<Report>) {
@@ -1,7 +1,7 @@
 use super::ast::*;
 
 impl AST {
-    pub fn get_includes(&self) -> &Vec<String> {
+    pub fn get_includes(&self) -> &Vec<Include> {
         &self.includes
     }
 
@@ -12,7 +12,9 @@ impl AST {
     pub fn get_definitions(&self) -> &Vec<Definition> {
         &self.definitions
     }
-    pub fn decompose(self) -> (Meta, Option<Version>, Vec<String>, Vec<Definition>, Option<MainComponent>) {
+    pub fn decompose(
+        self,
+    ) -> (Meta, Option<Version>, Vec<Include>, Vec<Definition>, Option<MainComponent>) {
         (self.meta, self.compiler_version, self.includes, self.definitions, self.main_component)
     }
 }
@@ -122,6 +122,33 @@ pub enum ReportCode {
     InvalidSignalAccessInBus,
     MustBeSameBus,
     MustBeBus,
+    // Veritas codes
+    UnsupportedPrimeField,
+    UnsupportedTargetVersion,
+    NonPrimeField,
+    MalformedPackageJson,
+    MalformedInputJson,
+    InternalPanic,
+    ExportFailure,
+    MalformedLibrary,
+    WitnessSignalNotFound,
+    WitnessTooManyInputs,
+    WitnessSignalAlreadySet,
+    WitnessAssertFailed,
+    WitnessOutOfMemory,
+    WitnessInputOutOfBounds,
+    WitnessMissingInputs,
+    WitnessDivisionByZero,
+    MissingInputSignal,
+    UnknownInputSignal,
+    InputSignalSizeMismatch,
+    UnknownComponent,
+    UnderconstrainedSignal,
+    UnknownWitnessSignal,
+    MalformedWitness,
+    UnsupportedOptimization,
+    UnsupportedPrimeSize,
+    UnsupportedGroupSize,
 }
 
 impl fmt::Display for ReportCode {
@@ -245,6 +272,33 @@ impl fmt::Display for ReportCode {
             InvalidSignalAccessInBus => "BU04",
             MustBeSameBus => "BU05",
             MustBeBus => "BU06",
+            // Veritas codes
+            UnsupportedPrimeField => "VE01",
+            UnsupportedTargetVersion => "VE02",
+            NonPrimeField => "VE03",
+            MalformedPackageJson => "VE04",
+            MalformedInputJson => "VE05",
+            InternalPanic => "VE06",
+            ExportFailure => "VE07",
+            MalformedLibrary => "VE08",
+            WitnessSignalNotFound => "VE09",
+            WitnessTooManyInputs => "VE10",
+            WitnessSignalAlreadySet => "VE11",
+            WitnessAssertFailed => "VE12",
+            WitnessOutOfMemory => "VE13",
+            WitnessInputOutOfBounds => "VE14",
+            WitnessMissingInputs => "VE15",
+            WitnessDivisionByZero => "VE16",
+            MissingInputSignal => "VE17",
+            UnknownInputSignal => "VE18",
+            InputSignalSizeMismatch => "VE19",
+            UnknownComponent => "VE20",
+            UnderconstrainedSignal => "VE21",
+            UnknownWitnessSignal => "VE22",
+            MalformedWitness => "VE23",
+            UnsupportedOptimization => "VE24",
+            UnsupportedPrimeSize => "VE25",
+            UnsupportedGroupSize => "VE26",
         };
         f.write_str(string_format)
     }
@@ -24,6 +24,17 @@ impl Clone for UsefulConstants {
 
 impl UsefulConstants {
     pub fn new(possible_prime: &String) -> UsefulConstants {
+        match UsefulConstants::try_new(possible_prime) {
+            Some(constants) => constants,
+            None => unreachable!(),
+        }
+    }
+
+    // Same as new, but returns None instead of panicking on an unknown prime.
+    // Besides the named primes, a decimal or 0x prefixed hex literal of an odd
+    // number between 2 and 2^256 is accepted (primality is not checked here,
+    // nor whether the witness generator supports its size)
+    pub fn try_new(possible_prime: &String) -> Option<UsefulConstants> {
         let prime_to_use = if possible_prime.eq("bn128") {P_BN128} 
           else if possible_prime.eq("bls12381") { P_BLS12381} 
           else if possible_prime.eq("goldilocks") { P_GOLDILOCKS} 
@@ -31,9 +42,30 @@ impl UsefulConstants {
           else if possible_prime.eq("pallas") { P_PALLAS} 
           else if possible_prime.eq("vesta") { P_VESTA} 
           else if possible_prime.eq("secq256r1") { P_SECQ256R1}
-          else {unreachable!()};
+          else {return UsefulConstants::from_literal(possible_prime)};
+
+        Some(UsefulConstants { p: BigInt::parse_bytes(prime_to_use.as_bytes(), 10).expect("can not parse p") })
+    }
+
+    fn from_literal(literal: &str) -> Option<UsefulConstants> {
+        let p = if literal.starts_with("0x") || literal.starts_with("0X") {
+            BigInt::parse_bytes(&literal.as_bytes()[2..], 16)?
+        } else {
+            BigInt::parse_bytes(literal.as_bytes(), 10)?
+        };
+        let is_odd = &p % BigInt::from(2) == BigInt::from(1);
+        if p <= BigInt::from(2) || !is_odd || p.bits() > 256 {
+            return None;
+        }
+        Some(UsefulConstants { p })
+    }
+
+    pub fn is_named_prime(possible_prime: &str) -> bool {
+        UsefulConstants::supported_primes().contains(&possible_prime)
+    }
 
-        UsefulConstants { p: BigInt::parse_bytes(prime_to_use.as_bytes(), 10).expect("can not parse p") }
+    pub fn supported_primes() -> Vec<&'static str> {
+        vec!["bn128", "bls12381", "goldilocks", "grumpkin", "pallas", "vesta", "secq256r1"]
     }
     
     pub fn get_p(&self) -> &BigInt {
@@ -1,10 +1,17 @@
 package veritas
 
 import (
+	"bytes"
+	"crypto/sha256"
+	"encoding/binary"
+	"encoding/hex"
 	"encoding/json"
 	"errors"
 	"fmt"
+	"io"
 	"math/big"
+	"os"
+	"path/filepath"
 	"runtime/cgo"
 	"strconv"
 	"strings"
@@ -24,8 +31,61 @@ typedef void* FFICircom;
 // that are required for witness generation
 extern void ffi_compile_library(uintptr_t ctx_handle, char* pkg_json_raw);
 
+// ffi_compile_file will compile the main declared in a .circom file on disk,
+// the includes are searched next to the including file, then in the libraries
+extern void ffi_compile_file(uintptr_t ctx_handle, char* file_json_raw);
+
 // ffi_circuit_execution will generate witness for the given inputs
-extern void ffi_circuit_execution(uintptr_t ctx_handle, FFICircom ffi_circom, char* pkg_json_raw);
+extern void ffi_circuit_execution(uintptr_t ctx_handle, FFICircom ffi_circom, char* pkg_json_raw, char* options_json);
+
+// ffi_witness_evaluation will evaluate the constraints against the given witness
+extern void ffi_witness_evaluation(uintptr_t ctx_handle, FFICircom ffi_circom, char* witness_json, char* options_json);
+
+// ffi_circuit_execution_binary will generate witness for the given inputs
+// and share the evaluation in the compact binary format, chunk by chunk
+extern void ffi_circuit_execution_binary(uintptr_t ctx_handle, FFICircom ffi_circom, char* inputs_json, char* options_json);
+
+// ffi_constraints_binary will share the constraints & the symbols the compact
+// evaluations refer to, in the compact binary format, chunk by chunk
+extern void ffi_constraints_binary(uintptr_t ctx_handle, FFICircom ffi_circom);
+
+// ffi_circuit_execution_batch will generate witnesses for a json array of inputs
+extern void ffi_circuit_execution_batch(uintptr_t ctx_handle, FFICircom ffi_circom, char* inputs_json, char* options_json);
+
+// ffi_detect_underconstrained will mutate the witness of the given inputs
+// and share the counterexamples proving signals underconstrained
+extern void ffi_detect_underconstrained(uintptr_t ctx_handle, FFICircom ffi_circom, char* inputs_json, char* options_json);
+
+// ffi_fuzz will draw inputs for the compiled circuit
+// and share the failing ones with their shrunk reproducers
+extern void ffi_fuzz(uintptr_t ctx_handle, FFICircom ffi_circom, char* options_json);
+
+// ffi_export_ast will parse the pkg and share the syntax tree of every program
+extern void ffi_export_ast(uintptr_t ctx_handle, char* pkg_json_raw);
+
+// ffi_export_r1cs will share the r1cs binary of the compiled circuit
+extern void ffi_export_r1cs(uintptr_t ctx_handle, FFICircom ffi_circom);
+
+// ffi_export_wtns will share the witness for the given inputs in the wtns format
+extern void ffi_export_wtns(uintptr_t ctx_handle, FFICircom ffi_circom, char* inputs_json);
+
+// ffi_export_wasm will share the witness calculation wasm module
+extern void ffi_export_wasm(uintptr_t ctx_handle, FFICircom ffi_circom);
+
+// ffi_export_sym will share the content of the .sym file
+extern void ffi_export_sym(uintptr_t ctx_handle, FFICircom ffi_circom);
+
+// ffi_template_statistics will share the statistics of the template instances
+extern void ffi_template_statistics(uintptr_t ctx_handle, FFICircom ffi_circom);
+
+// ffi_save_library will share a snapshot of the compiled library
+extern void ffi_save_library(uintptr_t ctx_handle, FFICircom ffi_circom);
+
+// ffi_load_library will restore a library from its snapshot without compiling it
+extern void ffi_load_library(uintptr_t ctx_handle, uint8_t* snapshot, size_t len);
+
+// ffi_snapshot_fingerprint will share the fingerprint of the snapshots of this build
+extern void ffi_snapshot_fingerprint(uintptr_t ctx_handle);
 
 // utils
 extern void free_string(char* str);
@@ -48,11 +108,39 @@ func share_report(ctx_handle C.uintptr_t, jsonBytes *C.void, bytesLen C.size_t)
 //export share_circom_ptr
 func share_circom_ptr(ctx_handle C.uintptr_t, circom C.FFICircom) { unwrapCtx(ctx_handle).ptr = circom }
 
+//export share_buffer
+func share_buffer(ctx_handle C.uintptr_t, buff *C.void, buffLen C.size_t) {
+	unwrapCtx(ctx_handle).last_buffer = C.GoBytes(unsafe.Pointer(buff), C.int(buffLen))
+}
+
+//export share_chunk
+func share_chunk(ctx_handle C.uintptr_t, chunk *C.void, chunkLen C.size_t) {
+	ctx := unwrapCtx(ctx_handle)
+	if ctx.sink == nil || ctx.sink_err != nil {
+		return
+	}
+	// the chunk is only valid during the call, so it is written straight away
+	data := unsafe.Slice((*byte)(unsafe.Pointer(chunk)), int(chunkLen))
+	_, ctx.sink_err = ctx.sink.Write(data)
+	ctx.sunk += int(chunkLen)
+	ctx.stream.follow(data)
+}
+
 type _CtxFFI struct {
-	ptr     C.FFICircom
+	ptr C.FFICircom
+	// guards the reports & the last evaluation
+	mtx     sync.Mutex
 	reports ReportCollection
 	// cache for the last evaluation result
 	last_eval *evaluation
+	// cache for the last exported artifact
+	last_buffer []byte
+	// destination of the chunks of a streamed evaluation
+	sink     io.Writer
+	sink_err error
+	sunk     int
+	// sections of the stream written to the sink
+	stream compactStream
 }
 
 func (f *_CtxFFI) free() {
@@ -62,16 +150,21 @@ func (f *_CtxFFI) free() {
 }
 
 func (f *_CtxFFI) CacheEval(e json.RawMessage) {
-	f.last_eval = &evaluation{}
-	if err := json.Unmarshal(e, f.last_eval); err != nil {
+	eval := &evaluation{}
+	if err := json.Unmarshal(e, eval); err != nil {
 		return
 	}
+	f.mtx.Lock()
+	defer f.mtx.Unlock()
+	f.last_eval = eval
 }
 func (f *_CtxFFI) StoreReport(r json.RawMessage) {
 	var report Report
 	if err := json.Unmarshal(r, &report); err != nil {
 		return
 	}
+	f.mtx.Lock()
+	defer f.mtx.Unlock()
 	f.reports = append(f.reports, report)
 }
 
@@ -90,20 +183,62 @@ type Program struct {
 }
 
 type CircuitPkg struct {
-	TargetVersion string    `json:"target_version"`
-	Field         string    `json:"field"`
-	Programs      []Program `json:"programs"`
+	TargetVersion string       `json:"target_version"`
+	Field         string       `json:"field"`
+	Programs      []Program    `json:"programs"`
+	Optimization  Optimization `json:"optimization"`
+	// the unsatisfied constraints also point to the constraints merged into them
+	Provenance bool `json:"provenance,omitempty"`
+}
+
+// CircuitFile is a main declared in a .circom file on disk,
+// compiled with its includes as circom does
+type CircuitFile struct {
+	TargetVersion string `json:"target_version"`
+	Field         string `json:"field"`
+	// path of the file declaring the main component
+	Root string `json:"root"`
+	// directories searched for the includes (circom's -l), e.g. node_modules
+	Libraries    []string     `json:"libraries,omitempty"`
+	Optimization Optimization `json:"optimization"`
+	Provenance   bool         `json:"provenance,omitempty"`
+}
+
+// OptimizationLevel is the simplification of the constraints, as circom's --O0, --O1 & --O2
+type OptimizationLevel string
+
+const (
+	// no simplification, every signal & constraint is kept
+	O0 OptimizationLevel = "O0"
+	// the equalities & the constant signals are simplified (the default)
+	O1 OptimizationLevel = "O1"
+	// the linear constraints are simplified as well
+	O2 OptimizationLevel = "O2"
+)
+
+// Optimization is how the constraints of a pkg are simplified,
+// the zero value takes the defaults
+type Optimization struct {
+	Level OptimizationLevel `json:"level,omitempty"`
+	// rounds of linear simplification at O2 (--O2round), a single one when 0
+	Rounds int `json:"rounds,omitempty"`
+	// the heuristics of circom before 2.1.6 to pick the linear substitutions
+	OldHeuristics bool `json:"old_heuristics,omitempty"`
 }
 
 func MergePackages(pkgs ...CircuitPkg) (*CircuitPkg, error) {
 	var (
 		ver   = pkgs[0].TargetVersion
 		field = pkgs[0].Field
+		opt   = pkgs[0].Optimization
+		prov  = pkgs[0].Provenance
 		pid   = make(map[string]int) // map of program identity to index
 		p     = &CircuitPkg{
 			TargetVersion: ver,
 			Field:         field,
 			Programs:      make([]Program, 0),
+			Optimization:  opt,
+			Provenance:    prov,
 		}
 	)
 
@@ -114,6 +249,12 @@ func MergePackages(pkgs ...CircuitPkg) (*CircuitPkg, error) {
 		if pkg.Field != field {
 			return nil, errors.New(fmt.Sprintf("field mismatch at index %d", i))
 		}
+		if pkg.Optimization != opt {
+			return nil, errors.New(fmt.Sprintf("optimization mismatch at index %d", i))
+		}
+		if pkg.Provenance != prov {
+			return nil, errors.New(fmt.Sprintf("provenance mismatch at index %d", i))
+		}
 		for j, ext := range pkg.Programs {
 
 			if k, ok := pid[ext.Identity]; ok {
@@ -131,27 +272,83 @@ func MergePackages(pkgs ...CircuitPkg) (*CircuitPkg, error) {
 	return p, nil
 }
 
+// ExportAST parses the programs of the packages without compiling them
+// and returns their syntax trees as a json array, one entry per program
+func ExportAST(pkgs ...CircuitPkg) (json.RawMessage, ReportCollection, error) {
+	var (
+		ctx = &_CtxFFI{
+			ptr:     nil,
+			reports: make(ReportCollection, 0),
+		}
+		ctx_handle = cgo.NewHandle(ctx)
+	)
+	defer ctx_handle.Delete()
+
+	_pkg, err := MergePackages(pkgs...)
+	if err != nil {
+		return nil, nil, err
+	}
+
+	pkgJson, err := json.Marshal(_pkg)
+	if err != nil {
+		return nil, nil, err
+	}
+	pkgJSONStr := cstring(pkgJson)
+	C.ffi_export_ast(C.uintptr_t(ctx_handle), pkgJSONStr)
+	C.free_string(pkgJSONStr)
+
+	reports := ctx.reports.Attach(_pkg.Programs)
+	if ctx.last_buffer == nil {
+		return nil, reports, errors.New("ast export failed, check the reports")
+	}
+	return json.RawMessage(ctx.last_buffer), reports, nil
+}
+
 type CircuitLibrary interface {
 	Evaluate(inputs []byte) (Evaluation, error)
+	EvaluateWith(inputs []byte, options EvalOptions) (Evaluation, error)
+	EvaluateBatch(inputs ...[]byte) (*BatchEvaluation, error)
+	EvaluateBatchWith(options EvalOptions, inputs ...[]byte) (*BatchEvaluation, error)
+	EvaluateWitness(witness SuppliedWitness, options EvalOptions) (Evaluation, error)
+	EvaluateStream(w io.Writer, inputs []byte, options EvalOptions) error
+	EvaluateCompact(inputs []byte, options EvalOptions) (*CompactEvaluation, error)
+	StreamConstraints(w io.Writer) error
+	CompactConstraints() (*CompactConstraints, error)
+	DetectUnderconstrained(inputs []byte, options MutationOptions) ([]Counterexample, error)
+	Fuzz(options FuzzOptions) (*Fuzzing, error)
+	TemplateStatistics() ([]TemplateStatistics, error)
 	Compile(pkg ...CircuitPkg) (ReportCollection, error)
+	CompileFile(file CircuitFile) (ReportCollection, error)
 	GetReports() (ReportCollection, error)
+	ExportR1CS() ([]byte, error)
+	ExportWtns(inputs []byte) ([]byte, error)
+	ExportWasm() ([]byte, error)
+	ExportSym() (string, error)
+	Save() ([]byte, error)
+	Load(snapshot []byte) (ReportCollection, error)
+	CompileCached(dir string, pkgs ...CircuitPkg) (ReportCollection, error)
 
 	Burn()
 }
 type _CircuitLibrary struct {
 	ctx *_CtxFFI
-	mtx *sync.Mutex
+	// evaluations & exports share the compiled library (read lock),
+	// compiling & burning it is exclusive (write lock)
+	mtx *sync.RWMutex
+	// sources the labels of the evaluation reports point into
+	programs []Program
 }
 
 func NewEmptyLibrary() CircuitLibrary {
-	return &_CircuitLibrary{mtx: &sync.Mutex{}}
+	return &_CircuitLibrary{mtx: &sync.RWMutex{}}
 }
 
 func (lib *_CircuitLibrary) Compile(pkgs ...CircuitPkg) (ReportCollection, error) {
+	defer lib.mtx.Unlock()
+	lib.mtx.Lock()
 	if lib.ctx != nil {
 		return nil, errors.New("FFI Bindings exists, make sure to free them before compiling again")
 	}
-	defer lib.mtx.Unlock()
 	var (
 		ctx = &_CtxFFI{
 			ptr:       nil,
@@ -172,7 +369,6 @@ func (lib *_CircuitLibrary) Compile(pkgs ...CircuitPkg) (ReportCollection, error
 		return nil, err
 	}
 	pkgJSONStr := cstring(pkgJson)
-	lib.mtx.Lock()
 
 	// compile the circuit
 	C.ffi_compile_library(C.uintptr_t(ctx_handle), pkgJSONStr)
@@ -180,6 +376,7 @@ func (lib *_CircuitLibrary) Compile(pkgs ...CircuitPkg) (ReportCollection, error
 	C.free_string(pkgJSONStr)
 	// store the context
 	lib.ctx = ctx
+	lib.programs = _pkg.Programs
 	// return the reports
 	collection, err := lib.GetReports()
 	if err != nil {
@@ -188,44 +385,706 @@ func (lib *_CircuitLibrary) Compile(pkgs ...CircuitPkg) (ReportCollection, error
 	return collection.Attach(_pkg.Programs), nil
 }
 
+// CompileFile compiles the main declared in a .circom file on disk,
+// the labels of the reports point into the files read from disk
+func (lib *_CircuitLibrary) CompileFile(file CircuitFile) (ReportCollection, error) {
+	defer lib.mtx.Unlock()
+	lib.mtx.Lock()
+	if lib.ctx != nil {
+		return nil, errors.New("FFI Bindings exists, make sure to free them before compiling again")
+	}
+	var (
+		ctx = &_CtxFFI{
+			ptr:       nil,
+			reports:   make(ReportCollection, 0),
+			last_eval: nil,
+		}
+		ctx_handle = cgo.NewHandle(ctx)
+	)
+	defer ctx_handle.Delete()
+
+	fileJson, err := json.Marshal(file)
+	if err != nil {
+		return nil, err
+	}
+	fileJSONStr := cstring(fileJson)
+	C.ffi_compile_file(C.uintptr_t(ctx_handle), fileJSONStr)
+	C.free_string(fileJSONStr)
+
+	// store the context
+	lib.ctx = ctx
+	// the programs read from disk, shared along the library
+	var programs []Program
+	if ctx.last_buffer != nil {
+		if err := json.Unmarshal(ctx.last_buffer, &programs); err != nil {
+			return nil, err
+		}
+		ctx.last_buffer = nil
+	}
+	lib.programs = programs
+	collection, err := lib.GetReports()
+	if err != nil {
+		return nil, err
+	}
+	return collection.Attach(programs), nil
+}
+
+// call runs an ffi call on the compiled library with a context of its own,
+// so that concurrent calls don't see each other's results.
+// The reports of the call are kept in the library context as well.
+// The caller must hold the read lock.
+func (lib *_CircuitLibrary) call(fn func(ctx_handle C.uintptr_t)) *_CtxFFI {
+	return lib.callTo(nil, fn)
+}
+
+// callTo is call, with the chunks shared by the ffi written to sink
+func (lib *_CircuitLibrary) callTo(sink io.Writer, fn func(ctx_handle C.uintptr_t)) *_CtxFFI {
+	ctx := &_CtxFFI{ptr: lib.ctx.ptr, reports: make(ReportCollection, 0), sink: sink}
+	ctx_handle := cgo.NewHandle(ctx)
+	defer ctx_handle.Delete()
+
+	fn(C.uintptr_t(ctx_handle))
+
+	lib.ctx.mtx.Lock()
+	defer lib.ctx.mtx.Unlock()
+	lib.ctx.reports = append(lib.ctx.reports, ctx.reports...)
+	return ctx
+}
+
+// EvalOptions narrow down the constraints an evaluation covers
+type EvalOptions struct {
+	// stop at the first unsatisfied constraint
+	FailFast bool `json:"fail_fast"`
+	// path of a component instance, e.g. main.hasher, only the constraints
+	// generated by it & by its subcomponents are evaluated
+	Scope string `json:"scope,omitempty"`
+	// the witness calculation carries on past a === the witness breaks,
+	// which the evaluation reports unsatisfied, instead of failing there
+	SkipConstraintAsserts bool `json:"skip_constraint_asserts,omitempty"`
+}
+
 func (lib *_CircuitLibrary) Evaluate(inputs []byte) (Evaluation, error) {
+	return lib.EvaluateWith(inputs, EvalOptions{})
+}
+
+func (lib *_CircuitLibrary) EvaluateWith(inputs []byte, options EvalOptions) (Evaluation, error) {
+	optionsJson, err := json.Marshal(options)
+	if err != nil {
+		return nil, err
+	}
+
+	defer lib.mtx.RUnlock()
+	lib.mtx.RLock()
+	if lib.ctx == nil || lib.ctx.ptr == nil {
+		return nil, errors.New("FFI Bindings has not been initialized")
+	}
+
+	call := lib.call(func(ctx_handle C.uintptr_t) {
+		inputsJSONCStr := cstring(inputs)
+		optionsJSONCStr := cstring(optionsJson)
+		C.ffi_circuit_execution(ctx_handle, lib.ctx.ptr, inputsJSONCStr, optionsJSONCStr)
+		C.free_string(inputsJSONCStr)
+		C.free_string(optionsJSONCStr)
+	})
+	if call.last_eval == nil {
+		return nil, errors.New("evaluation failed, check the reports")
+	}
+	for i := range call.last_eval.Constraints {
+		if report := call.last_eval.Constraints[i].Report; report != nil {
+			report.Attach(lib.programs)
+		}
+	}
+
+	lib.ctx.mtx.Lock()
+	defer lib.ctx.mtx.Unlock()
+	lib.ctx.last_eval = call.last_eval
+	return call.last_eval, nil
+}
+
+// SuppliedWitness is a witness given instead of calculated: the full
+// witness, or signals overriding the witness calculated from the inputs.
+// Every value is reduced modulo the prime.
+type SuppliedWitness struct {
+	// inputs the witness is calculated from, as given to Evaluate
+	Inputs json.RawMessage
+	// the full witness, in wire order, the constant wire first set to 1
+	Witness []*big.Int
+	// values by symbol, e.g. main.c, over the witness of the inputs or the full witness,
+	// the symbols sharing a wire must be given the same value
+	Signals map[string]*big.Int
+}
+
+func (w SuppliedWitness) MarshalJSON() ([]byte, error) {
+	var supplied struct {
+		Inputs  json.RawMessage   `json:"inputs,omitempty"`
+		Witness []string          `json:"witness,omitempty"`
+		Signals map[string]string `json:"signals,omitempty"`
+	}
+	supplied.Inputs = w.Inputs
+	for _, value := range w.Witness {
+		supplied.Witness = append(supplied.Witness, value.String())
+	}
+	if len(w.Signals) > 0 {
+		supplied.Signals = make(map[string]string, len(w.Signals))
+		for symbol, value := range w.Signals {
+			supplied.Signals[symbol] = value.String()
+		}
+	}
+	return json.Marshal(supplied)
+}
+
+// EvaluateWitness evaluates the constraints against the supplied witness
+// rather than the one calculated from inputs
+func (lib *_CircuitLibrary) EvaluateWitness(witness SuppliedWitness, options EvalOptions) (Evaluation, error) {
+	witnessJson, err := json.Marshal(witness)
+	if err != nil {
+		return nil, err
+	}
+	optionsJson, err := json.Marshal(options)
+	if err != nil {
+		return nil, err
+	}
+
+	defer lib.mtx.RUnlock()
+	lib.mtx.RLock()
+	if lib.ctx == nil || lib.ctx.ptr == nil {
+		return nil, errors.New("FFI Bindings has not been initialized")
+	}
+
+	call := lib.call(func(ctx_handle C.uintptr_t) {
+		witnessJSONCStr := cstring(witnessJson)
+		optionsJSONCStr := cstring(optionsJson)
+		C.ffi_witness_evaluation(ctx_handle, lib.ctx.ptr, witnessJSONCStr, optionsJSONCStr)
+		C.free_string(witnessJSONCStr)
+		C.free_string(optionsJSONCStr)
+	})
+	if call.last_eval == nil {
+		return nil, errors.New("evaluation failed, check the reports")
+	}
+	for i := range call.last_eval.Constraints {
+		if report := call.last_eval.Constraints[i].Report; report != nil {
+			report.Attach(lib.programs)
+		}
+	}
+
+	lib.ctx.mtx.Lock()
+	defer lib.ctx.mtx.Unlock()
+	lib.ctx.last_eval = call.last_eval
+	return call.last_eval, nil
+}
+
+func (lib *_CircuitLibrary) EvaluateBatch(inputs ...[]byte) (*BatchEvaluation, error) {
+	return lib.EvaluateBatchWith(EvalOptions{}, inputs...)
+}
+
+func (lib *_CircuitLibrary) EvaluateBatchWith(options EvalOptions, inputs ...[]byte) (*BatchEvaluation, error) {
+	optionsJson, err := json.Marshal(options)
+	if err != nil {
+		return nil, err
+	}
+	batch := make([]json.RawMessage, len(inputs))
+	for i, input := range inputs {
+		batch[i] = json.RawMessage(input)
+	}
+	batchJson, err := json.Marshal(batch)
+	if err != nil {
+		return nil, err
+	}
+
+	buff, err := lib.exportBuffer("batch evaluation", func(ctx_handle C.uintptr_t) {
+		inputsJSONCStr := cstring(batchJson)
+		optionsJSONCStr := cstring(optionsJson)
+		C.ffi_circuit_execution_batch(ctx_handle, lib.ctx.ptr, inputsJSONCStr, optionsJSONCStr)
+		C.free_string(inputsJSONCStr)
+		C.free_string(optionsJSONCStr)
+	})
+	if err != nil {
+		return nil, err
+	}
+	evaluation := &BatchEvaluation{}
+	if err := json.Unmarshal(buff, evaluation); err != nil {
+		return nil, err
+	}
+	for i := range evaluation.Evaluations {
+		evaluation.Evaluations[i].Reports.Attach(lib.programs)
+	}
+	return evaluation, nil
+}
+
+// EvaluateStream evaluates the inputs like EvaluateWith, writing the evaluation
+// to w in the compact binary format (see circom/src/binary_export.rs)
+// as it is produced, instead of sharing it as one json document.
+// DecodeCompactEvaluation reads it back. The records only hold the values
+// of the constraints, their coefficients are shared by StreamConstraints.
+func (lib *_CircuitLibrary) EvaluateStream(w io.Writer, inputs []byte, options EvalOptions) error {
+	optionsJson, err := json.Marshal(options)
+	if err != nil {
+		return err
+	}
+
+	defer lib.mtx.RUnlock()
+	lib.mtx.RLock()
+	if lib.ctx == nil || lib.ctx.ptr == nil {
+		return errors.New("FFI Bindings has not been initialized")
+	}
+
+	call := lib.callTo(w, func(ctx_handle C.uintptr_t) {
+		inputsJSONCStr := cstring(inputs)
+		optionsJSONCStr := cstring(optionsJson)
+		C.ffi_circuit_execution_binary(ctx_handle, lib.ctx.ptr, inputsJSONCStr, optionsJSONCStr)
+		C.free_string(inputsJSONCStr)
+		C.free_string(optionsJSONCStr)
+	})
+	if call.sink_err != nil {
+		return call.sink_err
+	}
+	if call.sunk == 0 {
+		return errors.New("evaluation failed, check the reports")
+	}
+	if !call.stream.complete() {
+		return errors.New("evaluation cut short, check the reports")
+	}
+	if declared, written := call.stream.records(); declared != written {
+		return fmt.Errorf("evaluation cut short, %d of %d records written", written, declared)
+	}
+	return nil
+}
+
+// StreamConstraints writes the constraints & the symbols of the compiled
+// circuit to w in the compact binary format, the records of the compact
+// evaluations refer to them by position. They don't change between
+// evaluations, so they are only needed once per compiled circuit.
+// DecodeCompactConstraints reads them back.
+func (lib *_CircuitLibrary) StreamConstraints(w io.Writer) error {
+	defer lib.mtx.RUnlock()
+	lib.mtx.RLock()
+	if lib.ctx == nil || lib.ctx.ptr == nil {
+		return errors.New("FFI Bindings has not been initialized")
+	}
+
+	call := lib.callTo(w, func(ctx_handle C.uintptr_t) {
+		C.ffi_constraints_binary(ctx_handle, lib.ctx.ptr)
+	})
+	if call.sink_err != nil {
+		return call.sink_err
+	}
+	if call.sunk == 0 {
+		return errors.New("constraints export failed, check the reports")
+	}
+	if !call.stream.complete() {
+		return errors.New("constraints export cut short, check the reports")
+	}
+	return nil
+}
+
+// CompactConstraints shares the constraints through StreamConstraints & decodes them
+func (lib *_CircuitLibrary) CompactConstraints() (*CompactConstraints, error) {
+	var buff bytes.Buffer
+	if err := lib.StreamConstraints(&buff); err != nil {
+		return nil, err
+	}
+	return DecodeCompactConstraints(buff.Bytes())
+}
+
+// EvaluateCompact evaluates the inputs through EvaluateStream & decodes the result
+func (lib *_CircuitLibrary) EvaluateCompact(inputs []byte, options EvalOptions) (*CompactEvaluation, error) {
+	var buff bytes.Buffer
+	if err := lib.EvaluateStream(&buff, inputs, options); err != nil {
+		return nil, err
+	}
+	evaluation, err := DecodeCompactEvaluation(buff.Bytes())
+	if err != nil {
+		return nil, err
+	}
+	lib.mtx.RLock()
+	defer lib.mtx.RUnlock()
+	for _, report := range evaluation.Reports {
+		report.Attach(lib.programs)
+	}
+	return evaluation, nil
+}
+
+// MutationOptions tune the search for underconstrained signals,
+// a zero field takes the default value
+type MutationOptions struct {
+	// mutate the signals alone (1) or alone & in pairs (2, the default),
+	// other sizes are reported as VE26
+	GroupSize int `json:"group_size,omitempty"`
+	// stop once this many counterexamples are found (16 by default)
+	MaxCounterexamples int `json:"max_counterexamples,omitempty"`
+}
+
+// Counterexample is another witness of the same inputs which satisfies
+// every constraint, the signals it mutates are underconstrained
+type Counterexample struct {
+	Signals []MutatedSignal `json:"signals"`
+	Report  Report          `json:"report"`
+}
+
+type MutatedSignal struct {
+	Symbol  string `json:"symbol"`
+	Witness uint   `json:"witness"`
+	// value of the signal in the witness of the inputs
+	Value string `json:"value"`
+	// value which satisfies the constraints as well
+	Alternative string `json:"alternative"`
+}
+
+// DetectUnderconstrained calculates the witness of the inputs, which must
+// satisfy every constraint, then mutates its outputs & intermediate signals
+// alone & in pairs. Every mutated witness still satisfying the constraints
+// is returned as a counterexample.
+//
+// Only 6 alternative values are tried for each signal (value ± 1, 0, 1,
+// -value & 2 * value), so no counterexample is not a proof that the
+// constraints determine every signal.
+func (lib *_CircuitLibrary) DetectUnderconstrained(inputs []byte, options MutationOptions) ([]Counterexample, error) {
+	optionsJson, err := json.Marshal(options)
+	if err != nil {
+		return nil, err
+	}
+	buff, err := lib.exportBuffer("underconstrained signal analysis", func(ctx_handle C.uintptr_t) {
+		inputsJSONCStr := cstring(inputs)
+		optionsJSONCStr := cstring(optionsJson)
+		C.ffi_detect_underconstrained(ctx_handle, lib.ctx.ptr, inputsJSONCStr, optionsJSONCStr)
+		C.free_string(inputsJSONCStr)
+		C.free_string(optionsJSONCStr)
+	})
+	if err != nil {
+		return nil, err
+	}
+	var counterexamples []Counterexample
+	if err := json.Unmarshal(buff, &counterexamples); err != nil {
+		return nil, err
+	}
+	lib.mtx.RLock()
+	defer lib.mtx.RUnlock()
+	for i := range counterexamples {
+		counterexamples[i].Report.Attach(lib.programs)
+	}
+	return counterexamples, nil
+}
+
+// FuzzOptions tune a fuzzing session,
+// a zero field other than the seed takes the default value
+type FuzzOptions struct {
+	// the same seed draws the same inputs
+	Seed uint64 `json:"seed"`
+	// number of inputs drawn (100 by default)
+	Runs int `json:"runs,omitempty"`
+	// inclusive ranges the values of an input signal are drawn from, by name
+	Ranges map[string]FuzzRange `json:"ranges,omitempty"`
+	// stop once this many distinct failures are found (8 by default)
+	MaxFailures int `json:"max_failures,omitempty"`
+	// evaluations spent shrinking each failing input (512 by default)
+	ShrinkBudget int `json:"shrink_budget,omitempty"`
+}
+
+// FuzzRange is an inclusive range of values, in decimal
+type FuzzRange struct {
+	Min string `json:"min"`
+	Max string `json:"max"`
+}
+
+// Fuzzing holds the inputs of a fuzzing session which failed
+type Fuzzing struct {
+	Seed     uint64        `json:"seed"`
+	Runs     int           `json:"runs"`
+	Failures []FuzzFailure `json:"failures"`
+}
+
+// FuzzFailure is a failing input with its shrunk reproducer, both in the
+// json format of the inputs. Kind is "trap" when the witness calculation
+// failed, "unsatisfied" when the witness does not satisfy the constraints &
+// "rejected" when the checks of the inputs rejected them.
+type FuzzFailure struct {
+	Kind string `json:"kind"`
+	// first run that failed this way & the number of runs that did
+	Run         int              `json:"run"`
+	Occurrences int              `json:"occurrences"`
+	Input       json.RawMessage  `json:"input"`
+	Reproducer  json.RawMessage  `json:"reproducer"`
+	Reports     ReportCollection `json:"reports"`
+}
+
+// Fuzz draws inputs shaped like the input signals of the main component
+// from random field elements, boundary values & the ranges of the options,
+// & returns the ones failing the witness calculation or the constraints.
+func (lib *_CircuitLibrary) Fuzz(options FuzzOptions) (*Fuzzing, error) {
+	optionsJson, err := json.Marshal(options)
+	if err != nil {
+		return nil, err
+	}
+	buff, err := lib.exportBuffer("fuzzing", func(ctx_handle C.uintptr_t) {
+		optionsJSONCStr := cstring(optionsJson)
+		C.ffi_fuzz(ctx_handle, lib.ctx.ptr, optionsJSONCStr)
+		C.free_string(optionsJSONCStr)
+	})
+	if err != nil {
+		return nil, err
+	}
+	fuzzing := &Fuzzing{}
+	if err := json.Unmarshal(buff, fuzzing); err != nil {
+		return nil, err
+	}
+	lib.mtx.RLock()
+	defer lib.mtx.RUnlock()
+	for i := range fuzzing.Failures {
+		fuzzing.Failures[i].Reports.Attach(lib.programs)
+	}
+	return fuzzing, nil
+}
+
+// TemplateStatistics is the size of a template instance of the compiled main
+type TemplateStatistics struct {
+	// path of the instance, e.g. main.hasher
+	Path       string   `json:"path"`
+	Template   string   `json:"template"`
+	Parameters []string `json:"parameters"`
+	// node of the instance, shared by the instances of the template with the same parameters
+	Node         int `json:"node"`
+	Components   int `json:"components"`
+	TimesReached int `json:"times_reached"`
+	// counts of the instance alone & with the instances below it
+	Own        TemplateCounts `json:"own"`
+	Cumulative TemplateCounts `json:"cumulative"`
+}
+
+// TemplateCounts are the signals & the constraints of template instances,
+// as generated & as left by the simplification
+type TemplateCounts struct {
+	Signals struct {
+		Inputs        int `json:"inputs"`
+		Outputs       int `json:"outputs"`
+		Intermediates int `json:"intermediates"`
+	} `json:"signals"`
+	Generated  ConstraintCounts `json:"generated"`
+	Simplified ConstraintCounts `json:"simplified"`
+}
+
+type ConstraintCounts struct {
+	Linear    int `json:"linear"`
+	NonLinear int `json:"non_linear"`
+}
+
+// TemplateStatistics returns the statistics of the template instances of
+// the compiled main, in depth first order from the main. A library restored
+// from a snapshot, e.g. by CompileCached, has none.
+func (lib *_CircuitLibrary) TemplateStatistics() ([]TemplateStatistics, error) {
+	buff, err := lib.exportBuffer("statistics", func(ctx_handle C.uintptr_t) {
+		C.ffi_template_statistics(ctx_handle, lib.ctx.ptr)
+	})
+	if err != nil {
+		return nil, err
+	}
+	var statistics []TemplateStatistics
+	if err := json.Unmarshal(buff, &statistics); err != nil {
+		return nil, err
+	}
+	return statistics, nil
+}
+
+// exportBuffer runs an ffi export and returns the buffer it shared
+func (lib *_CircuitLibrary) exportBuffer(name string, export func(ctx_handle C.uintptr_t)) ([]byte, error) {
+	defer lib.mtx.RUnlock()
+	lib.mtx.RLock()
 	if lib.ctx == nil || lib.ctx.ptr == nil {
 		return nil, errors.New("FFI Bindings has not been initialized")
 	}
 
+	call := lib.call(export)
+	if call.last_buffer == nil {
+		return nil, errors.New(fmt.Sprintf("%s export failed, check the reports", name))
+	}
+	return call.last_buffer, nil
+}
+
+func (lib *_CircuitLibrary) ExportR1CS() ([]byte, error) {
+	return lib.exportBuffer("r1cs", func(ctx_handle C.uintptr_t) {
+		C.ffi_export_r1cs(ctx_handle, lib.ctx.ptr)
+	})
+}
+
+func (lib *_CircuitLibrary) ExportWtns(inputs []byte) ([]byte, error) {
+	return lib.exportBuffer("wtns", func(ctx_handle C.uintptr_t) {
+		inputsJSONCStr := cstring(inputs)
+		C.ffi_export_wtns(ctx_handle, lib.ctx.ptr, inputsJSONCStr)
+		C.free_string(inputsJSONCStr)
+	})
+}
+
+func (lib *_CircuitLibrary) ExportWasm() ([]byte, error) {
+	return lib.exportBuffer("wasm", func(ctx_handle C.uintptr_t) {
+		C.ffi_export_wasm(ctx_handle, lib.ctx.ptr)
+	})
+}
+
+func (lib *_CircuitLibrary) ExportSym() (string, error) {
+	sym, err := lib.exportBuffer("sym", func(ctx_handle C.uintptr_t) {
+		C.ffi_export_sym(ctx_handle, lib.ctx.ptr)
+	})
+	return string(sym), err
+}
+
+// Save returns a snapshot of the compiled library which Load restores
+// in place of compiling the pkgs again.
+// The snapshot holds native code: only load snapshots saved by the same
+// build of Veritas on the same kind of host.
+func (lib *_CircuitLibrary) Save() ([]byte, error) {
+	return lib.exportBuffer("library snapshot", func(ctx_handle C.uintptr_t) {
+		C.ffi_save_library(ctx_handle, lib.ctx.ptr)
+	})
+}
+
+func (lib *_CircuitLibrary) Load(snapshot []byte) (ReportCollection, error) {
 	defer lib.mtx.Unlock()
 	lib.mtx.Lock()
+	if lib.ctx != nil {
+		return nil, errors.New("FFI Bindings exists, make sure to free them before loading a library")
+	}
+	var (
+		ctx = &_CtxFFI{
+			ptr:       nil,
+			reports:   make(ReportCollection, 0),
+			last_eval: nil,
+		}
+		ctx_handle = cgo.NewHandle(ctx)
+	)
+	defer ctx_handle.Delete()
 
-	ctx_handle := cgo.NewHandle(lib.ctx)
+	var ptr *C.uint8_t
+	if len(snapshot) > 0 {
+		ptr = (*C.uint8_t)(unsafe.Pointer(&snapshot[0]))
+	}
+	C.ffi_load_library(C.uintptr_t(ctx_handle), ptr, C.size_t(len(snapshot)))
+	if ctx.ptr == nil {
+		return ctx.reports, errors.New("library snapshot could not be loaded, check the reports")
+	}
+	lib.ctx = ctx
+	return ctx.reports, nil
+}
+
+var snapshotFingerprint = sync.OnceValue(func() string {
+	ctx := &_CtxFFI{reports: make(ReportCollection, 0)}
+	ctx_handle := cgo.NewHandle(ctx)
 	defer ctx_handle.Delete()
+	C.ffi_snapshot_fingerprint(C.uintptr_t(ctx_handle))
+	return string(ctx.last_buffer)
+})
 
-	inputsJSONCStr := cstring(inputs)
-	C.ffi_circuit_execution(C.uintptr_t(ctx_handle), lib.ctx.ptr, inputsJSONCStr)
-	C.free_string(inputsJSONCStr)
-	return lib.GetEvaluation()
+// SnapshotFingerprint identifies the snapshots this build saves & loads:
+// their format version, the Veritas build & the wasmer engine
+func SnapshotFingerprint() string {
+	return snapshotFingerprint()
+}
+
+// CacheKey identifies the library compiled from the pkgs by this build
+func CacheKey(pkgs ...CircuitPkg) (string, error) {
+	_pkg, err := MergePackages(pkgs...)
+	if err != nil {
+		return "", err
+	}
+	pkgJson, err := json.Marshal(_pkg)
+	if err != nil {
+		return "", err
+	}
+	hash := sha256.New()
+	hash.Write([]byte(SnapshotFingerprint()))
+	hash.Write([]byte{0})
+	hash.Write(pkgJson)
+	return hex.EncodeToString(hash.Sum(nil)), nil
+}
+
+// CompileCached loads the snapshot of the pkgs cached in dir,
+// or compiles them & caches the snapshot when there is none
+// (or it was saved by another build).
+// Compilation warnings are only reported when the pkgs are compiled.
+func (lib *_CircuitLibrary) CompileCached(dir string, pkgs ...CircuitPkg) (ReportCollection, error) {
+	key, err := CacheKey(pkgs...)
+	if err != nil {
+		return nil, err
+	}
+	path := filepath.Join(dir, key+".vlib")
+	if snapshot, err := os.ReadFile(path); err == nil {
+		if reports, err := lib.Load(snapshot); err == nil {
+			_pkg, err := MergePackages(pkgs...)
+			if err != nil {
+				return reports, err
+			}
+			lib.mtx.Lock()
+			lib.programs = _pkg.Programs
+			lib.mtx.Unlock()
+			return reports, nil
+		}
+	}
+
+	reports, err := lib.Compile(pkgs...)
+	if err != nil {
+		return reports, err
+	}
+	if reports.HasErrors() {
+		// the pkgs did not compile, the reports tell why
+		return reports, nil
+	}
+	snapshot, err := lib.Save()
+	if err != nil {
+		return reports, err
+	}
+	if err := os.MkdirAll(dir, 0o755); err != nil {
+		return reports, err
+	}
+	return reports, writeFileAtomic(path, snapshot)
+}
+
+// writeFileAtomic writes through a temporary file renamed into place,
+// so concurrent writers & readers never see a partial file
+func writeFileAtomic(path string, data []byte) error {
+	tmp, err := os.CreateTemp(filepath.Dir(path), filepath.Base(path)+".*.tmp")
+	if err != nil {
+		return err
+	}
+	defer os.Remove(tmp.Name())
+	if _, err := tmp.Write(data); err != nil {
+		tmp.Close()
+		return err
+	}
+	if err := tmp.Close(); err != nil {
+		return err
+	}
+	if err := os.Chmod(tmp.Name(), 0o644); err != nil {
+		return err
+	}
+	return os.Rename(tmp.Name(), path)
 }
 
 func (lib *_CircuitLibrary) GetReports() (ReportCollection, error) {
 	if lib.ctx == nil {
 		return nil, errors.New("FFI Bindings does not exist")
 	}
-	return lib.ctx.reports, nil
+	lib.ctx.mtx.Lock()
+	defer lib.ctx.mtx.Unlock()
+	return append(ReportCollection{}, lib.ctx.reports...), nil
 }
 
 func (lib *_CircuitLibrary) GetEvaluation() (Evaluation, error) {
 	if lib.ctx == nil {
 		return nil, errors.New("FFI Bindings does not exist")
 	}
+	lib.ctx.mtx.Lock()
+	defer lib.ctx.mtx.Unlock()
 	if lib.ctx.last_eval == nil {
 		return nil, errors.New("No evaluation has been performed")
 	}
 	return lib.ctx.last_eval, nil
 }
 func (lib *_CircuitLibrary) Burn() {
+	defer lib.mtx.Unlock()
+	lib.mtx.Lock()
 	if lib.ctx != nil {
 		lib.ctx.free()
 	}
 	lib.ctx = nil
+	lib.programs = nil
 }
 
 type ReportCollection []Report
@@ -244,6 +1103,17 @@ func (c ReportCollection) String() (s string) {
 	return
 }
 
+// HasErrors tells whether a report is an error rather than a warning,
+// the severities are named as codespan names them (Error, Warning, ...)
+func (c ReportCollection) HasErrors() bool {
+	for _, r := range c {
+		if strings.EqualFold(r.Severity, "error") || strings.EqualFold(r.Severity, "bug") {
+			return true
+		}
+	}
+	return false
+}
+
 type Report struct {
 	Severity string `json:"severity"`
 	Code     string `json:"code"`
@@ -274,6 +1144,10 @@ func (*Report) Default() Report {
 
 func (r *Report) Attach(programs []Program) {
 	for i, label := range r.Labels {
+		// a loaded library does not know the programs it was compiled from
+		if label.FileId < 0 || label.FileId >= len(programs) {
+			continue
+		}
 		min_start := label.Range.Start
 		// work backwards to find the start of the line
 		for min_start > 0 &&
@@ -301,6 +1175,10 @@ func (r *Report) Detail() string {
 	header := fmt.Sprintf("%s[%s]: %s\n", r.Severity, r.Code, r.Message)
 	detail := fmt.Sprintf("\aCaught Report:\n\n%s", header)
 	for i, label := range r.Labels {
+		if label.Src == "" {
+			detail += fmt.Sprintf("\n[%d] %d:%d:%d: %s\n", i, label.FileId, label.Range.Start, label.Range.End, label.Message)
+			continue
+		}
 		msg := fmt.Sprintf("%s\n%s%s%s",
 			label.Src,
 			strings.Repeat(" ", len(label.Src)-(label.Range.End-label.Range.Start)),
@@ -322,6 +1200,10 @@ type Evaluation interface {
 	GetSymbolAssignment(sym *Symbol) *big.Int
 	SatisfiedConstraints() []uint
 	UnSatisfiedConstraints() []uint
+	UnSatisfiedReports() ReportCollection
+	Logs() []Log
+	Public() []*big.Int
+	Outputs() json.RawMessage
 	AssignWitToSym()
 	String() string
 }
@@ -330,21 +1212,74 @@ type evaluation struct {
 	Field       string   `json:"field"`
 	Assignments []string `json:"assignments"`
 	Constraints lcs      `json:"constraints"`
-	Symbols     struct {
-		Constrained   []Symbol `json:"constrained"`
-		Unconstrained []Symbol `json:"unconstrained"`
-	} `json:"symbols"`
+	Symbols     symbols  `json:"symbols"`
+	Logged      []Log    `json:"logs"`
+	// public signals as snarkjs lists them: outputs then public inputs
+	PublicSignals []string `json:"public"`
+	// outputs of the main component, keyed by name in their declared shape
+	OutputValues json.RawMessage `json:"outputs"`
+}
+
+// Log is a line written by log() during the witness calculation.
+// Component is the path of the component instance that wrote it, e.g. main.sq[0].
+type Log struct {
+	Message   string  `json:"message"`
+	Component *string `json:"component"`
+}
+
+type symbols struct {
+	Constrained   []Symbol `json:"constrained"`
+	Unconstrained []Symbol `json:"unconstrained"`
+}
+
+// BatchEvaluation holds the results of EvaluateBatch,
+// Evaluations[i] belongs to the i-th input of the batch
+type BatchEvaluation struct {
+	Field       string       `json:"field"`
+	Symbols     symbols      `json:"symbols"`
+	Evaluations []BatchEntry `json:"evaluations"`
+}
+
+type BatchEntry struct {
+	Assignments            []string        `json:"assignments"`
+	IsSatisfied            bool            `json:"satisfied"`
+	UnsatisfiedConstraints []uint          `json:"unsatisfied_constraints"`
+	Logs                   []Log           `json:"logs"`
+	Public                 []string        `json:"public"`
+	Outputs                json.RawMessage `json:"outputs"`
+	// reports of an input that could not be evaluated
+	Reports ReportCollection `json:"reports"`
 }
 
 // Keeping fields as string for now
 type lcs []lc
 type lc struct {
+	// position of the constraint in the constraint system
+	Id uint `json:"id"`
 	// witness to coefficient mapping
 	A               [][2]string `json:"a_constraints"`
 	B               [][2]string `json:"b_constraints"`
 	C               [][2]string `json:"c_constraints"`
 	Arithmetization [4]string   `json:"arithmetization"`
-	IsSatisfied     string      `json:"satisfied"`
+	IsSatisfied     bool        `json:"satisfied"`
+	// labels the statements the constraint comes from, when unsatisfied
+	Report *Report `json:"report"`
+}
+
+func (e *evaluation) Logs() []Log {
+	return e.Logged
+}
+
+func (e *evaluation) Public() []*big.Int {
+	var public = make([]*big.Int, len(e.PublicSignals))
+	for i, signal := range e.PublicSignals {
+		public[i], _ = new(big.Int).SetString(signal, 10)
+	}
+	return public
+}
+
+func (e *evaluation) Outputs() json.RawMessage {
+	return e.OutputValues
 }
 
 func (e *evaluation) ConstrainedSyms() []string {
@@ -390,18 +1325,28 @@ func (e *evaluation) GetSymbolAssignment(sym *Symbol) *big.Int {
 }
 func (e *evaluation) SatisfiedConstraints() []uint {
 	var res []uint
-	for i, lc := range e.Constraints {
-		if lc.IsSatisfied == "true" {
-			res = append(res, uint(i))
+	for _, lc := range e.Constraints {
+		if lc.IsSatisfied {
+			res = append(res, lc.Id)
 		}
 	}
 	return res
 }
 func (e *evaluation) UnSatisfiedConstraints() []uint {
 	var res []uint
-	for i, lc := range e.Constraints {
-		if lc.IsSatisfied == "false" {
-			res = append(res, uint(i))
+	for _, lc := range e.Constraints {
+		if !lc.IsSatisfied {
+			res = append(res, lc.Id)
+		}
+	}
+	return res
+}
+
+func (e *evaluation) UnSatisfiedReports() ReportCollection {
+	var res ReportCollection
+	for _, lc := range e.Constraints {
+		if lc.Report != nil {
+			res = append(res, *lc.Report)
 		}
 	}
 	return res
@@ -478,6 +1423,332 @@ func (s *Symbol) SameSym(other *Symbol) string {
 	return ""
 }
 
+// CompactEvaluation is an evaluation decoded from the compact binary format,
+// its field elements are kept as big integers instead of decimal strings
+type CompactEvaluation struct {
+	Prime       *big.Int
+	Assignments []*big.Int
+	// bit i%8 of Satisfied[i/8] is set when Records[i] is satisfied
+	Satisfied []byte
+	Records   []CompactRecord
+	// reports of the unsatisfied records, by constraint id
+	Reports       map[uint]*Report
+	PublicSignals []string        `json:"public"`
+	OutputValues  json.RawMessage `json:"outputs"`
+	Logged        []Log           `json:"logs"`
+}
+
+type CompactRecord struct {
+	// position of the constraint in CompactConstraints.Constraints
+	Id uint
+	// values of a, b, c & a * b - c
+	Arithmetization [4]*big.Int
+}
+
+// CompactConstraints are the constraints & the symbols of a compiled
+// circuit, decoded from the compact binary format
+type CompactConstraints struct {
+	Prime         *big.Int
+	Wires         uint64
+	Constraints   []CompactConstraint
+	Constrained   []Symbol
+	Unconstrained []Symbol
+}
+
+// CompactConstraint is a constraint a * b - c = 0 of linear combinations
+type CompactConstraint struct {
+	A []CompactTerm
+	B []CompactTerm
+	C []CompactTerm
+}
+
+type CompactTerm struct {
+	Wire        uint32
+	Coefficient *big.Int
+}
+
+func (e *CompactEvaluation) IsSatisfied(record int) bool {
+	return e.Satisfied[record/8]&(1<<(record%8)) != 0
+}
+
+func (e *CompactEvaluation) SatisfiedConstraints() []uint {
+	var res []uint
+	for i, record := range e.Records {
+		if e.IsSatisfied(i) {
+			res = append(res, record.Id)
+		}
+	}
+	return res
+}
+
+func (e *CompactEvaluation) UnSatisfiedConstraints() []uint {
+	var res []uint
+	for i, record := range e.Records {
+		if !e.IsSatisfied(i) {
+			res = append(res, record.Id)
+		}
+	}
+	return res
+}
+
+func (e *CompactEvaluation) UnSatisfiedReports() ReportCollection {
+	var res ReportCollection
+	for _, id := range e.UnSatisfiedConstraints() {
+		if report, ok := e.Reports[id]; ok {
+			res = append(res, *report)
+		}
+	}
+	return res
+}
+
+// compactStream follows the sections of a compact stream while its chunks
+// are written, to tell a complete stream from one cut short on the rust side
+type compactStream struct {
+	// preamble or section header being gathered
+	pending []byte
+	// sections declared by the preamble & the ones written in full
+	sections, written uint32
+	started           bool
+	// section being written & its bytes still to come
+	kind uint32
+	left uint64
+	// content of the header section & the bytes written of every section
+	header []byte
+	sizes  map[uint32]uint64
+}
+
+func (s *compactStream) follow(chunk []byte) {
+	for len(chunk) > 0 && !s.complete() {
+		if s.left > 0 {
+			n := s.left
+			if n > uint64(len(chunk)) {
+				n = uint64(len(chunk))
+			}
+			if s.kind == 1 {
+				s.header = append(s.header, chunk[:n]...)
+			}
+			s.sizes[s.kind] += n
+			s.left -= n
+			chunk = chunk[n:]
+			if s.left == 0 {
+				s.written++
+			}
+			continue
+		}
+		n := min(12-len(s.pending), len(chunk))
+		s.pending = append(s.pending, chunk[:n]...)
+		chunk = chunk[n:]
+		if len(s.pending) < 12 {
+			continue
+		}
+		if !s.started {
+			// magic, version & number of sections
+			s.started = true
+			s.sections = binary.LittleEndian.Uint32(s.pending[8:])
+			s.sizes = make(map[uint32]uint64)
+		} else {
+			s.kind, s.left = binary.LittleEndian.Uint32(s.pending), binary.LittleEndian.Uint64(s.pending[4:])
+			if s.left == 0 {
+				s.written++
+			}
+		}
+		s.pending = s.pending[:0]
+	}
+}
+
+// complete tells whether every section declared was written in full
+func (s *compactStream) complete() bool {
+	return s.started && s.written == s.sections
+}
+
+// records declared by the header of an evaluation & the ones its records
+// section holds, each the position of its constraint & 4 values
+func (s *compactStream) records() (declared, written uint64) {
+	if len(s.header) < 4 {
+		return 0, 0
+	}
+	n8 := uint64(binary.LittleEndian.Uint32(s.header))
+	if uint64(len(s.header)) < 4+n8+16 {
+		return 0, 0
+	}
+	return binary.LittleEndian.Uint64(s.header[4+n8+8:]), s.sizes[3] / (8 + 4*n8)
+}
+
+// compactReader reads the little endian fields of the compact format,
+// the first read past the end of the data sets err
+type compactReader struct {
+	data []byte
+	at   int
+	err  error
+}
+
+func (r *compactReader) bytes(n uint64) []byte {
+	if r.err != nil {
+		return nil
+	}
+	if n > uint64(len(r.data)-r.at) {
+		r.err = errors.New("truncated compact data")
+		return nil
+	}
+	b := r.data[r.at : r.at+int(n)]
+	r.at += int(n)
+	return b
+}
+
+func (r *compactReader) u32() uint32 {
+	if b := r.bytes(4); b != nil {
+		return binary.LittleEndian.Uint32(b)
+	}
+	return 0
+}
+
+func (r *compactReader) u64() uint64 {
+	if b := r.bytes(8); b != nil {
+		return binary.LittleEndian.Uint64(b)
+	}
+	return 0
+}
+
+func (r *compactReader) element(n8 uint64) *big.Int {
+	b := r.bytes(n8)
+	be := make([]byte, len(b))
+	for i := range b {
+		be[len(b)-1-i] = b[i]
+	}
+	return new(big.Int).SetBytes(be)
+}
+
+func (r *compactReader) terms(n8 uint64) []CompactTerm {
+	terms := make([]CompactTerm, 0)
+	for i, n := uint32(0), r.u32(); i < n && r.err == nil; i++ {
+		terms = append(terms, CompactTerm{Wire: r.u32(), Coefficient: r.element(n8)})
+	}
+	return terms
+}
+
+func (r *compactReader) symbols() []Symbol {
+	symbols := make([]Symbol, 0)
+	for i, n := uint64(0), r.u64(); i < n && r.err == nil; i++ {
+		original, witness, nodeId := int64(r.u64()), int64(r.u64()), int64(r.u64())
+		symbols = append(symbols, Symbol{
+			Symbol:   string(r.bytes(uint64(r.u32()))),
+			NodeID:   strconv.FormatInt(nodeId, 10),
+			Original: strconv.FormatInt(original, 10),
+			Witness:  strconv.FormatInt(witness, 10),
+		})
+	}
+	return symbols
+}
+
+// sections reads the preamble of the compact data & hands every section to
+// read, the header first, with n8 & the two counts the header declares
+func (r *compactReader) sections(magic string, version uint32, read func(kind uint32, section *compactReader, n8 uint64) error) (uint64, uint64, error) {
+	if m := r.bytes(4); r.err != nil || string(m) != magic {
+		return 0, 0, fmt.Errorf("not a compact %s", magic)
+	}
+	if v := r.u32(); v != version {
+		return 0, 0, fmt.Errorf("unsupported compact %s version %d", magic, v)
+	}
+	var n8, first, second uint64
+	for i, n := uint32(0), r.u32(); i < n && r.err == nil; i++ {
+		kind, size := r.u32(), r.u64()
+		if kind != 1 && n8 == 0 {
+			return 0, 0, fmt.Errorf("compact %s without a header", magic)
+		}
+		section := &compactReader{data: r.bytes(size)}
+		if kind == 1 {
+			n8 = uint64(section.u32())
+			section.bytes(n8)
+			first, second = section.u64(), section.u64()
+			section.at = 4
+		}
+		if err := read(kind, section, n8); err != nil {
+			return 0, 0, err
+		}
+		if section.err != nil {
+			r.err = section.err
+		}
+	}
+	return first, second, r.err
+}
+
+// DecodeCompactEvaluation decodes an evaluation written by EvaluateStream
+func DecodeCompactEvaluation(data []byte) (*CompactEvaluation, error) {
+	e := &CompactEvaluation{Reports: make(map[uint]*Report)}
+	r := &compactReader{data: data}
+	_, noRecords, err := r.sections("veva", 2, func(kind uint32, section *compactReader, n8 uint64) error {
+		switch kind {
+		case 1:
+			e.Prime = section.element(n8)
+			e.Assignments = make([]*big.Int, 0)
+		case 2:
+			for section.at < len(section.data) && section.err == nil {
+				e.Assignments = append(e.Assignments, section.element(n8))
+			}
+		case 3:
+			for section.at < len(section.data) && section.err == nil {
+				record := CompactRecord{Id: uint(section.u64())}
+				for j := range record.Arithmetization {
+					record.Arithmetization[j] = section.element(n8)
+				}
+				e.Records = append(e.Records, record)
+			}
+		case 4:
+			e.Satisfied = section.data
+		case 5:
+			var reports []struct {
+				Id     uint   `json:"id"`
+				Report Report `json:"report"`
+			}
+			if err := json.Unmarshal(section.data, &reports); err != nil {
+				return err
+			}
+			for j := range reports {
+				e.Reports[reports[j].Id] = &reports[j].Report
+			}
+		case 6:
+			return json.Unmarshal(section.data, e)
+		}
+		return nil
+	})
+	if err != nil {
+		return nil, err
+	}
+	if uint64(len(e.Records)) != noRecords || uint64(len(e.Satisfied)) < (noRecords+7)/8 {
+		return nil, errors.New("inconsistent compact evaluation")
+	}
+	return e, nil
+}
+
+// DecodeCompactConstraints decodes the constraints written by StreamConstraints
+func DecodeCompactConstraints(data []byte) (*CompactConstraints, error) {
+	cs := &CompactConstraints{}
+	r := &compactReader{data: data}
+	wires, noConstraints, err := r.sections("vcon", 1, func(kind uint32, section *compactReader, n8 uint64) error {
+		switch kind {
+		case 1:
+			cs.Prime = section.element(n8)
+		case 2:
+			for section.at < len(section.data) && section.err == nil {
+				a, b, c := section.terms(n8), section.terms(n8), section.terms(n8)
+				cs.Constraints = append(cs.Constraints, CompactConstraint{A: a, B: b, C: c})
+			}
+		case 3:
+			cs.Constrained = section.symbols()
+			cs.Unconstrained = section.symbols()
+		}
+		return nil
+	})
+	if err != nil {
+		return nil, err
+	}
+	if uint64(len(cs.Constraints)) != noConstraints {
+		return nil, errors.New("inconsistent compact constraints")
+	}
+	cs.Wires = wires
+	return cs, nil
+}
+
 func toJsonRaw(jsonBytes *C.void, bytesLen C.size_t) json.RawMessage {
 	return json.RawMessage(C.GoBytes(unsafe.Pointer(jsonBytes), C.int(bytesLen)))
 }
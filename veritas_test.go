@@ -1,8 +1,14 @@
 package veritas
 
 import (
+	"bytes"
+	"encoding/binary"
+	"encoding/json"
 	"fmt"
 	"math/big"
+	"os"
+	"path/filepath"
+	"strings"
 	"testing"
 
 	"github.com/test-go/testify/require"
@@ -60,8 +66,47 @@ var (
 			out <== 1;
 		}`,
 	}
+	// the main of a single constraint: out <== a * a
+	testSquare = `
+		template Square(){
+			signal input a;
+			signal output out;
+			out <== a * a;
+		}
+		component main = Square();`
 )
 
+// mainPkg is the pkg of a single program holding the main component
+func mainPkg(src string) CircuitPkg {
+	return CircuitPkg{
+		TargetVersion: "2.2.0",
+		Field:         "bn128",
+		Programs:      []Program{{Identity: "main", Src: src}},
+	}
+}
+
+// compileMain compiles the main program, which must compile,
+// the library is burnt once the test is done
+func compileMain(t *testing.T, src string) CircuitLibrary {
+	lib := NewEmptyLibrary()
+	t.Cleanup(lib.Burn)
+	reports, err := lib.Compile(mainPkg(src))
+	require.Nil(t, err)
+	require.False(t, reports.HasErrors(), reports.String())
+	return lib
+}
+
+// reportCodes lists the codes of the reports the library collected
+func reportCodes(t *testing.T, lib CircuitLibrary) []string {
+	reports, err := lib.GetReports()
+	require.Nil(t, err)
+	var codes []string
+	for _, report := range reports {
+		codes = append(codes, report.Code)
+	}
+	return codes
+}
+
 func Test_Compile(t *testing.T) {
 	var (
 		public_inputs = "in1"
@@ -259,3 +304,517 @@ func Test_Evaluation(t *testing.T) {
 	}
 
 }
+
+// compactSection lays out a section of the compact formats, n8 being 8
+func compactSection(kind uint32, fields ...any) []byte {
+	var content []byte
+	for _, field := range fields {
+		switch v := field.(type) {
+		case uint32:
+			content = binary.LittleEndian.AppendUint32(content, v)
+		case uint64:
+			content = binary.LittleEndian.AppendUint64(content, v)
+		case string:
+			content = append(content, v...)
+		case []byte:
+			content = append(content, v...)
+		}
+	}
+	section := binary.LittleEndian.AppendUint32(nil, kind)
+	section = binary.LittleEndian.AppendUint64(section, uint64(len(content)))
+	return append(section, content...)
+}
+
+func compactData(magic string, version uint32, sections ...[]byte) []byte {
+	data := append([]byte(magic), binary.LittleEndian.AppendUint32(nil, version)...)
+	data = binary.LittleEndian.AppendUint32(data, uint32(len(sections)))
+	return append(data, bytes.Join(sections, nil)...)
+}
+
+func Test_DecodeCompactEvaluation(t *testing.T) {
+	// in the field of 17, 3 * 3 - 9 is satisfied & 3 * 3 - 10 is not
+	data := compactData("veva", 2,
+		compactSection(1, uint32(8), uint64(17), uint64(3), uint64(2)),
+		compactSection(2, uint64(1), uint64(3), uint64(9)),
+		compactSection(3,
+			uint64(0), uint64(3), uint64(3), uint64(9), uint64(0),
+			uint64(4), uint64(3), uint64(3), uint64(10), uint64(16)),
+		compactSection(4, []byte{0b01}),
+		compactSection(5, `[{"id": 4, "report": {"message": "Constraint is not satisfied"}}]`),
+		compactSection(6, `{"public": ["9"], "outputs": {"out": "9"}, "logs": []}`),
+	)
+
+	evaluation, err := DecodeCompactEvaluation(data)
+	require.Nil(t, err)
+	require.Equal(t, big.NewInt(17), evaluation.Prime)
+	require.Equal(t, []*big.Int{big.NewInt(1), big.NewInt(3), big.NewInt(9)}, evaluation.Assignments)
+	require.Equal(t, []uint{0}, evaluation.SatisfiedConstraints())
+	require.Equal(t, []uint{4}, evaluation.UnSatisfiedConstraints())
+	require.Equal(t, big.NewInt(16), evaluation.Records[1].Arithmetization[3])
+	require.Len(t, evaluation.UnSatisfiedReports(), 1)
+	require.Equal(t, "Constraint is not satisfied", evaluation.UnSatisfiedReports()[0].Message)
+	require.Equal(t, []string{"9"}, evaluation.PublicSignals)
+
+	// the stream is followed chunk by chunk
+	var stream compactStream
+	for at := 0; at < len(data); at += 5 {
+		stream.follow(data[at:min(at+5, len(data))])
+	}
+	require.True(t, stream.complete())
+	declared, written := stream.records()
+	require.Equal(t, uint64(2), declared)
+	require.Equal(t, uint64(2), written)
+
+	// a stream cut short in the records is told apart
+	var cut compactStream
+	cut.follow(data[:len(data)-200])
+	require.False(t, cut.complete())
+
+	_, err = DecodeCompactEvaluation(data[:len(data)-1])
+	require.NotNil(t, err)
+	_, err = DecodeCompactEvaluation(compactData("veva", 1))
+	require.NotNil(t, err)
+}
+
+func Test_DecodeCompactConstraints(t *testing.T) {
+	// out * 1 - a * a, whose wires are 1 & 2
+	data := compactData("vcon", 1,
+		compactSection(1, uint32(8), uint64(17), uint64(3), uint64(1)),
+		compactSection(2,
+			uint32(1), uint32(2), uint64(1),
+			uint32(1), uint32(2), uint64(1),
+			uint32(1), uint32(1), uint64(1)),
+		compactSection(3,
+			uint64(1), uint64(1), uint64(1), uint64(0), uint32(8), "main.out",
+			uint64(0)),
+	)
+
+	cs, err := DecodeCompactConstraints(data)
+	require.Nil(t, err)
+	require.Equal(t, uint64(3), cs.Wires)
+	require.Len(t, cs.Constraints, 1)
+	require.Equal(t, []CompactTerm{{Wire: 2, Coefficient: big.NewInt(1)}}, cs.Constraints[0].A)
+	require.Equal(t, uint32(1), cs.Constraints[0].C[0].Wire)
+	require.Equal(t, "main.out", cs.Constrained[0].Symbol)
+	require.Len(t, cs.Unconstrained, 0)
+
+	_, err = DecodeCompactConstraints(data[:len(data)-3])
+	require.NotNil(t, err)
+}
+
+func Test_EvaluateStream(t *testing.T) {
+	lib := compileMain(t, `
+		template Square(){
+			signal input a;
+			signal input b;
+			signal output out;
+			out <== a * a;
+			out === b * b;
+		}
+		component main = Square();`)
+
+	cs, err := lib.CompactConstraints()
+	require.Nil(t, err)
+	require.Len(t, cs.Constraints, 2)
+
+	// the witness calculation stops at the broken ===
+	var stream bytes.Buffer
+	require.NotNil(t, lib.EvaluateStream(&stream, []byte(`{"a": 3, "b": 10}`), EvalOptions{}))
+	require.Contains(t, reportCodes(t, lib), "VE12")
+
+	// or carries on & the evaluation reports it
+	carryOn := EvalOptions{SkipConstraintAsserts: true}
+	stream.Reset()
+	require.Nil(t, lib.EvaluateStream(&stream, []byte(`{"a": 3, "b": 10}`), carryOn))
+	evaluation, err := DecodeCompactEvaluation(stream.Bytes())
+	require.Nil(t, err)
+	require.Len(t, evaluation.Records, len(cs.Constraints))
+	require.Len(t, evaluation.UnSatisfiedConstraints(), 1)
+	unsatisfied := cs.Constraints[evaluation.UnSatisfiedConstraints()[0]]
+	require.Len(t, unsatisfied.A, 1)
+
+	evaluation, err = lib.EvaluateCompact([]byte(`{"a": 3, "b": 10}`), EvalOptions{FailFast: true, SkipConstraintAsserts: true})
+	require.Nil(t, err)
+	require.Len(t, evaluation.Records, 1)
+	require.Len(t, evaluation.UnSatisfiedReports(), 1)
+
+	err = lib.EvaluateStream(&stream, []byte(`{"a": 3}`), EvalOptions{})
+	require.NotNil(t, err)
+}
+
+func Test_CompileFile(t *testing.T) {
+	var (
+		dir   = t.TempDir()
+		write = func(path, src string) string {
+			path = filepath.Join(dir, path)
+			require.Nil(t, os.MkdirAll(filepath.Dir(path), 0o755))
+			require.Nil(t, os.WriteFile(path, []byte(src), 0o644))
+			return path
+		}
+		file = func(root string) CircuitFile {
+			return CircuitFile{
+				TargetVersion: "2.2.0",
+				Field:         "bn128",
+				Root:          root,
+				Libraries:     []string{filepath.Join(dir, "lib")},
+			}
+		}
+		main = func(include string) string {
+			return fmt.Sprintf("pragma circom 2.0.0;\ninclude \"%s\";\ncomponent main = Square();\n", include)
+		}
+	)
+	write("lib/square.circom", "pragma circom 2.0.0;\n"+testSquare[:strings.Index(testSquare, "component")])
+	write("lib/broken.circom", "pragma circom 2.0.0;\ntemplate Square(){ signal output out; out <== ; }\n")
+
+	// the include is found in the library
+	lib := NewEmptyLibrary()
+	defer lib.Burn()
+	reports, err := lib.CompileFile(file(write("circuits/main.circom", main("square.circom"))))
+	require.Nil(t, err)
+	require.Len(t, reports, 0)
+	evaluation, err := lib.Evaluate([]byte(`{"a": 3}`))
+	require.Nil(t, err)
+	require.Equal(t, []*big.Int{big.NewInt(1), big.NewInt(9), big.NewInt(3)}, evaluation.WitnessAssignment())
+
+	// the labels of the reports point into the files read from disk
+	broken := NewEmptyLibrary()
+	defer broken.Burn()
+	reports, err = broken.CompileFile(file(write("circuits/broken.circom", main("broken.circom"))))
+	require.Nil(t, err)
+	require.True(t, len(reports) > 0)
+	require.True(t, strings.HasSuffix(reports[0].Labels[0].SrcID, "broken.circom"))
+	require.Contains(t, reports[0].Labels[0].Src, "<== ;")
+}
+
+func Test_CompileCached(t *testing.T) {
+	var (
+		dir = t.TempDir()
+		pkg = mainPkg(testSquare)
+	)
+	key, err := CacheKey(pkg)
+	require.Nil(t, err)
+	require.NotEmpty(t, SnapshotFingerprint())
+
+	// compiled & cached the first time, loaded from the cache afterwards
+	for i := 0; i < 2; i++ {
+		lib := NewEmptyLibrary()
+		reports, err := lib.CompileCached(dir, pkg)
+		require.Nil(t, err)
+		require.Len(t, reports, 0)
+		require.FileExists(t, filepath.Join(dir, key+".vlib"))
+
+		evaluation, err := lib.Evaluate([]byte(`{"a": 3}`))
+		require.Nil(t, err)
+		require.Equal(t, []*big.Int{big.NewInt(9)}, evaluation.Public())
+		lib.Burn()
+	}
+
+	// nothing is cached when the pkgs don't compile
+	broken := NewEmptyLibrary()
+	defer broken.Burn()
+	reports, err := broken.CompileCached(dir, mainPkg("template A( {}"))
+	require.Nil(t, err)
+	require.True(t, reports.HasErrors())
+	entries, err := os.ReadDir(dir)
+	require.Nil(t, err)
+	require.Len(t, entries, 1)
+
+	// a snapshot of another build is not loaded
+	lib := NewEmptyLibrary()
+	defer lib.Burn()
+	_, err = lib.Load([]byte("not a snapshot"))
+	require.NotNil(t, err)
+}
+
+func Test_EvalOptions(t *testing.T) {
+	lib := compileMain(t, `
+		template Cube(){
+			signal input x;
+			signal output cb;
+			signal sq <== x * x;
+			cb <== sq * x;
+		}
+		template WrongCube(){
+			signal input x;
+			signal output cb;
+			signal sq <== x * x;
+			cb <-- sq;
+			cb === sq * x;
+		}
+		template Cubes(){
+			signal input a;
+			signal input b;
+			signal output out[3];
+			component cube = Cube();
+			component wrong[2];
+			cube.x <== a;
+			out[0] <== cube.cb;
+			for (var i = 0; i < 2; i++) {
+				wrong[i] = WrongCube();
+				wrong[i].x <== i == 0 ? a : b;
+				out[i + 1] <== wrong[i].cb;
+			}
+		}
+		component main = Cubes();`)
+	var (
+		inputs   = []byte(`{"a": 3, "b": 5}`)
+		evaluate = func(options EvalOptions) (all int, unsatisfied []uint) {
+			evaluation, err := lib.EvaluateWith(inputs, options)
+			require.Nil(t, err)
+			unsatisfied = evaluation.UnSatisfiedConstraints()
+			return len(evaluation.SatisfiedConstraints()) + len(unsatisfied), unsatisfied
+		}
+	)
+
+	// the witness calculation stops at the first broken === by default
+	_, err := lib.EvaluateWith(inputs, EvalOptions{})
+	require.NotNil(t, err)
+	require.Contains(t, reportCodes(t, lib), "VE12")
+
+	all, unsatisfied := evaluate(EvalOptions{SkipConstraintAsserts: true})
+	require.Len(t, unsatisfied, 2)
+
+	// only the first unsatisfied constraint is recorded
+	failFast, first := evaluate(EvalOptions{FailFast: true, SkipConstraintAsserts: true})
+	require.Equal(t, 1, failFast)
+	require.Equal(t, unsatisfied[:1], first)
+
+	// the constraints of the satisfied component alone
+	scoped, none := evaluate(EvalOptions{Scope: "main.cube", SkipConstraintAsserts: true})
+	require.True(t, scoped > 0 && scoped < all)
+	require.Len(t, none, 0)
+	_, wrong := evaluate(EvalOptions{Scope: "main.wrong[1]", SkipConstraintAsserts: true})
+	require.Len(t, wrong, 1)
+
+	// the batch is evaluated with the same options
+	options := EvalOptions{Scope: "main.cube", SkipConstraintAsserts: true}
+	batch, err := lib.EvaluateBatchWith(options, inputs, []byte(`{"a": 2, "b": 2}`))
+	require.Nil(t, err)
+	require.Len(t, batch.Evaluations, 2)
+	for _, entry := range batch.Evaluations {
+		require.True(t, entry.IsSatisfied)
+	}
+}
+
+// mustEvaluate evaluates the inputs, which must be evaluated
+func mustEvaluate(t *testing.T, lib CircuitLibrary, inputs []byte) Evaluation {
+	evaluation, err := lib.Evaluate(inputs)
+	require.Nil(t, err)
+	return evaluation
+}
+
+func Test_EvaluateBatch(t *testing.T) {
+	lib := compileMain(t, `
+		template IsSquare(){
+			signal input a;
+			signal input b;
+			signal sqrd <== a * a;
+			sqrd === b;
+		}
+		component main = IsSquare();`)
+
+	var (
+		inputs     = [][]byte{[]byte(`{"a": 3, "b": 9}`), []byte(`{"a": 3, "b": 8}`), []byte(`{"a": 1.5}`)}
+		batch, err = lib.EvaluateBatch(inputs...)
+	)
+	require.Nil(t, err)
+	require.Len(t, batch.Evaluations, 3)
+	require.True(t, batch.Evaluations[0].IsSatisfied)
+	require.Equal(t, "3", batch.Evaluations[0].Assignments[1])
+	// the witness calculation of the second stops at the broken ===
+	require.False(t, batch.Evaluations[1].IsSatisfied)
+	require.Equal(t, "VE12", batch.Evaluations[1].Reports[0].Code)
+	require.Equal(t, "VE05", batch.Evaluations[2].Reports[0].Code)
+
+	batch, err = lib.EvaluateBatchWith(EvalOptions{SkipConstraintAsserts: true}, inputs...)
+	require.Nil(t, err)
+	require.False(t, batch.Evaluations[1].IsSatisfied)
+	require.Len(t, batch.Evaluations[1].UnsatisfiedConstraints, 1)
+}
+
+func Test_EvaluateWitness(t *testing.T) {
+	lib := compileMain(t, `
+		template Square(){
+			signal input a;
+			signal output out;
+			signal b;
+			b <== a * a;
+			out <== b * b;
+		}
+		component main = Square();`)
+	var (
+		inputs  = json.RawMessage(`{"a": 2}`)
+		witness = mustEvaluate(t, lib, inputs).WitnessAssignment()
+	)
+
+	// the honest witness, given in full, satisfies the constraints
+	supplied, err := lib.EvaluateWitness(SuppliedWitness{Witness: witness}, EvalOptions{})
+	require.Nil(t, err)
+	require.Len(t, supplied.UnSatisfiedConstraints(), 0)
+	require.Equal(t, witness, supplied.WitnessAssignment())
+
+	// b overridden breaks both constraints on it, the rest comes from the inputs
+	supplied, err = lib.EvaluateWitness(SuppliedWitness{
+		Inputs:  inputs,
+		Signals: map[string]*big.Int{"main.b": big.NewInt(5)},
+	}, EvalOptions{})
+	require.Nil(t, err)
+	require.Len(t, supplied.UnSatisfiedConstraints(), 2)
+	require.Len(t, supplied.UnSatisfiedReports(), 2)
+
+	// every wire given by symbol, the constant one aside
+	supplied, err = lib.EvaluateWitness(SuppliedWitness{
+		Signals: map[string]*big.Int{
+			"main.a":   big.NewInt(2),
+			"main.b":   big.NewInt(4),
+			"main.out": big.NewInt(-1),
+		},
+	}, EvalOptions{})
+	require.Nil(t, err)
+	require.Len(t, supplied.UnSatisfiedConstraints(), 1)
+
+	_, err = lib.EvaluateWitness(SuppliedWitness{
+		Inputs:  inputs,
+		Signals: map[string]*big.Int{"main.c": big.NewInt(1)},
+	}, EvalOptions{})
+	require.NotNil(t, err)
+	require.Contains(t, reportCodes(t, lib), "VE22")
+	_, err = lib.EvaluateWitness(SuppliedWitness{Witness: witness[:2]}, EvalOptions{})
+	require.NotNil(t, err)
+	require.Contains(t, reportCodes(t, lib), "VE23")
+}
+
+func Test_DetectUnderconstrained(t *testing.T) {
+	lib := compileMain(t, `
+		template Loose(){
+			signal input a;
+			signal output out;
+			signal b;
+			signal x;
+			signal y;
+			b <-- a * 2;
+			out <== b * b;
+			x <-- a;
+			y <-- a;
+			x + y === 2 * a;
+		}
+		component main = Loose();`)
+	found := func(options MutationOptions) [][]string {
+		counterexamples, err := lib.DetectUnderconstrained([]byte(`{"a": 3}`), options)
+		require.Nil(t, err)
+		var symbols [][]string
+		for _, counterexample := range counterexamples {
+			var mutated []string
+			for _, signal := range counterexample.Signals {
+				mutated = append(mutated, signal.Symbol)
+			}
+			symbols = append(symbols, mutated)
+		}
+		return symbols
+	}
+
+	// b is only fixed up to its sign, x & y only by their sum
+	require.Equal(t, [][]string{{"main.b"}, {"main.x", "main.y"}}, found(MutationOptions{}))
+	require.Equal(t, [][]string{{"main.b"}}, found(MutationOptions{GroupSize: 1}))
+
+	_, err := lib.DetectUnderconstrained([]byte(`{"a": 3}`), MutationOptions{GroupSize: 3})
+	require.NotNil(t, err)
+	require.Contains(t, reportCodes(t, lib), "VE26")
+}
+
+func Test_Fuzz(t *testing.T) {
+	lib := compileMain(t, `
+		template Fuzzed(){
+			signal input a;
+			signal input b[2];
+			signal output c;
+			assert(a < 1000);
+			signal sq <== b[0] * b[0];
+			c <-- b[0] < 100 ? b[0] : 0;
+			c * c === sq;
+		}
+		component main = Fuzzed();`)
+	options := FuzzOptions{Seed: 7, Runs: 10}
+	fuzzing, err := lib.Fuzz(options)
+	require.Nil(t, err)
+	reproducers := make(map[string]string)
+	for _, failure := range fuzzing.Failures {
+		reproducers[failure.Kind] = string(failure.Reproducer)
+	}
+	require.JSONEq(t, `{"a": "1000", "b": ["0", "0"]}`, reproducers["trap"])
+	require.JSONEq(t, `{"a": "0", "b": ["100", "0"]}`, reproducers["unsatisfied"])
+
+	// the seed makes the session reproducible
+	again, err := lib.Fuzz(options)
+	require.Nil(t, err)
+	require.Equal(t, fuzzing, again)
+
+	// drawn from its range, a never trips the assert
+	options.Ranges = map[string]FuzzRange{"a": {Min: "0", Max: "999"}}
+	fuzzing, err = lib.Fuzz(options)
+	require.Nil(t, err)
+	for _, failure := range fuzzing.Failures {
+		require.Equal(t, "unsatisfied", failure.Kind)
+	}
+}
+
+func Test_TemplateStatistics(t *testing.T) {
+	lib := compileMain(t, `
+		template Scaled(n){
+			signal input x;
+			signal output y;
+			signal t <== x * x;
+			y <== t * n;
+		}
+		template Sum(){
+			signal input a;
+			signal input b;
+			signal output out;
+			component s[2];
+			s[0] = Scaled(2);
+			s[1] = Scaled(3);
+			s[0].x <== a;
+			s[1].x <== b;
+			out <== s[0].y + s[1].y;
+		}
+		component main = Sum();`)
+
+	statistics, err := lib.TemplateStatistics()
+	require.Nil(t, err)
+	require.Len(t, statistics, 3)
+	require.Equal(t, "main.s[1]", statistics[2].Path)
+	require.Equal(t, []string{"3"}, statistics[2].Parameters)
+	require.Equal(t, 2, statistics[0].Own.Signals.Inputs)
+	require.Equal(t, ConstraintCounts{Linear: 3, NonLinear: 2}, statistics[0].Cumulative.Simplified)
+	require.Equal(t, statistics[1].Own, statistics[1].Cumulative)
+}
+
+func Test_Exports(t *testing.T) {
+	lib := compileMain(t, testSquare)
+
+	r1cs, err := lib.ExportR1CS()
+	require.Nil(t, err)
+	require.Equal(t, "r1cs", string(r1cs[:4]))
+	wtns, err := lib.ExportWtns([]byte(`{"a": 3}`))
+	require.Nil(t, err)
+	require.Equal(t, "wtns", string(wtns[:4]))
+	_, err = lib.ExportWtns([]byte(`{"a": "x"}`))
+	require.NotNil(t, err)
+	wasm, err := lib.ExportWasm()
+	require.Nil(t, err)
+	require.Equal(t, "\x00asm", string(wasm[:4]))
+	sym, err := lib.ExportSym()
+	require.Nil(t, err)
+	require.Equal(t, "1,1,0,main.out\n2,2,0,main.a\n", sym)
+
+	ast, reports, err := ExportAST(mainPkg(testSquare))
+	require.Nil(t, err)
+	require.Len(t, reports, 0)
+	var programs []struct {
+		Identity string `json:"identity"`
+	}
+	require.Nil(t, json.Unmarshal(ast, &programs))
+	require.Equal(t, "main", programs[0].Identity)
+	_, _, err = ExportAST(mainPkg("template A( {}"))
+	require.NotNil(t, err)
+}